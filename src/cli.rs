@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+
+use crate::commands::RowTemplate;
+use crate::ui::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "dusty")]
@@ -6,6 +9,30 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Use the database as-is without rescanning PATH (or set DUSTY_NO_SYNC=1)
+    #[arg(long, global = true)]
+    pub no_sync: bool,
+
+    /// Never run package managers or binary inspection tools; use cached data only (or set DUSTY_OFFLINE=1)
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Write the command's output (table, JSON, CSV...) to FILE instead of stdout, unstyled and unpaged (not for commands that prompt)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Print how long each phase (scan, queries, external tools, rendering) took to stderr
+    #[arg(long, global = true)]
+    pub timing: bool,
+
+    /// Page long output with dusty's built-in viewer even when $PAGER is set
+    #[arg(long, global = true)]
+    pub internal_pager: bool,
+
+    /// Show timestamps in UTC (overrides [display] timezone); JSON gets ISO-8601
+    #[arg(long, global = true)]
+    pub utc: bool,
 }
 
 #[derive(Subcommand)]
@@ -25,66 +52,55 @@ pub enum Commands {
 
     /// Show summary statistics
     Stats {
-        /// Output as JSON (for scripting/nushell)
+        /// Count only usage since the last reboot
         #[arg(long)]
-        json: bool,
-    },
+        since_boot: bool,
 
-    /// Show package usage report
-    Report {
-        /// Show only unused packages (count = 0)
-        #[arg(long)]
-        dust: bool,
-
-        /// Show packages with fewer than N uses
-        #[arg(long, value_name = "N")]
-        low: Option<u32>,
+        /// Show changes since a snapshot saved with `dusty stats --json > FILE`
+        #[arg(long, value_name = "FILE")]
+        compare: Option<String>,
 
-        /// Show packages not used in N days (e.g., --stale 30)
-        #[arg(long, value_name = "DAYS")]
-        stale: Option<u32>,
-
-        /// Filter by source (homebrew, cargo, npm, local, etc.)
-        #[arg(long, short)]
-        source: Option<String>,
+        /// Estimate how many packages will go unused (and get installed) in the next DAYS (default 30)
+        #[arg(long, value_name = "DAYS", num_args = 0..=1, default_missing_value = "30", conflicts_with = "since_boot")]
+        projection: Option<u32>,
 
-        /// Show all items (default: fits terminal height)
-        #[arg(long, short)]
-        all: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
 
-        /// Output as JSON (for scripting/nushell)
-        #[arg(long)]
+        /// Output as JSON (alias for --format json)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
-
-        /// Output uninstall commands for shell
-        #[arg(long)]
-        export: bool,
     },
 
-    /// Interactively remove unused packages
-    Clean {
-        /// Show what would be removed without removing
-        #[arg(long)]
-        dry_run: bool,
-
-        /// Include packages not used in N days
-        #[arg(long, value_name = "DAYS")]
-        stale: Option<u32>,
-
-        /// Filter by source (homebrew, cargo, npm, etc.)
-        #[arg(long, short)]
-        source: Option<String>,
+    /// Show package usage report
+    Report(ReportArgs),
 
-        /// Permanently delete instead of moving to trash
-        #[arg(long)]
-        no_trash: bool,
-    },
+    /// Interactively remove unused packages
+    Clean(CleanArgs),
 
     /// Show or edit configuration
     Config {
         /// Open config file in editor
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["reset", "default"])]
         edit: bool,
+
+        /// Back up the config to config.toml.<timestamp>.bak and regenerate defaults
+        #[arg(long, conflicts_with = "default")]
+        reset: bool,
+
+        /// Print what a freshly generated config would contain, without writing it
+        #[arg(long)]
+        default: bool,
+
+        /// Show only where the config differs from a freshly generated default
+        #[arg(long, conflicts_with_all = ["edit", "reset", "default"])]
+        diff: bool,
+
+        /// Add new default settings and newly detected sources to the config,
+        /// keeping your values and comments (old file saved to config.toml.<timestamp>.bak)
+        #[arg(long, conflicts_with_all = ["edit", "reset", "default", "diff"])]
+        migrate: bool,
     },
 
     /// Find duplicate binaries installed from different sources
@@ -97,8 +113,12 @@ pub enum Commands {
         #[arg(long, short)]
         all: bool,
 
-        /// Output as JSON (for scripting/nushell)
-        #[arg(long)]
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Output as JSON (alias for --format json)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
     },
 
@@ -108,7 +128,7 @@ pub enum Commands {
         #[arg(long)]
         orphans: bool,
 
-        /// Show dependencies for a specific binary
+        /// Show dependencies for a specific binary (full path or tracked binary name)
         #[arg(long, value_name = "BINARY")]
         binary: Option<String>,
 
@@ -116,16 +136,67 @@ pub enum Commands {
         #[arg(long)]
         refresh: bool,
 
+        /// Only report library packages from this manager (e.g. homebrew, apt)
+        #[arg(long, value_name = "NAME")]
+        manager: Option<String>,
+
+        /// Hide orphan packages smaller than this (e.g. 10M, 1.5G); they still count toward the total
+        #[arg(long, value_name = "SIZE", value_parser = crate::ui::parse_bytes, conflicts_with_all = ["binary", "graph"])]
+        min_size: Option<u64>,
+
+        /// Print the cached binary → library → package graph instead of the orphan report
+        #[arg(long, conflicts_with_all = ["orphans", "binary", "refresh"])]
+        graph: bool,
+
+        /// Graph output format: dot (Graphviz) or json
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot, requires = "graph")]
+        graph_format: GraphFormat,
+
         /// Output as JSON (for scripting/nushell)
         #[arg(long)]
         json: bool,
     },
 
+    /// Export every tracked binary with its usage and resolved library deps
+    Audit {
+        /// Force re-analysis (ignore cache)
+        #[arg(long)]
+        refresh: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Output as JSON (alias for --format json)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+    },
+
+    /// Export the packages you use as a provisioning artifact for a new machine
+    Export {
+        /// Artifact to emit
+        #[arg(long, value_enum, default_value_t = ExportFormat::Script)]
+        format: ExportFormat,
+
+        /// Include dusty packages (default: skip them; new installs within [report] grace_days stay)
+        #[arg(long)]
+        all: bool,
+
+        /// Filter by source (homebrew, cargo, npm, etc.)
+        #[arg(long, short)]
+        source: Option<String>,
+    },
+
     /// Explain why a binary is installed
     Why {
         /// Binary name to look up (e.g., "yosys")
         name: String,
 
+        /// Look up a shared library instead (path, file name like libssl.so.3,
+        /// libssl, or its package) and list the tracked binaries linking it
+        #[arg(long)]
+        lib: bool,
+
         /// Output as JSON (for scripting/nushell)
         #[arg(long)]
         json: bool,
@@ -141,8 +212,28 @@ pub enum Commands {
         #[arg(long, short)]
         source: Option<String>,
 
-        /// Output as JSON (for scripting/nushell)
+        /// Report apparent (logical) size instead of disk usage
+        #[arg(long)]
+        apparent: bool,
+
+        /// Also count library packages that only this package's binaries link against
+        #[arg(long)]
+        include_deps: bool,
+
+        /// Sort by
+        #[arg(long, value_enum, default_value_t = SortKey::Size)]
+        sort: SortKey,
+
+        /// Reverse the sort order
         #[arg(long)]
+        reverse: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Output as JSON (alias for --format json)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
     },
 
@@ -156,8 +247,20 @@ pub enum Commands {
         #[arg(long)]
         empty: bool,
 
-        /// Output as JSON (for scripting/nushell)
-        #[arg(long)]
+        /// Check receipts against the filesystem and offer to purge dangling ones
+        #[arg(long, conflicts_with_all = ["drop", "empty"])]
+        verify: bool,
+
+        /// Evict items past the [clean] trash_max_bytes / trash_max_days limits now
+        #[arg(long, conflicts_with_all = ["drop", "empty", "verify"])]
+        gc: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Output as JSON (alias for --format json)
+        #[arg(long, conflicts_with = "format")]
         json: bool,
     },
 
@@ -180,8 +283,40 @@ pub enum Commands {
     Restore {
         /// Package name to restore
         name: String,
+
+        /// Move the trashed directory here instead of back to where it came from
+        #[arg(long, value_name = "PATH")]
+        to: Option<std::path::PathBuf>,
+    },
+
+    /// Show how resolved symlink targets map to tracked binaries
+    Alias {
+        /// Rebuild the alias table from a fresh scan
+        #[arg(long)]
+        repair: bool,
+
+        /// Output as JSON (for scripting/nushell)
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Seed usage counts from shell history so reports are useful from day one
+    Import {
+        /// History to read: a zsh or bash history file, "atuin", or nothing for $HISTFILE / your shell's default
+        #[arg(long, value_name = "FILE|atuin", num_args = 0..=1, default_missing_value = "auto", required = true)]
+        from_history: Option<String>,
+
+        /// Show what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
+    /// Compact the usage database and report the space reclaimed
+    Vacuum,
+
+    /// Re-apply the configured source rules to every tracked binary (done automatically after the config file changes)
+    Recategorize,
+
     /// Show daemon logs
     Log {
         /// Number of lines to show (default: 50)
@@ -193,6 +328,13 @@ pub enum Commands {
         follow: bool,
     },
 
+    /// Print the JSON Schema of a command's `--json` output
+    Schema {
+        /// Command whose output to describe
+        #[arg(value_enum)]
+        command: SchemaCommand,
+    },
+
     /// Generate shell completions
     #[command(hide = true)]
     Completions {
@@ -201,7 +343,416 @@ pub enum Commands {
         shell: clap_complete::Shell,
     },
 
+    /// List completion candidates for a partly typed word (called by the completion scripts)
+    // Invoked as `dusty __complete`; clap_complete's bash generator can't
+    // handle `__` in a subcommand's own name
+    #[command(alias = "__complete", hide = true)]
+    Complete {
+        /// What kind of name to list
+        #[arg(value_enum)]
+        kind: CompleteKind,
+
+        /// Only list names starting with this
+        #[arg(default_value = "", allow_hyphen_values = true)]
+        prefix: String,
+    },
+
     /// Run the daemon (internal use)
     #[command(hide = true)]
-    Daemon,
+    Daemon {
+        /// Read exec events from this named pipe (created if missing) instead of the system monitor
+        #[arg(long, value_name = "FIFO")]
+        ingest: Option<std::path::PathBuf>,
+
+        /// Log to this file, rotated by size, instead of stdout/stderr (overrides [daemon] log_file)
+        #[arg(long, value_name = "FILE")]
+        log_file: Option<std::path::PathBuf>,
+    },
+}
+
+impl Commands {
+    /// The invocation, if this one prompts or opens an editor: with `--output`
+    /// its output would go to the file while the questions stay on the terminal
+    pub fn interactive_use(&self) -> Option<&'static str> {
+        match self {
+            Commands::Config { edit: true, .. } => Some("config --edit"),
+            Commands::Config { reset: true, .. } => Some("config --reset"),
+            Commands::Clean(args) if !args.dry_run => Some("clean without --dry-run"),
+            Commands::Trash { verify: true, .. } => Some("trash --verify"),
+            _ => None,
+        }
+    }
+}
+
+/// Candidates listed by `dusty __complete`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompleteKind {
+    /// Source names from the config and the database
+    Sources,
+    /// Tracked package names and binary file names
+    Names,
+}
+
+/// Commands whose JSON output `dusty schema` can describe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaCommand {
+    Report,
+    Stats,
+    Status,
+    Deps,
+    Audit,
+    Dupes,
+    Size,
+    Trash,
+    Inventory,
+    Alias,
+    Why,
+    /// The `clean --dry-run --json` plan
+    Clean,
+    /// The `clean --summary-json` rollup
+    CleanSummary,
+}
+
+/// Output format for `dusty deps --graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT, e.g. `dusty deps --graph | dot -Tsvg > deps.svg`
+    Dot,
+    /// Nodes and edges as JSON
+    Json,
+}
+
+/// Artifact emitted by `dusty export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Homebrew Brewfile for `brew bundle` (homebrew/linuxbrew packages only)
+    Brewfile,
+    /// Ansible task list with one install task per source
+    Ansible,
+    /// Shell script with one install command per source
+    Script,
+}
+
+/// How `dusty clean` removes binaries that have no uninstall command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Trash each package's whole install root directory
+    Package,
+    /// Pick individual files, grouped by their bin directory
+    BinaryDir,
+}
+
+/// What the `dusty report` table colors rows by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorBy {
+    /// Usage status: dusty red, low yellow, new cyan
+    Status,
+    /// A fixed color per source name, to pick out one manager's rows
+    Source,
+}
+
+/// Binary provenance for `dusty report --exec-source`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExecSource {
+    /// Seen executing by the daemon at least once
+    Observed,
+    /// Only discovered by scanning PATH
+    Scanned,
+}
+
+/// Row ordering for tabular package listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Most used first
+    Uses,
+    /// Largest on disk first
+    Size,
+    /// Alphabetical by package name
+    Name,
+    /// Most recently used first
+    LastUsed,
+    /// Most binaries first
+    Bins,
+    /// Grouped by source, then alphabetical
+    Source,
+    /// Highest recency-weighted score first (recent execs count more)
+    Recency,
+    /// Most uses per week since first seen
+    Rate,
+}
+
+#[derive(Args)]
+pub struct CleanArgs {
+    /// Show what would be removed without removing
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Include packages not used in N days
+    #[arg(long, value_name = "DAYS")]
+    pub stale: Option<u32>,
+
+    /// Filter by source (homebrew, cargo, npm, etc.)
+    #[arg(long, short)]
+    pub source: Option<String>,
+
+    /// Permanently delete instead of moving to trash
+    #[arg(long)]
+    pub no_trash: bool,
+
+    /// Keep the newest N versions of each package family (e.g. python@3.x).
+    /// Packages without a version in their name are always kept
+    #[arg(long, value_name = "N")]
+    pub keep_newest: Option<usize>,
+
+    /// How to group binaries that have no package manager
+    #[arg(long, value_enum, default_value_t = GroupBy::Package)]
+    pub group_by: GroupBy,
+
+    /// Skip packages with a binary under this path or matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub protect_path: Vec<String>,
+
+    /// Write a JSON receipt of every removal (commands, failures, trash paths) to FILE
+    #[arg(long, value_name = "FILE", conflicts_with = "dry_run")]
+    pub report: Option<std::path::PathBuf>,
+
+    /// Remove the selection N packages at a time, asking before each new batch
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub batch_size: Option<usize>,
+
+    /// Go on to the next --batch-size batch without asking
+    #[arg(long, short, requires = "batch_size")]
+    pub yes: bool,
+
+    /// Only offer packages from user-global or system sources
+    #[arg(long, value_enum)]
+    pub scope: Option<crate::config::SourceScope>,
+
+    /// Print the --dry-run plan as JSON
+    #[arg(long, requires = "dry_run")]
+    pub json: bool,
+
+    /// Shell command to run after removing at least one package (overrides [clean] on_success)
+    #[arg(long, value_name = "CMD", conflicts_with = "dry_run")]
+    pub on_success: Option<String>,
+
+    /// Remove every installed version of each selected package, not just the unused ones
+    #[arg(long, requires = "source", conflicts_with_all = ["keep_newest", "batch_size", "json"])]
+    pub all_versions: bool,
+
+    /// Remove install directories even when active binaries elsewhere link libraries inside them
+    #[arg(long)]
+    pub no_protect_deps: bool,
+
+    /// Send human output to stderr and end with a one-line JSON summary on stdout
+    #[arg(long, conflicts_with = "dry_run")]
+    pub summary_json: bool,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Show only unused packages (count = 0)
+    #[arg(long)]
+    pub dust: bool,
+
+    /// Show packages with fewer than N uses
+    #[arg(long, value_name = "N")]
+    pub low: Option<u32>,
+
+    /// Show packages not used in N days (e.g., --stale 30)
+    #[arg(long, value_name = "DAYS")]
+    pub stale: Option<u32>,
+
+    /// Show packages used within the last N days (the opposite of --stale)
+    #[arg(long, value_name = "DAYS")]
+    pub active_in: Option<u32>,
+
+    /// Show packages used less than this binary or package (e.g., --less-used-than git)
+    #[arg(long, value_name = "NAME")]
+    pub less_used_than: Option<String>,
+
+    /// Show packages used more than this binary or package
+    #[arg(long, value_name = "NAME")]
+    pub more_used_than: Option<String>,
+
+    /// Show packages that install at least N binaries
+    #[arg(long, value_name = "N")]
+    pub min_binaries: Option<usize>,
+
+    /// Show packages that install at most N binaries
+    #[arg(long, value_name = "N")]
+    pub max_binaries: Option<usize>,
+
+    /// Never-run packages first seen within N days are "new", not dusty (default: [report] grace_days)
+    #[arg(long, value_name = "N")]
+    pub grace_days: Option<u32>,
+
+    /// Show only packages used since the last reboot
+    #[arg(long)]
+    pub since_boot: bool,
+
+    /// Filter by source (homebrew, cargo, npm, local, etc.)
+    #[arg(long, short)]
+    pub source: Option<String>,
+
+    /// Widen name columns to fit the longest name instead of cutting names short
+    #[arg(long)]
+    pub no_truncate_names: bool,
+
+    /// Fold versioned packages (python@3.12, python@3.13) into one row per base name
+    #[arg(long, conflicts_with_all = ["explain", "export", "names_only", "since_last", "update_baseline"])]
+    pub merge_versions: bool,
+
+    /// Show packages whose name, or any binary's path, matches this regex
+    #[arg(long, value_name = "REGEX", value_parser = regex::Regex::new)]
+    pub find: Option<regex::Regex>,
+
+    /// Show only binaries the daemon has seen run, or only ones known from PATH scans
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub exec_source: Option<ExecSource>,
+
+    /// Show all items (default: fits terminal height)
+    #[arg(long, short)]
+    pub all: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+
+    /// Output as JSON (alias for --format json)
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// Output uninstall commands for shell
+    #[arg(long)]
+    pub export: bool,
+
+    /// Print one package name per line (no table or summary), for piping
+    #[arg(long, conflicts_with_all = ["format", "json", "export", "explain"])]
+    pub names_only: bool,
+
+    /// Print one line per package from a template, e.g. "{name}\t{uses}" (placeholders:
+    /// name, source, uses, bins, last_used, path, status; `\t`, `\n`, and `{{`/`}}` for braces)
+    #[arg(long, alias = "output-template", value_name = "FMT", value_parser = RowTemplate::parse, conflicts_with_all = ["format", "json", "export", "explain", "names_only"])]
+    pub template: Option<RowTemplate>,
+
+    /// Show last-used times as "3d ago" instead of a date
+    #[arg(long)]
+    pub relative_time: bool,
+
+    /// Add a disk size column (computed only for displayed rows)
+    #[arg(long)]
+    pub with_size: bool,
+
+    /// Add a uses-per-week column, normalizing counts by how long each package has been around
+    #[arg(long)]
+    pub rate: bool,
+
+    /// Sort rows by this key (size implies --with-size; also spelled --rank)
+    #[arg(long, visible_alias = "rank", value_enum, default_value_t = SortKey::Uses)]
+    pub sort: SortKey,
+
+    /// Append a per-source count of the shown rows (`source_counts` in JSON)
+    #[arg(long)]
+    pub source_summary_footer: bool,
+
+    /// Group table rows under a header per source, each with its own totals
+    #[arg(long)]
+    pub source_first: bool,
+
+    /// Color table rows by usage status or by source
+    #[arg(long, value_enum, default_value_t = ColorBy::Status)]
+    pub color_by: ColorBy,
+
+    /// Show binaries hidden by ignore_binaries, tagged with the matching pattern
+    #[arg(long)]
+    pub include_ignored: bool,
+
+    /// Explain why a binary or package is shown or hidden
+    #[arg(long, value_name = "NAME")]
+    pub explain: Option<String>,
+
+    /// Show what changed since the last interactive report instead of the report
+    #[arg(long, conflicts_with_all = ["explain", "export", "names_only", "template"])]
+    pub since_last: bool,
+
+    /// Show active binaries that alone keep library packages installed
+    #[arg(long, conflicts_with_all = ["explain", "export", "since_last", "names_only", "template"])]
+    pub unused_deps: bool,
+
+    /// Group uses by the command name typed (argv[0]), e.g. `vi` running nvim
+    #[arg(long, conflicts_with_all = ["explain", "export", "since_last", "unused_deps", "names_only", "merge_versions", "template"])]
+    pub by_invocation: bool,
+
+    /// Save this run as the --since-last baseline even when output is JSON or piped
+    #[arg(long)]
+    pub update_baseline: bool,
+
+    /// Exit non-zero after the report if more than N matching packages are dusty (for CI)
+    #[arg(long, value_name = "N", conflicts_with_all = ["explain", "since_last", "unused_deps", "by_invocation"])]
+    pub fail_on_dusty: Option<usize>,
+
+    /// Exit non-zero after the report if more than N packages are unused for DAYS (e.g. 90,10)
+    #[arg(long, value_name = "DAYS,N", value_parser = parse_fail_on_stale, conflicts_with_all = ["explain", "since_last", "unused_deps", "by_invocation"])]
+    pub fail_on_stale: Option<(u32, usize)>,
+}
+
+/// `--fail-on-stale DAYS,N`
+fn parse_fail_on_stale(value: &str) -> Result<(u32, usize), String> {
+    let (days, max) = value
+        .split_once(',')
+        .ok_or_else(|| "expected DAYS,N (e.g. 90,10)".to_string())?;
+    let days = days
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number of days '{}'", days))?;
+    let max = max
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid package count '{}'", max))?;
+    Ok((days, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn interactive_use(args: &[&str]) -> Option<&'static str> {
+        let cli = Cli::try_parse_from(["dusty"].iter().chain(args)).unwrap();
+        cli.command.interactive_use()
+    }
+
+    #[test]
+    fn test_batch_size_must_be_positive() {
+        assert!(Cli::try_parse_from(["dusty", "clean", "--batch-size", "0"]).is_err());
+        assert!(Cli::try_parse_from(["dusty", "clean", "--batch-size", "1"]).is_ok());
+    }
+
+    #[test]
+    fn test_output_refused_for_prompting_commands() {
+        assert_eq!(
+            interactive_use(&["config", "--edit"]),
+            Some("config --edit")
+        );
+        assert_eq!(
+            interactive_use(&["config", "--reset"]),
+            Some("config --reset")
+        );
+        assert!(interactive_use(&["clean"]).is_some());
+        assert!(interactive_use(&["clean", "--yes", "--batch-size", "5"]).is_some());
+        assert!(interactive_use(&["trash", "--verify"]).is_some());
+
+        assert_eq!(interactive_use(&["clean", "--dry-run", "--json"]), None);
+        assert_eq!(interactive_use(&["config", "--diff"]), None);
+        assert_eq!(interactive_use(&["trash", "--json"]), None);
+        assert_eq!(interactive_use(&["report", "--format", "csv"]), None);
+    }
+
+    #[test]
+    fn test_by_invocation_refuses_template() {
+        let args = ["dusty", "report", "--by-invocation", "--template", "{name}"];
+        assert!(Cli::try_parse_from(args).is_err());
+        assert!(Cli::try_parse_from(["dusty", "report", "--by-invocation"]).is_ok());
+    }
 }
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use console::style;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::package::scan_all_binaries;
+use crate::storage::Database;
+use crate::ui::{print_json, shorten_path};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct AliasJson {
+    alias_path: String,
+    canonical_path: String,
+    /// False when the canonical path is no longer a tracked binary
+    canonical_tracked: bool,
+}
+
+pub fn cmd_alias(repair: bool, json: bool) -> Result<()> {
+    let db = Database::open()?;
+
+    if repair {
+        return repair_aliases(&db, json);
+    }
+
+    // Show the table as stored (no sync), so stale mappings stay visible
+    let tracked: HashSet<String> = db.get_all_binaries()?.into_iter().map(|b| b.path).collect();
+    let rows: Vec<AliasJson> = db
+        .get_all_aliases()?
+        .into_iter()
+        .map(|(alias_path, canonical_path)| AliasJson {
+            canonical_tracked: tracked.contains(&canonical_path),
+            alias_path,
+            canonical_path,
+        })
+        .collect();
+
+    if json {
+        print_json(&rows)?;
+        return Ok(());
+    }
+
+    println!();
+    if rows.is_empty() {
+        println!("  {} No path aliases recorded", style("◦").dim());
+        println!();
+        return Ok(());
+    }
+
+    println!(
+        "  {:<50}    {}",
+        style("Resolved path").bold().underlined(),
+        style("Counted as").bold().underlined()
+    );
+    println!();
+
+    for row in &rows {
+        let canonical = if row.canonical_tracked {
+            style(shorten_path(&row.canonical_path)).to_string()
+        } else {
+            format!(
+                "{} {}",
+                style(shorten_path(&row.canonical_path)).red(),
+                style("(not tracked)").red().dim()
+            )
+        };
+        println!(
+            "  {:<50} {} {}",
+            shorten_path(&row.alias_path),
+            style("→").dim(),
+            canonical
+        );
+    }
+
+    let broken = rows.iter().filter(|r| !r.canonical_tracked).count();
+    println!();
+    println!("  {} {} aliases", style("●").green(), rows.len());
+    if broken > 0 {
+        println!(
+            "  {} {} point at untracked paths (fix with {})",
+            style("!").yellow(),
+            broken,
+            style("dusty alias --repair").cyan()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// Rebuild path_aliases from scratch using the symlink resolution done during a scan
+fn repair_aliases(db: &Database, json: bool) -> Result<()> {
+    let before = db.get_all_aliases()?.len();
+
+    let scanned = scan_all_binaries()?;
+    db.clear_aliases()?;
+    let mut after = 0;
+    for (bin_path, _, _, resolved) in &scanned {
+        if let Some(resolved_path) = resolved {
+            db.register_alias(resolved_path, bin_path)?;
+            after += 1;
+        }
+    }
+
+    if json {
+        #[derive(Serialize)]
+        struct RepairJson {
+            aliases_before: usize,
+            aliases_after: usize,
+        }
+        print_json(&RepairJson {
+            aliases_before: before,
+            aliases_after: after,
+        })?;
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {} Rebuilt path aliases from a fresh scan ({} before, {} now)",
+        style("●").green(),
+        before,
+        after
+    );
+    println!();
+    Ok(())
+}
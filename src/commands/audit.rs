@@ -0,0 +1,116 @@
+use anyhow::Result;
+use console::style;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::deps::{self, ResolvedLib};
+use crate::storage::Database;
+use crate::ui::{OutputFormat, Spinner, Timer, emit};
+use crate::utils::{detect_install_roots, json_timestamp, sync_binaries};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct AuditEntry {
+    path: String,
+    package_name: Option<String>,
+    source: Option<String>,
+    count: i64,
+    first_seen: Option<String>,
+    last_seen: Option<String>,
+    install_root: Option<String>,
+    /// "dynamic", "static", "script", or "failed"
+    linkage: Option<String>,
+    libs: Vec<ResolvedLib>,
+}
+
+pub fn cmd_audit(refresh: bool, format: OutputFormat) -> Result<()> {
+    let db = Database::open()?;
+    sync_binaries(&db)?;
+    let binaries = db.get_all_binaries()?;
+
+    // Reuses the `dusty deps` cache: only new or changed binaries are analyzed
+    let spinner = RefCell::new(Spinner::new());
+    let timer = Timer::start("dep analysis");
+    deps::update_dep_cache(
+        &db,
+        &binaries,
+        refresh,
+        Some(&|current, total| {
+            spinner
+                .borrow_mut()
+                .update("Analyzing binaries", current + 1, total)
+        }),
+    )?;
+    spinner.into_inner().finish();
+    drop(timer);
+
+    let mut dylib_deps = db.get_all_dylib_deps()?;
+    let mut linkage = db.get_all_linkage()?;
+    let lib_packages: HashMap<String, (String, String)> = db
+        .get_all_lib_packages()?
+        .into_iter()
+        .map(|(lib_path, manager, pkg)| (lib_path, (manager, pkg)))
+        .collect();
+
+    let timestamp = |ts: Option<i64>| ts.map(json_timestamp);
+
+    let entries: Vec<AuditEntry> = binaries
+        .into_iter()
+        .map(|b| {
+            let libs = dylib_deps
+                .remove(&b.path)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lib_path| {
+                    let resolved = lib_packages.get(&lib_path);
+                    ResolvedLib {
+                        package_name: resolved.map(|(_, pkg)| pkg.clone()),
+                        manager: resolved.map(|(mgr, _)| mgr.clone()),
+                        lib_path,
+                    }
+                })
+                .collect();
+            AuditEntry {
+                install_root: detect_install_roots(&[b.path.as_str()]).into_iter().next(),
+                linkage: linkage.remove(&b.path),
+                first_seen: timestamp(b.first_seen),
+                last_seen: timestamp(b.last_seen),
+                path: b.path,
+                package_name: b.package_name,
+                source: b.source,
+                count: b.count,
+                libs,
+            }
+        })
+        .collect();
+
+    if !format.is_table() {
+        return emit(&entries, format);
+    }
+
+    let total_libs: usize = entries.iter().map(|e| e.libs.len()).sum();
+    let unresolved: usize = entries
+        .iter()
+        .flat_map(|e| &e.libs)
+        .filter(|l| l.package_name.is_none())
+        .count();
+
+    println!();
+    println!(
+        "  {} {} binaries, {} library links ({} unresolved)",
+        style("●").green(),
+        style(entries.len()).bold(),
+        total_libs,
+        unresolved
+    );
+    println!(
+        "  {} Export the full inventory with {} or {}",
+        style("◦").dim(),
+        style("dusty audit --json").cyan(),
+        style("--format ndjson").cyan()
+    );
+    println!();
+
+    Ok(())
+}
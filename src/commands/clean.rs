@@ -1,13 +1,186 @@
 use anyhow::{Context, Result};
 use console::style;
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::config;
+use super::size::batch_dir_sizes;
+use super::trash::gc_trash;
+use crate::cli::{CleanArgs, GroupBy};
+use crate::config::{self, SourceScope};
 use crate::defaults;
+use crate::deps;
+use crate::package;
+use crate::platform;
 use crate::storage::{self, Database};
-use crate::ui::{print_with_pager, terminal_fit};
-use crate::utils::{detect_install_roots, sync_binaries};
+use crate::ui::{
+    is_dumb_terminal, print_json, print_with_pager, shorten_path, stdout_to_stderr, styled_stdout,
+    terminal_fit, with_schema_version,
+};
+use crate::utils::{detect_install_roots, display_now, du_sizes, format_timestamp, sync_binaries};
+
+/// One removal attempted by `dusty clean`, as written to `--report`
+#[derive(Serialize)]
+struct CleanAction {
+    package: String,
+    source: String,
+    /// "pm" (package manager), "moved" (to dusty's trash) or "rm" (deleted)
+    method: &'static str,
+    path: Option<String>,
+    command: Option<String>,
+    success: bool,
+    error: Option<String>,
+    trash_path: Option<String>,
+    restore_cmd: Option<String>,
+}
+
+impl CleanAction {
+    fn new(package: &str, source: &str, method: &'static str) -> Self {
+        Self {
+            package: package.to_string(),
+            source: source.to_string(),
+            method,
+            path: None,
+            command: None,
+            success: false,
+            error: None,
+            trash_path: None,
+            restore_cmd: None,
+        }
+    }
+}
+
+/// The `--summary-json` rollup of a clean run
+#[derive(Serialize, JsonSchema)]
+pub(super) struct CleanSummary {
+    removed: usize,
+    failed: usize,
+    /// Removals that went to dusty's trash (and can be restored)
+    trashed: usize,
+    /// Sources with at least one successful removal
+    sources: Vec<String>,
+    freed_bytes: u64,
+}
+
+/// Collects every action of a clean run and writes them to the `--report` file
+/// on drop, so the receipt still lands when the run bails out partway.
+/// With `--summary-json` the rollup is printed on drop as well.
+struct CleanReceipt {
+    path: Option<PathBuf>,
+    /// The real stdout, kept aside while human output goes to stderr
+    summary: Option<std::fs::File>,
+    /// Sizes of the selected packages, measured before anything is removed
+    sizes: HashMap<(String, String), Option<u64>>,
+    actions: Vec<CleanAction>,
+    /// Trash paths of this run's moves, spared by the eviction at the end
+    trashed: Vec<String>,
+    /// Sources with at least one successful removal (autoremove hints, the
+    /// on_success hook's `DUSTY_CLEANED_SOURCES`)
+    cleaned_sources: BTreeSet<String>,
+    /// How `--summary-json` measures what went to the trash
+    size_config: config::SizeConfig,
+}
+
+impl CleanReceipt {
+    fn new(
+        path: Option<PathBuf>,
+        summary: Option<std::fs::File>,
+        size_config: config::SizeConfig,
+    ) -> Self {
+        Self {
+            path,
+            summary,
+            sizes: HashMap::new(),
+            actions: Vec::new(),
+            trashed: Vec::new(),
+            cleaned_sources: BTreeSet::new(),
+            size_config,
+        }
+    }
+
+    fn push(&mut self, action: CleanAction) {
+        if action.success {
+            self.cleaned_sources.insert(action.source.clone());
+            if let Some(trash_path) = &action.trash_path {
+                self.trashed.push(trash_path.clone());
+            }
+        }
+        if self.path.is_some() || self.summary.is_some() {
+            self.actions.push(action);
+        }
+    }
+
+    fn summarize(&self) -> CleanSummary {
+        let done: Vec<&CleanAction> = self.actions.iter().filter(|a| a.success).collect();
+
+        // Packages count once, at the size measured up front; anything else
+        // that landed in the trash (single files, version directories) is
+        // measured there
+        let mut counted = HashSet::new();
+        let mut freed = 0;
+        let mut unmeasured = Vec::new();
+        for a in &done {
+            let key = (a.source.clone(), a.package.clone());
+            match self.sizes.get(&key) {
+                Some(size) => {
+                    if counted.insert(key) {
+                        freed += size.unwrap_or(0);
+                    }
+                }
+                None => unmeasured.extend(a.trash_path.as_deref()),
+            }
+        }
+        freed += du_sizes(&unmeasured, &self.size_config)
+            .values()
+            .sum::<u64>();
+
+        let mut sources: Vec<String> = done.iter().map(|a| a.source.clone()).collect();
+        sources.sort();
+        sources.dedup();
+        CleanSummary {
+            removed: done.len(),
+            failed: self.actions.len() - done.len(),
+            trashed: done.iter().filter(|a| a.method == "moved").count(),
+            sources,
+            freed_bytes: freed,
+        }
+    }
+
+    fn write(&self, path: &std::path::Path) -> Result<()> {
+        let removed = self.actions.iter().filter(|a| a.success).count();
+        let json = serde_json::json!({
+            "generated_at": display_now().to_rfc3339(),
+            "removed": removed,
+            "failed": self.actions.len() - removed,
+            "actions": self.actions,
+        });
+        std::fs::write(
+            path,
+            serde_json::to_string_pretty(&with_schema_version(json))? + "\n",
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl Drop for CleanReceipt {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path
+            && let Err(e) = self.write(path)
+        {
+            eprintln!("  {} {:#}", style("●").red(), e);
+        }
+        if let Some(mut out) = self.summary.take() {
+            use std::io::Write;
+
+            let summary =
+                with_schema_version(serde_json::to_value(self.summarize()).unwrap_or_default());
+            std::io::stdout().flush().ok();
+            writeln!(out, "{}", summary).ok();
+        }
+    }
+}
 
 /// A group of binaries belonging to the same (source, package) pair
 struct PackageGroup {
@@ -77,7 +250,7 @@ fn build_package_groups(
                 .and_then(|n| n.to_str())
                 .unwrap_or("");
 
-            if config.should_ignore_binary(binary_name) {
+            if config.should_ignore_binary(binary_name, b.source.as_deref()) {
                 return false;
             }
 
@@ -88,9 +261,14 @@ fn build_package_groups(
                 return false;
             }
 
-            // Include if dusty
+            // Read-only sources are reported but never offered for cleanup
+            if config.is_read_only(b.source.as_deref().unwrap_or("other")) {
+                return false;
+            }
+
+            // Include if dusty, unless it was only just installed
             if b.count == 0 {
-                return true;
+                return !b.is_new(now, config.report.grace_days);
             }
 
             // Include if stale
@@ -108,15 +286,7 @@ fn build_package_groups(
     // Group by (source, package_name)
     let mut groups: HashMap<(String, String), Vec<storage::BinaryRecord>> = HashMap::new();
     for b in filtered {
-        let source = b.source.clone().unwrap_or_else(|| "other".to_string());
-        let pkg = b.package_name.clone().unwrap_or_else(|| {
-            std::path::Path::new(&b.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
-        groups.entry((source, pkg)).or_default().push(b);
+        groups.entry(b.package_key()).or_default().push(b);
     }
 
     let mut result: Vec<PackageGroup> = groups
@@ -136,12 +306,100 @@ fn build_package_groups(
     result
 }
 
-pub fn cmd_clean(
-    dry_run: bool,
-    stale: Option<u32>,
-    source_filter: Option<String>,
-    no_trash: bool,
-) -> Result<()> {
+/// Drop the newest `keep` installs of each version family (e.g. python@3.11/3.12/3.13)
+/// from the cleanup candidates. Families are ranked across all installs, not just
+/// the dusty ones, so an active newer version still counts toward `keep`.
+/// Versions come from package names only: a package without one (pyenv's
+/// versions all file under one install root) is a family of one and is kept.
+fn apply_keep_newest(
+    groups: Vec<PackageGroup>,
+    all_binaries: &[storage::BinaryRecord],
+    keep: usize,
+) -> Vec<PackageGroup> {
+    // Newest binary mtime per (source, package), used when versions tie or are missing
+    let mut installs: HashMap<(String, String), i64> = HashMap::new();
+    for b in all_binaries {
+        let Some(pkg) = b.package_name.clone() else {
+            continue;
+        };
+        let source = b.source.clone().unwrap_or_else(|| "other".to_string());
+        let mtime = std::fs::metadata(&b.path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let entry = installs.entry((source, pkg)).or_insert(0);
+        *entry = (*entry).max(mtime);
+    }
+
+    type Install = (String, Option<Vec<u64>>, i64);
+    let mut families: HashMap<(String, String), Vec<Install>> = HashMap::new();
+    for ((source, pkg), mtime) in installs {
+        let (base, version) = package::split_version(&pkg);
+        families
+            .entry((source, base.to_string()))
+            .or_default()
+            .push((pkg.clone(), version, mtime));
+    }
+
+    let mut kept: std::collections::HashSet<(String, String)> = std::collections::HashSet::new();
+    for ((source, _), mut members) in families {
+        members.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)));
+        for (pkg, _, _) in members.into_iter().take(keep) {
+            kept.insert((source.clone(), pkg));
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|g| !kept.contains(&(g.source.clone(), g.package_name.clone())))
+        .collect()
+}
+
+/// `--protect-path` matching: globs go through `glob_match`, and a plain path
+/// protects everything beneath it
+fn is_protected(pattern: &str, path: &str) -> bool {
+    if pattern.contains(['*', '?']) {
+        config::glob_match(pattern, path)
+    } else {
+        std::path::Path::new(path).starts_with(pattern)
+    }
+}
+
+/// Reject names with shell metacharacters before they reach an uninstall command.
+/// npm scopes ("@scope/pkg") are the only names allowed a slash.
+pub(super) fn is_safe_package_name(name: &str) -> bool {
+    let safe = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_alphanumeric() || "-_.@+".contains(c))
+    };
+    match name.strip_prefix('@').and_then(|rest| rest.split_once('/')) {
+        Some((scope, pkg)) => safe(scope) && safe(pkg),
+        None => safe(name),
+    }
+}
+
+pub fn cmd_clean(args: CleanArgs) -> Result<()> {
+    let CleanArgs {
+        dry_run,
+        stale,
+        source: source_filter,
+        no_trash,
+        keep_newest,
+        group_by,
+        protect_path: protect_paths,
+        report,
+        batch_size,
+        yes,
+        scope,
+        json,
+        on_success,
+        all_versions,
+        no_protect_deps,
+        summary_json,
+    } = args;
     use dialoguer::{Confirm, MultiSelect, theme::ColorfulTheme};
 
     let theme = ColorfulTheme {
@@ -151,12 +409,34 @@ pub fn cmd_clean(
         ..ColorfulTheme::default()
     };
 
-    let has_filter = stale.is_some() || source_filter.is_some();
+    if platform::is_offline() && !dry_run {
+        anyhow::bail!("dusty clean runs package managers; use --dry-run with --offline");
+    }
+
+    let has_filter = stale.is_some() || source_filter.is_some() || scope.is_some();
 
     let db = Database::open()?;
     let config = config::Config::load()?;
+
+    if let Some(sf) = source_filter.as_deref()
+        && config.is_read_only(sf)
+    {
+        anyhow::bail!(
+            "source '{}' is marked read_only in {}; dusty will not clean it",
+            sf,
+            config::Config::config_path()?.display()
+        );
+    }
+
     sync_binaries(&db)?;
 
+    // Everything but the final summary line goes to stderr
+    let mut receipt = CleanReceipt::new(
+        report,
+        summary_json.then(stdout_to_stderr).transpose()?,
+        config.size.clone(),
+    );
+
     let binaries = db.get_all_binaries()?;
 
     // Without a filter, show a summary and ask user to narrow down
@@ -175,7 +455,7 @@ pub fn cmd_clean(
             *by_source.entry(g.source.as_str()).or_default() += 1;
         }
         let mut sources: Vec<_> = by_source.into_iter().collect();
-        sources.sort_by(|a, b| b.1.cmp(&a.1));
+        sources.sort_by_key(|s| std::cmp::Reverse(s.1));
 
         println!();
         println!(
@@ -210,13 +490,71 @@ pub fn cmd_clean(
         return Ok(());
     }
 
-    let groups = build_package_groups(binaries, stale, source_filter.as_deref(), &config);
+    // Arrow-key prompts hang or garble without cursor control
+    if !dry_run && is_dumb_terminal() {
+        anyhow::bail!(
+            "interactive cleanup needs a full terminal (TERM=dumb); preview with `dusty clean --dry-run`"
+        );
+    }
+
+    let all_binaries = binaries.clone();
+    let mut groups = build_package_groups(binaries, stale, source_filter.as_deref(), &config);
+    let had_candidates = !groups.is_empty();
+    if let Some(keep) = keep_newest {
+        groups = apply_keep_newest(groups, &all_binaries, keep);
+    }
+
+    if let Some(scope) = scope {
+        groups.retain(|g| config.source_scope(&g.source) == Some(scope));
+    }
+
+    // One-shot exclusions: a group is protected if any of its binaries match
+    if !protect_paths.is_empty() {
+        let before = groups.len();
+        groups.retain(|g| {
+            !g.binaries
+                .iter()
+                .any(|b| protect_paths.iter().any(|p| is_protected(p, &b.path)))
+        });
+        if groups.len() < before && !json {
+            println!();
+            println!(
+                "  {} {} packages protected by --protect-path",
+                style("◦").dim(),
+                before - groups.len()
+            );
+        }
+    }
+
+    // `--dry-run --json`: the plan as data, nothing else on stdout
+    if json {
+        return print_plan(&groups, &config);
+    }
 
     if groups.is_empty() {
+        // Don't offer the whole list_cmd output past the user's exclusions
+        if had_candidates {
+            println!();
+            println!(
+                "  {} Every candidate was excluded by --keep-newest, --scope or --protect-path",
+                style("●").green().bold()
+            );
+            println!();
+            return Ok(());
+        }
+
         // If source has a list_cmd, use that instead of DB
         if let Some(ref sf) = source_filter {
             if let Some(list_cmd) = config.get_list_cmd(sf) {
-                return clean_from_list_cmd(sf, &list_cmd, &config, dry_run, &theme);
+                return clean_from_list_cmd(
+                    sf,
+                    &list_cmd,
+                    &config,
+                    scope,
+                    dry_run,
+                    &theme,
+                    &mut receipt,
+                );
             }
         }
 
@@ -249,7 +587,7 @@ pub fn cmd_clean(
     if dry_run {
         use std::fmt::Write;
 
-        let is_term = console::Term::stdout().is_term();
+        let is_term = styled_stdout();
         macro_rules! s {
             ($expr:expr) => {
                 if is_term {
@@ -279,6 +617,17 @@ pub fn cmd_clean(
                 mixed
             )
             .ok();
+            if all_versions {
+                for v in installed_versions(group, &all_binaries, &config, &protect_paths) {
+                    writeln!(
+                        buf,
+                        "      {} {}",
+                        s!(style("-").red()),
+                        s!(style(v.summary()).dim())
+                    )
+                    .ok();
+                }
+            }
         }
         writeln!(buf).ok();
         writeln!(
@@ -392,200 +741,431 @@ pub fn cmd_clean(
         }
     }
 
-    // Group selected packages by source for batch uninstall
-    let mut by_source: HashMap<String, Vec<&PackageGroup>> = HashMap::new();
-    for &i in &indices {
-        by_source
-            .entry(groups[i].source.clone())
-            .or_default()
-            .push(&groups[i]);
+    // System packages can take parts of the OS with them; make that explicit
+    let selected_system: Vec<&PackageGroup> = indices
+        .iter()
+        .map(|&i| &groups[i])
+        .filter(|g| config.source_scope(&g.source) == Some(SourceScope::System))
+        .collect();
+
+    if !selected_system.is_empty() {
+        let mut sources: Vec<&str> = selected_system.iter().map(|g| g.source.as_str()).collect();
+        sources.sort();
+        sources.dedup();
+        println!();
+        println!(
+            "  {} {} selected packages come from system sources ({}); removing them can break other software:",
+            style("!").magenta().bold(),
+            selected_system.len(),
+            sources.join(", ")
+        );
+        for g in &selected_system {
+            println!("    {} {}", style("•").magenta(), g.package_name);
+        }
+
+        let confirm = Confirm::with_theme(&theme)
+            .with_prompt("Remove these system packages?")
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("  {} Cancelled", style("◦").dim());
+            println!();
+            return Ok(());
+        }
+    }
+
+    // Remove dependents before the packages they link against, and check that
+    // nothing kept and in use links against what's going away
+    let (indices, broken) = plan_removal_order(&db, &groups, &indices)?;
+    if !broken.is_empty() {
+        println!();
+        println!(
+            "  {} {} active binaries you are keeping link against selected packages:",
+            style("!").yellow().bold(),
+            broken.len()
+        );
+        for link in &broken {
+            println!(
+                "    {} {} -> {} (from {})",
+                style("•").yellow(),
+                shorten_path(&link.binary),
+                shorten_path(&link.lib),
+                groups[link.provider].package_name
+            );
+        }
+
+        let confirm = Confirm::with_theme(&theme)
+            .with_prompt("Remove them anyway?")
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("  {} Cancelled", style("◦").dim());
+            println!();
+            return Ok(());
+        }
+    }
+
+    if receipt.summary.is_some() {
+        let mut selected: HashMap<(String, String), Vec<&storage::BinaryRecord>> = HashMap::new();
+        for &i in &indices {
+            let g = &groups[i];
+            selected
+                .entry((g.source.clone(), g.package_name.clone()))
+                .or_default()
+                .extend(&g.binaries);
+        }
+        receipt.sizes = batch_dir_sizes(&db, &selected, &config.size);
     }
 
     let mut total_removed = 0;
     let mut total_failed = 0;
+    let dep_guard = (!no_protect_deps).then(|| DepGuard::new(&db, &all_binaries));
+
+    // --all-versions expands each selected package into its installed versions
+    if all_versions {
+        let selected: Vec<&PackageGroup> = indices.iter().map(|&i| &groups[i]).collect();
+        let (removed, failed) = clean_all_versions(
+            &selected,
+            &all_binaries,
+            &config,
+            &protect_paths,
+            &db,
+            no_trash,
+            dep_guard.as_ref(),
+            &theme,
+            &mut receipt,
+        )?;
+        total_removed += removed;
+        total_failed += failed;
+    }
 
-    for (source, pkgs) in &by_source {
-        let uninstall_cmd = config.get_uninstall_cmd(source);
-
-        match uninstall_cmd {
-            Some(cmd) => {
-                // Use package names (not binary names)
-                // Reject names with shell metacharacters to prevent injection
-                let pkg_names: Vec<&str> = pkgs
-                    .iter()
-                    .map(|g| g.package_name.as_str())
-                    .filter(|name| {
-                        let safe = name
-                            .chars()
-                            .all(|c| c.is_alphanumeric() || "-_.@+".contains(c));
-                        if !safe {
-                            eprintln!(
-                                "  {} Skipping '{}' (unsafe characters in name)",
-                                style("●").red(),
-                                name
-                            );
-                        }
-                        safe
-                    })
-                    .collect();
+    // --batch-size splits the selection into chunks with a checkpoint between them
+    let batches: Vec<&[usize]> = if all_versions {
+        Vec::new()
+    } else {
+        indices
+            .chunks(batch_size.unwrap_or(indices.len()))
+            .collect()
+    };
+    let mut stopped_after: Option<usize> = None;
 
-                if pkg_names.is_empty() {
-                    continue;
+    for (batch_no, batch) in batches.iter().enumerate() {
+        if batches.len() > 1 {
+            if batch_no > 0 {
+                println!();
+                println!(
+                    "  {} Batch {}/{} done: removed {}, failed {} so far",
+                    style("◦").dim(),
+                    batch_no,
+                    batches.len(),
+                    total_removed,
+                    total_failed
+                );
+                let proceed = yes
+                    || Confirm::with_theme(&theme)
+                        .with_prompt(format!(
+                            "Continue with batch {}/{} ({} packages)?",
+                            batch_no + 1,
+                            batches.len(),
+                            batch.len()
+                        ))
+                        .default(true)
+                        .interact()?;
+                if !proceed {
+                    stopped_after = Some(batch_no);
+                    break;
                 }
+            }
+            println!();
+            println!(
+                "  {} Batch {}/{} ({} packages)",
+                style("●").cyan(),
+                batch_no + 1,
+                batches.len(),
+                batch.len()
+            );
+        }
 
-                let full_cmd = format!("{} {}", cmd, pkg_names.join(" "));
-                println!();
-                println!("  Running: {}", style(&full_cmd).cyan());
+        // Group this batch's packages into runs of one source for batch
+        // uninstall; a source comes back as a new run when a package from
+        // another source has to go in between
+        let mut by_source: Vec<(String, Vec<&PackageGroup>)> = Vec::new();
+        for &i in batch.iter() {
+            match by_source.last_mut() {
+                Some((s, pkgs)) if *s == groups[i].source => pkgs.push(&groups[i]),
+                _ => by_source.push((groups[i].source.clone(), vec![&groups[i]])),
+            }
+        }
+
+        for (source, pkgs) in &by_source {
+            let uninstall_cmd = config.get_uninstall_cmd(source);
+
+            match uninstall_cmd {
+                Some(cmd) => {
+                    // Use package names (not binary names)
+                    // Reject names with shell metacharacters to prevent injection
+                    let pkg_names: Vec<&str> = pkgs
+                        .iter()
+                        .map(|g| g.package_name.as_str())
+                        .filter(|name| {
+                            let reason = if !is_safe_package_name(name) {
+                                "unsafe characters in name"
+                            } else if defaults::uninstall_needs_version(&cmd) && !name.contains('@')
+                            {
+                                "no single version to uninstall"
+                            } else {
+                                return true;
+                            };
+                            eprintln!("  {} Skipping '{}' ({})", style("●").red(), name, reason);
+                            let mut action = CleanAction::new(name, source, "pm");
+                            action.error = Some(reason.to_string());
+                            receipt.push(action);
+                            false
+                        })
+                        .collect();
+
+                    if pkg_names.is_empty() {
+                        continue;
+                    }
+
+                    let full_cmd = format!("{} {}", cmd, pkg_names.join(" "));
+                    println!();
+                    println!("  Running: {}", style(&full_cmd).cyan());
 
-                let status = Command::new(defaults::SHELL)
-                    .args([defaults::SHELL_CMD_FLAG, &full_cmd])
-                    .status()
-                    .context("Failed to run uninstall command")?;
+                    let status = Command::new(defaults::SHELL)
+                        .args([defaults::SHELL_CMD_FLAG, &full_cmd])
+                        .status()
+                        .context("Failed to run uninstall command")?;
 
-                if status.success() {
-                    // Record trash receipts for package manager removals
                     let install_cmd = defaults::install_cmd_from_uninstall(&cmd);
                     for pkg_name in &pkg_names {
-                        let restore = install_cmd
-                            .as_ref()
-                            .map(|ic| format!("{} {}", ic, pkg_name));
-                        db.record_trash(
-                            pkg_name,
-                            None,
-                            source.as_str(),
-                            pkg_name,
-                            "package_manager",
-                            restore.as_deref(),
-                        )
-                        .ok();
+                        let mut action = CleanAction::new(pkg_name, source, "pm");
+                        action.command = Some(full_cmd.clone());
+                        action.success = status.success();
+                        if status.success() {
+                            action.restore_cmd = install_cmd
+                                .as_ref()
+                                .map(|ic| format!("{} {}", ic, pkg_name));
+                        } else {
+                            action.error =
+                                Some(format!("uninstall command exited with {}", status));
+                        }
+                        receipt.push(action);
                     }
 
-                    println!(
-                        "  {} Removed {} packages",
-                        style("●").green(),
-                        pkg_names.len()
-                    );
-                    total_removed += pkg_names.len();
-                } else {
-                    println!("  {} Some packages failed to remove", style("●").red());
-                    total_failed += pkg_names.len();
-                }
-            }
-            None => {
-                // No package manager -- detect install root directories
-                let all_paths: Vec<&str> = pkgs
-                    .iter()
-                    .flat_map(|g| g.binaries.iter().map(|b| b.path.as_str()))
-                    .collect();
-
-                let roots = detect_install_roots(&all_paths);
+                    if status.success() {
+                        // Record trash receipts for package manager removals
+                        for pkg_name in &pkg_names {
+                            let restore = install_cmd
+                                .as_ref()
+                                .map(|ic| format!("{} {}", ic, pkg_name));
+                            db.record_trash(
+                                pkg_name,
+                                None,
+                                source.as_str(),
+                                pkg_name,
+                                "package_manager",
+                                restore.as_deref(),
+                            )
+                            .ok();
+                        }
 
-                if roots.is_empty() {
-                    continue;
+                        println!(
+                            "  {} Removed {} packages",
+                            style("●").green(),
+                            pkg_names.len()
+                        );
+                        total_removed += pkg_names.len();
+                    } else {
+                        println!("  {} Some packages failed to remove", style("●").red());
+                        total_failed += pkg_names.len();
+                    }
                 }
-
-                let action = if no_trash { "remove" } else { "trash" };
-                println!();
-                println!(
-                    "  {} {} (no package manager -- {} directories):",
-                    style("●").yellow(),
-                    style(source).yellow().bold(),
-                    action
-                );
-                for root in &roots {
-                    println!("    {} {}", style("◦").dim(), root);
+                None if group_by == GroupBy::BinaryDir => {
+                    // No package manager -- offer individual files per bin directory
+                    let (removed, failed) =
+                        clean_by_binary_dir(pkgs, source, &db, no_trash, &theme, &mut receipt)?;
+                    total_removed += removed;
+                    total_failed += failed;
                 }
+                None => {
+                    // `go install` binaries are removed one file at a time
+                    let (go_pkgs, pkgs): (Vec<&PackageGroup>, Vec<&PackageGroup>) = pkgs
+                        .iter()
+                        .partition(|g| g.binaries.iter().all(|b| package::is_go_install(&b.path)));
+                    let (removed, failed) =
+                        clean_go_installs(&go_pkgs, source, &db, no_trash, &theme, &mut receipt)?;
+                    total_removed += removed;
+                    total_failed += failed;
+
+                    // No package manager -- detect install root directories.
+                    // Nix paths are left to nix: the store is immutable and GC-managed.
+                    let (nix_paths, all_paths): (Vec<&str>, Vec<&str>) = pkgs
+                        .iter()
+                        .flat_map(|g| g.binaries.iter().map(|b| b.path.as_str()))
+                        .partition(|p| package::is_nix_managed(p));
+                    if !nix_paths.is_empty() {
+                        println!();
+                        println!(
+                            "  {} Skipping {} Nix-managed binaries; remove them with {}",
+                            style("◦").dim(),
+                            nix_paths.len(),
+                            style("nix-env --uninstall").cyan()
+                        );
+                    }
 
-                let prompt = if no_trash {
-                    format!(
-                        "Permanently remove {} directories? (may require sudo)",
-                        roots.len()
-                    )
-                } else {
-                    format!("Move {} directories to trash?", roots.len())
-                };
-
-                let confirm = Confirm::with_theme(&theme)
-                    .with_prompt(prompt)
-                    .default(false)
-                    .interact()?;
-
-                if confirm {
-                    // Derive a single package_name for the group
-                    let pkg_name = pkgs
-                        .first()
-                        .map(|g| g.package_name.as_str())
-                        .unwrap_or("unknown");
+                    let roots = detect_install_roots(&all_paths);
+
+                    if roots.is_empty() {
+                        continue;
+                    }
 
+                    let action = if no_trash { "remove" } else { "trash" };
+                    println!();
+                    println!(
+                        "  {} {} (no package manager -- {} directories):",
+                        style("●").yellow(),
+                        style(source).yellow().bold(),
+                        action
+                    );
                     for root in &roots {
-                        // Safety: refuse to delete paths that are too short
-                        // (must have at least 3 components like /opt/something)
-                        let components = std::path::Path::new(root).components().count();
-                        if components < 3 {
-                            println!(
-                                "  {} Refusing to delete {} (path too short)",
-                                style("●").red(),
-                                root
+                        println!("    {} {}", style("◦").dim(), root);
+                    }
+
+                    let prompt = if no_trash {
+                        format!(
+                            "Permanently remove {} directories? (may require sudo)",
+                            roots.len()
+                        )
+                    } else {
+                        format!("Move {} directories to trash?", roots.len())
+                    };
+
+                    let confirm = Confirm::with_theme(&theme)
+                        .with_prompt(prompt)
+                        .default(false)
+                        .interact()?;
+
+                    if confirm {
+                        // Derive a single package_name for the group
+                        let pkg_name = pkgs
+                            .first()
+                            .map(|g| g.package_name.as_str())
+                            .unwrap_or("unknown");
+
+                        for root in &roots {
+                            let mut action = CleanAction::new(
+                                pkg_name,
+                                source,
+                                if no_trash { "rm" } else { "moved" },
                             );
-                            total_failed += 1;
-                            continue;
-                        }
+                            action.path = Some(root.clone());
 
-                        if no_trash {
-                            // Permanent deletion (old behavior)
-                            println!("  Running: {}", style(format!("rm -rf {}", root)).cyan());
-                            if std::fs::remove_dir_all(root).is_ok() {
-                                println!("  {} Removed {}", style("●").green(), root);
-                                total_removed += 1;
-                            } else {
+                            // Safety: refuse to delete paths that are too short
+                            // (must have at least 3 components like /opt/something)
+                            let components = std::path::Path::new(root).components().count();
+                            if components < 3 {
                                 println!(
-                                    "  Running: {}",
-                                    style(format!("sudo rm -rf {}", root)).cyan()
+                                    "  {} Refusing to delete {} (path too short)",
+                                    style("●").red(),
+                                    root
                                 );
-                                let status = Command::new(defaults::SUDO)
-                                    .arg(defaults::RM)
-                                    .args(defaults::RM_RECURSIVE_FLAGS)
-                                    .arg(root.as_str())
-                                    .status();
-                                if status.map(|s| s.success()).unwrap_or(false) {
+                                action.error = Some("path too short".to_string());
+                                receipt.push(action);
+                                total_failed += 1;
+                                continue;
+                            }
+
+                            if dep_guard
+                                .as_ref()
+                                .is_some_and(|guard| guard.blocks(root, &mut action))
+                            {
+                                receipt.push(action);
+                                total_failed += 1;
+                                continue;
+                            }
+
+                            if no_trash {
+                                // Permanent deletion (old behavior)
+                                let rm_cmd = format!("rm -rf {}", root);
+                                println!("  Running: {}", style(&rm_cmd).cyan());
+                                if std::fs::remove_dir_all(root).is_ok() {
                                     println!("  {} Removed {}", style("●").green(), root);
+                                    action.command = Some(rm_cmd);
+                                    action.success = true;
                                     total_removed += 1;
                                 } else {
-                                    println!("  {} Failed to remove {}", style("●").red(), root);
-                                    total_failed += 1;
-                                }
-                            }
-                        } else {
-                            // Move to trash
-                            match move_to_trash(root, &db, source, pkg_name) {
-                                Ok(trash_path) => {
-                                    println!(
-                                        "  {} Trashed {} → {}",
-                                        style("●").green(),
-                                        root,
-                                        style(&trash_path).dim()
-                                    );
-                                    total_removed += 1;
+                                    let sudo_cmd = format!("sudo {}", rm_cmd);
+                                    println!("  Running: {}", style(&sudo_cmd).cyan());
+                                    let status = Command::new(defaults::SUDO)
+                                        .arg(defaults::RM)
+                                        .args(defaults::RM_RECURSIVE_FLAGS)
+                                        .arg(root.as_str())
+                                        .status();
+                                    action.command = Some(sudo_cmd);
+                                    if status.map(|s| s.success()).unwrap_or(false) {
+                                        println!("  {} Removed {}", style("●").green(), root);
+                                        action.success = true;
+                                        total_removed += 1;
+                                    } else {
+                                        println!(
+                                            "  {} Failed to remove {}",
+                                            style("●").red(),
+                                            root
+                                        );
+                                        action.error = Some("sudo rm failed".to_string());
+                                        total_failed += 1;
+                                    }
                                 }
-                                Err(e) => {
-                                    println!(
-                                        "  {} Failed to trash {}: {}",
-                                        style("●").red(),
-                                        root,
-                                        e
-                                    );
-                                    total_failed += 1;
+                            } else {
+                                // Move to trash
+                                match move_to_trash(root, &db, source, pkg_name) {
+                                    Ok(trash_path) => {
+                                        println!(
+                                            "  {} Trashed {} → {}",
+                                            style("●").green(),
+                                            root,
+                                            style(&trash_path).dim()
+                                        );
+                                        action.command =
+                                            Some(format!("mv {} {}", root, trash_path));
+                                        action.restore_cmd =
+                                            Some(format!("dusty restore {}", pkg_name));
+                                        action.trash_path = Some(trash_path);
+                                        action.success = true;
+                                        total_removed += 1;
+                                    }
+                                    Err(e) => {
+                                        println!(
+                                            "  {} Failed to trash {}: {}",
+                                            style("●").red(),
+                                            root,
+                                            e
+                                        );
+                                        action.error = Some(format!("{:#}", e));
+                                        total_failed += 1;
+                                    }
                                 }
                             }
+                            receipt.push(action);
                         }
+                    } else {
+                        println!("  {} Skipped", style("◦").dim());
                     }
-                } else {
-                    println!("  {} Skipped", style("◦").dim());
                 }
             }
         }
     }
 
+    // Keep the trash within its configured limits, sparing everything this run moved
+    if !receipt.trashed.is_empty()
+        && let Err(e) = gc_trash(&db, &receipt.trashed)
+    {
+        eprintln!("  {} Trash eviction failed: {:#}", style("●").red(), e);
+    }
+
     println!();
     if total_removed > 0 || total_failed > 0 {
         println!(
@@ -603,9 +1183,20 @@ pub fn cmd_clean(
             );
         }
 
+        if let Some(done) = stopped_after {
+            let left: usize = batches[done..].iter().map(|b| b.len()).sum();
+            println!(
+                "  {} Stopped after batch {}/{}; {} selected packages left untouched",
+                style("◦").dim(),
+                done,
+                batches.len(),
+                left
+            );
+        }
+
         // Show autoremove hints for sources that were cleaned
-        let mut shown = std::collections::HashSet::new();
-        for source in by_source.keys() {
+        let mut shown = HashSet::new();
+        for source in &receipt.cleaned_sources {
             if let Some(hint) = defaults::autoremove_hint(source) {
                 if shown.insert(hint) {
                     println!(
@@ -617,51 +1208,951 @@ pub fn cmd_clean(
             }
         }
     }
+
+    if total_removed > 0
+        && let Some(hook) = on_success.or(config.clean.on_success)
+    {
+        run_on_success(&hook, &receipt.cleaned_sources, total_removed);
+    }
     println!();
 
     Ok(())
 }
 
-/// Move a directory to the trash instead of deleting it.
-/// Returns the trash path on success.
-fn move_to_trash(
-    root: &str,
-    db: &storage::Database,
-    source: &str,
-    package_name: &str,
-) -> Result<String> {
-    let data_dir = dirs::data_local_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not find local data directory"))?;
-    let trash_dir = data_dir.join("dusty").join(defaults::TRASH_DIR);
-    std::fs::create_dir_all(&trash_dir)?;
-
-    let dir_name = std::path::Path::new(root)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
-    let timestamp = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S");
-    let dest = trash_dir.join(format!("{}_{}", timestamp, dir_name));
-    let dest_str = dest.to_string_lossy().to_string();
+/// Keeps directory removals from taking libraries that active binaries
+/// elsewhere still link against (on unless `--no-protect-deps`)
+struct DepGuard<'a> {
+    db: &'a storage::Database,
+    binaries: &'a [storage::BinaryRecord],
+    refreshed: std::cell::Cell<bool>,
+}
 
-    // Try rename (fast if same filesystem)
-    if std::fs::rename(root, &dest).is_ok() {
-        db.record_trash(root, Some(&dest_str), source, package_name, "moved", None)?;
-        return Ok(dest_str);
+impl<'a> DepGuard<'a> {
+    fn new(db: &'a storage::Database, binaries: &'a [storage::BinaryRecord]) -> Self {
+        Self {
+            db,
+            binaries,
+            refreshed: std::cell::Cell::new(false),
+        }
     }
 
-    // Cross-filesystem or permission issue: try sudo mv
-    let status = Command::new(defaults::SUDO)
-        .args(["mv", root, &dest_str])
-        .status()
-        .context("Failed to run sudo mv")?;
+    /// Whether `root` must be kept, explaining why. The dependency cache of
+    /// active binaries is brought up to date before the first check; if that
+    /// fails the removal is refused rather than risked.
+    fn blocks(&self, root: &str, action: &mut CleanAction) -> bool {
+        let dependents = (|| {
+            if !self.refreshed.replace(true) {
+                let active: Vec<_> = self
+                    .binaries
+                    .iter()
+                    .filter(|b| b.count > 0)
+                    .cloned()
+                    .collect();
+                deps::update_dep_cache(self.db, &active, false, None)?;
+            }
+            deps::active_dependents_under(self.db, self.binaries, root)
+        })();
 
-    if status.success() {
-        db.record_trash(root, Some(&dest_str), source, package_name, "moved", None)?;
-        Ok(dest_str)
-    } else {
-        anyhow::bail!("Failed to move {} to trash", root)
-    }
-}
+        let dependents = match dependents {
+            Ok(d) if d.is_empty() => return false,
+            Ok(d) => d,
+            Err(e) => {
+                println!(
+                    "  {} Keeping {}: couldn't check which binaries link against it ({:#})",
+                    style("●").red(),
+                    root,
+                    e
+                );
+                println!(
+                    "    {} Pass {} to remove it anyway",
+                    style("◦").dim(),
+                    style("--no-protect-deps").cyan()
+                );
+                action.error = Some(format!("dependency check failed: {:#}", e));
+                return true;
+            }
+        };
+
+        println!(
+            "  {} Keeping {}: {} active binaries link libraries inside it",
+            style("●").red(),
+            root,
+            dependents.len()
+        );
+        for d in dependents.iter().take(5) {
+            println!(
+                "    {} {} ({} uses) → {}",
+                style("◦").dim(),
+                shorten_path(&d.binary_path),
+                d.uses,
+                style(shorten_path(&d.lib_path)).dim()
+            );
+        }
+        if dependents.len() > 5 {
+            println!(
+                "    {} ... and {} more",
+                style("◦").dim(),
+                dependents.len() - 5
+            );
+        }
+        println!(
+            "    {} Pass {} to remove it anyway",
+            style("◦").dim(),
+            style("--no-protect-deps").cyan()
+        );
+        action.error = Some(format!(
+            "libraries in use by {}",
+            dependents
+                .iter()
+                .map(|d| d.binary_path.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        true
+    }
+}
+
+/// One installed version of a package family, as found by `--all-versions`
+struct VersionInstall {
+    /// `tool@version` for asdf/mise, the list_cmd line, or the directory name
+    name: String,
+    /// Version directory; None when the version is removed through the uninstall command
+    dir: Option<String>,
+    last_used: Option<i64>,
+}
+
+impl VersionInstall {
+    fn summary(&self) -> String {
+        let last_used = match self.last_used {
+            Some(ts) => format!("last used {}", format_timestamp(ts)),
+            None => "never used".to_string(),
+        };
+        match &self.dir {
+            Some(dir) => format!("{} ({}, {})", self.name, last_used, shorten_path(dir)),
+            None => format!("{} ({})", self.name, last_used),
+        }
+    }
+}
+
+/// The directory of the version a binary belongs to: its nearest ancestor named
+/// like a version ("3.11.4", "v20.1.0"), e.g. `~/.pyenv/versions/3.11.4`
+fn version_dir(path: &str) -> Option<&Path> {
+    Path::new(path)
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.components().count() > 3)
+        .find(|dir| {
+            dir.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(is_version_name)
+        })
+}
+
+/// "3.11.4", "v20.1.0", "17" -- but not "2023tools"
+fn is_version_name(name: &str) -> bool {
+    let name = name.strip_prefix('v').unwrap_or(name);
+    let digits = name.chars().take_while(|c| c.is_ascii_digit()).count();
+    digits > 0 && matches!(name[digits..].chars().next(), None | Some('.'))
+}
+
+/// The version managers' version roots (`~/.pyenv/versions`, ...), `~` expanded
+fn version_manager_roots() -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    defaults::VERSION_MANAGER_DIRS
+        .iter()
+        .map(|root| match root.strip_prefix("~/") {
+            Some(rest) => home.join(rest),
+            None => PathBuf::from(root),
+        })
+        .collect()
+}
+
+/// Whether `dir` is a version directory under one of `roots`
+/// (`~/.pyenv/versions/3.11.4`, `~/.asdf/installs/nodejs/20.1.0`, ...)
+fn is_version_manager_dir(dir: &Path, roots: &[PathBuf]) -> bool {
+    roots
+        .iter()
+        .any(|root| dir.starts_with(root) && dir != root)
+}
+
+/// Every installed version of `group`'s package family that `--protect-path`
+/// doesn't cover: the source's list_cmd output when it has one (and an
+/// uninstall command to use on it), then the sibling directories of the
+/// version-manager versions its binaries live in, and otherwise just the
+/// package itself through the uninstall command.
+fn installed_versions(
+    group: &PackageGroup,
+    all_binaries: &[storage::BinaryRecord],
+    config: &config::Config,
+    protect_paths: &[String],
+) -> Vec<VersionInstall> {
+    let protected = |path: &str| protect_paths.iter().any(|p| is_protected(p, path));
+    let source = group.source.as_str();
+    let uninstall_cmd = config.get_uninstall_cmd(source);
+    let mut versions = find_versions(
+        group,
+        all_binaries,
+        config,
+        uninstall_cmd.as_deref(),
+        &version_manager_roots(),
+    );
+    versions.retain(|v| match &v.dir {
+        Some(dir) => !protected(dir),
+        None => !all_binaries.iter().any(|b| {
+            b.source.as_deref() == Some(source)
+                && b.package_name.as_deref() == Some(v.name.as_str())
+                && protected(&b.path)
+        }),
+    });
+    versions
+}
+
+fn find_versions(
+    group: &PackageGroup,
+    all_binaries: &[storage::BinaryRecord],
+    config: &config::Config,
+    uninstall_cmd: Option<&str>,
+    roots: &[PathBuf],
+) -> Vec<VersionInstall> {
+    let source = group.source.as_str();
+    let base = package::split_version(&group.package_name).0;
+    let last_used = |pred: &dyn Fn(&storage::BinaryRecord) -> bool| {
+        all_binaries
+            .iter()
+            .filter(|b| b.source.as_deref() == Some(source) && pred(b))
+            .filter_map(|b| b.last_seen)
+            .max()
+    };
+
+    if uninstall_cmd.is_some()
+        && !platform::is_offline()
+        && let Some(list_cmd) = config.get_list_cmd(source)
+        && let Ok(output) = Command::new(defaults::SHELL)
+            .args([defaults::SHELL_CMD_FLAG, &list_cmd])
+            .output()
+        && output.status.success()
+    {
+        let versions: Vec<VersionInstall> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && package::split_version(line).0 == base)
+            .map(|line| VersionInstall {
+                name: line.to_string(),
+                dir: None,
+                last_used: last_used(&|b| b.package_name.as_deref() == Some(line)),
+            })
+            .collect();
+        if !versions.is_empty() {
+            return versions;
+        }
+    }
+
+    // Anywhere else (e.g. a Homebrew Cellar) a version directory is owned by
+    // the package manager, so the package goes through its uninstall command
+    let dirs: Vec<&Path> = group
+        .binaries
+        .iter()
+        .filter_map(|b| version_dir(&b.path))
+        .filter(|d| is_version_manager_dir(d, roots))
+        .collect();
+    if dirs.is_empty() {
+        if uninstall_cmd.is_none() {
+            return Vec::new();
+        }
+        return vec![VersionInstall {
+            name: group.package_name.clone(),
+            dir: None,
+            last_used: group.binaries.iter().filter_map(|b| b.last_seen).max(),
+        }];
+    }
+
+    // asdf and mise name packages `tool@version` after the install directory,
+    // so their versions can go through the uninstall command
+    let by_name = uninstall_cmd.is_some()
+        && dirs
+            .iter()
+            .any(|d| tool_at_version(d).as_deref() == Some(&group.package_name));
+
+    let mut parents: Vec<&Path> = dirs.iter().filter_map(|d| d.parent()).collect();
+    parents.sort();
+    parents.dedup();
+
+    let mut versions = Vec::new();
+    for parent in parents {
+        let Ok(entries) = std::fs::read_dir(parent) else {
+            continue;
+        };
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_dir()
+                    && p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(is_version_name)
+            })
+            .collect();
+        found.sort();
+        for dir in found {
+            let name = if by_name {
+                tool_at_version(&dir)
+            } else {
+                dir.file_name().and_then(|n| n.to_str()).map(String::from)
+            };
+            let Some(name) = name else {
+                continue;
+            };
+            versions.push(VersionInstall {
+                last_used: last_used(&|b| Path::new(&b.path).starts_with(&dir)),
+                dir: (!by_name).then(|| dir.display().to_string()),
+                name,
+            });
+        }
+    }
+    versions
+}
+
+/// `nodejs@20.1.0` for `.../installs/nodejs/20.1.0`
+fn tool_at_version(dir: &Path) -> Option<String> {
+    let version = dir.file_name()?.to_str()?;
+    let tool = dir.parent()?.file_name()?.to_str()?;
+    Some(format!("{}@{}", tool, version))
+}
+
+/// `--all-versions`: list every installed version of the selected packages and,
+/// once confirmed, remove each one. Returns (removed, failed).
+#[allow(clippy::too_many_arguments)]
+fn clean_all_versions(
+    selected: &[&PackageGroup],
+    all_binaries: &[storage::BinaryRecord],
+    config: &config::Config,
+    protect_paths: &[String],
+    db: &storage::Database,
+    no_trash: bool,
+    dep_guard: Option<&DepGuard>,
+    theme: &dialoguer::theme::ColorfulTheme,
+    receipt: &mut CleanReceipt,
+) -> Result<(usize, usize)> {
+    use dialoguer::Confirm;
+
+    let (mut removed, mut failed) = (0, 0);
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for group in selected {
+        let source = group.source.as_str();
+        let versions: Vec<VersionInstall> =
+            installed_versions(group, all_binaries, config, protect_paths)
+                .into_iter()
+                .filter(|v| seen.insert(format!("{}\0{}", source, v.name)))
+                .collect();
+        if versions.is_empty() {
+            continue;
+        }
+
+        println!();
+        println!(
+            "  {} {} ({}): {} installed versions",
+            style("●").yellow(),
+            style(package::split_version(&group.package_name).0).bold(),
+            source,
+            versions.len()
+        );
+        for v in &versions {
+            println!("    {} {}", style("◦").dim(), v.summary());
+        }
+
+        let confirm = Confirm::with_theme(theme)
+            .with_prompt(format!("Remove all {} versions?", versions.len()))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("  {} Skipped", style("◦").dim());
+            continue;
+        }
+
+        for v in &versions {
+            let ok = match &v.dir {
+                Some(dir) => {
+                    remove_version_dir(&v.name, dir, source, db, no_trash, dep_guard, receipt)
+                }
+                None => uninstall_version(&v.name, source, config, db, receipt)?,
+            };
+            if ok {
+                removed += 1;
+            } else {
+                failed += 1;
+            }
+        }
+    }
+
+    Ok((removed, failed))
+}
+
+/// Remove one version through the source's uninstall command
+fn uninstall_version(
+    name: &str,
+    source: &str,
+    config: &config::Config,
+    db: &storage::Database,
+    receipt: &mut CleanReceipt,
+) -> Result<bool> {
+    let mut action = CleanAction::new(name, source, "pm");
+    let Some(cmd) = config.get_uninstall_cmd(source) else {
+        return Ok(false);
+    };
+    if !is_safe_package_name(name) {
+        eprintln!(
+            "  {} Skipping '{}' (unsafe characters in name)",
+            style("●").red(),
+            name
+        );
+        action.error = Some("unsafe characters in name".to_string());
+        receipt.push(action);
+        return Ok(false);
+    }
+
+    let full_cmd = format!("{} {}", cmd, name);
+    println!("  Running: {}", style(&full_cmd).cyan());
+    let status = Command::new(defaults::SHELL)
+        .args([defaults::SHELL_CMD_FLAG, &full_cmd])
+        .status()
+        .context("Failed to run uninstall command")?;
+
+    action.command = Some(full_cmd);
+    action.success = status.success();
+    if status.success() {
+        let restore =
+            defaults::install_cmd_from_uninstall(&cmd).map(|ic| format!("{} {}", ic, name));
+        db.record_trash(
+            name,
+            None,
+            source,
+            name,
+            "package_manager",
+            restore.as_deref(),
+        )
+        .ok();
+        action.restore_cmd = restore;
+        println!("  {} Removed {}", style("●").green(), name);
+    } else {
+        action.error = Some(format!("uninstall command exited with {}", status));
+        println!("  {} Failed to remove {}", style("●").red(), name);
+    }
+    receipt.push(action);
+    Ok(status.success())
+}
+
+/// Trash (or with --no-trash, delete) one version directory
+fn remove_version_dir(
+    name: &str,
+    dir: &str,
+    source: &str,
+    db: &storage::Database,
+    no_trash: bool,
+    dep_guard: Option<&DepGuard>,
+    receipt: &mut CleanReceipt,
+) -> bool {
+    let mut action = CleanAction::new(name, source, if no_trash { "rm" } else { "moved" });
+    action.path = Some(dir.to_string());
+
+    if dep_guard.is_some_and(|guard| guard.blocks(dir, &mut action)) {
+        receipt.push(action);
+        return false;
+    }
+
+    let result = if no_trash {
+        action.command = Some(format!("rm -rf {}", dir));
+        std::fs::remove_dir_all(dir).map_err(anyhow::Error::from)
+    } else {
+        move_to_trash(dir, db, source, name).map(|trash_path| {
+            action.command = Some(format!("mv {} {}", dir, trash_path));
+            action.restore_cmd = Some(format!("dusty restore {}", name));
+            action.trash_path = Some(trash_path);
+        })
+    };
+
+    match result {
+        Ok(()) => {
+            let verb = if no_trash { "Removed" } else { "Trashed" };
+            println!("  {} {} {}", style("●").green(), verb, dir);
+            action.success = true;
+        }
+        Err(e) => {
+            println!("  {} Failed to remove {}: {:#}", style("●").red(), dir, e);
+            action.error = Some(format!("{:#}", e));
+        }
+    }
+    let success = action.success;
+    receipt.push(action);
+    success
+}
+
+/// Whether a dependency-cache library owner (`manager`) is the clean source `source`
+fn manager_is_source(manager: &str, source: &str) -> bool {
+    manager == source || (manager == "rpm" && matches!(source, "dnf" | "yum" | "zypper"))
+}
+
+/// An active binary outside the selection that links a selected package's library
+struct BrokenLink {
+    binary: String,
+    lib: String,
+    /// Index into the package groups of the package providing `lib`
+    provider: usize,
+}
+
+/// Order the selected groups so that a package is removed before any selected
+/// package it links against (from the cached `dusty deps` data; a no-op without it).
+/// Also returns the active binaries outside the selection that would lose a library.
+fn plan_removal_order(
+    db: &Database,
+    groups: &[PackageGroup],
+    indices: &[usize],
+) -> Result<(Vec<usize>, Vec<BrokenLink>)> {
+    let dylib_deps = db.get_all_dylib_deps()?;
+    if dylib_deps.is_empty() {
+        return Ok((indices.to_vec(), Vec::new()));
+    }
+    let lib_owner: HashMap<String, (String, String)> = db
+        .get_all_lib_packages()?
+        .into_iter()
+        .map(|(lib, manager, pkg)| (lib, (manager, pkg)))
+        .collect();
+    let provider_of = |lib: &str| lib_provider(groups, indices, &lib_owner, lib);
+    let ordered = removal_order(groups, indices, &dylib_deps, &lib_owner);
+
+    let selected: HashSet<&str> = indices
+        .iter()
+        .flat_map(|&i| groups[i].binaries.iter().map(|b| b.path.as_str()))
+        .collect();
+    let mut broken = Vec::new();
+    for b in db.get_all_binaries()? {
+        if b.count == 0 || selected.contains(b.path.as_str()) {
+            continue;
+        }
+        let mut seen = HashSet::new();
+        for lib in dylib_deps.get(&b.path).into_iter().flatten() {
+            if let Some(j) = provider_of(lib)
+                && seen.insert(j)
+            {
+                broken.push(BrokenLink {
+                    binary: b.path.clone(),
+                    lib: lib.clone(),
+                    provider: j,
+                });
+            }
+        }
+    }
+
+    Ok((ordered, broken))
+}
+
+/// The selected group whose package owns `lib`, if any
+fn lib_provider(
+    groups: &[PackageGroup],
+    indices: &[usize],
+    lib_owner: &HashMap<String, (String, String)>,
+    lib: &str,
+) -> Option<usize> {
+    let (manager, pkg) = lib_owner.get(lib)?;
+    indices
+        .iter()
+        .copied()
+        .find(|&i| groups[i].package_name == *pkg && manager_is_source(manager, &groups[i].source))
+}
+
+/// Dependents-first order of the whole selection, across sources. Kahn's
+/// algorithm, stable in selection order but staying with the source of the
+/// last package placed while it can, so each source's packages stay together
+/// for a batch uninstall. Anything left in a cycle keeps its original position
+/// at the end.
+fn removal_order(
+    groups: &[PackageGroup],
+    indices: &[usize],
+    dylib_deps: &HashMap<String, Vec<String>>,
+    lib_owner: &HashMap<String, (String, String)>,
+) -> Vec<usize> {
+    // needs[i] = selected packages i links against, i.e. ones that must go after it
+    let mut needs: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for &i in indices {
+        for b in &groups[i].binaries {
+            for lib in dylib_deps.get(&b.path).into_iter().flatten() {
+                if let Some(j) = lib_provider(groups, indices, lib_owner, lib).filter(|&j| j != i) {
+                    needs.entry(i).or_default().insert(j);
+                }
+            }
+        }
+    }
+
+    let mut dependents_left: HashMap<usize, usize> = HashMap::new();
+    for deps in needs.values() {
+        for &j in deps {
+            *dependents_left.entry(j).or_default() += 1;
+        }
+    }
+    let mut ordered: Vec<usize> = Vec::with_capacity(indices.len());
+    let mut placed: HashSet<usize> = HashSet::new();
+    loop {
+        let ready =
+            |i: &&usize| !placed.contains(*i) && dependents_left.get(*i).copied().unwrap_or(0) == 0;
+        let same_source = ordered.last().and_then(|&last| {
+            indices
+                .iter()
+                .filter(ready)
+                .find(|&&i| groups[i].source == groups[last].source)
+        });
+        let Some(&next) = same_source.or_else(|| indices.iter().find(ready)) else {
+            break;
+        };
+        placed.insert(next);
+        ordered.push(next);
+        for j in needs.get(&next).into_iter().flatten() {
+            if let Some(n) = dependents_left.get_mut(j) {
+                *n -= 1;
+            }
+        }
+    }
+    ordered.extend(indices.iter().filter(|i| !placed.contains(i)));
+    ordered
+}
+
+/// Run the `--on-success` / `[clean] on_success` hook. A failing hook is
+/// reported but doesn't fail the clean, which has already happened.
+fn run_on_success(hook: &str, cleaned_sources: &BTreeSet<String>, removed: usize) {
+    let sources: Vec<&str> = cleaned_sources.iter().map(String::as_str).collect();
+
+    println!();
+    println!("  {} Running {}", style("◦").dim(), style(hook).cyan());
+    let status = Command::new(defaults::SHELL)
+        .args([defaults::SHELL_CMD_FLAG, hook])
+        .env("DUSTY_CLEANED_SOURCES", sources.join(","))
+        .env("DUSTY_REMOVED_COUNT", removed.to_string())
+        .status();
+    match status {
+        Ok(s) if s.success() => {}
+        Ok(s) => println!(
+            "  {} on_success hook exited with {}",
+            style("●").yellow(),
+            s.code().map_or("a signal".to_string(), |c| c.to_string())
+        ),
+        Err(e) => println!(
+            "  {} Could not run on_success hook: {}",
+            style("●").red(),
+            e
+        ),
+    }
+}
+
+/// One package `clean --dry-run --json` would offer
+#[derive(Serialize, JsonSchema)]
+pub(super) struct PlannedGroup {
+    package_name: String,
+    source: String,
+    binaries: Vec<String>,
+    is_mixed: bool,
+    install_roots: Vec<String>,
+    uninstall_cmd: Option<String>,
+}
+
+/// `rm` of the files when every one is a `go install` binary
+fn go_remove_cmd(paths: &[&str]) -> Option<String> {
+    (!paths.is_empty() && paths.iter().all(|p| package::is_go_install(p)))
+        .then(|| format!("rm {}", paths.join(" ")))
+}
+
+fn print_plan(groups: &[PackageGroup], config: &config::Config) -> Result<()> {
+    let rows: Vec<PlannedGroup> = groups
+        .iter()
+        .map(|g| {
+            let paths: Vec<&str> = g.binaries.iter().map(|b| b.path.as_str()).collect();
+            PlannedGroup {
+                package_name: g.package_name.clone(),
+                source: g.source.clone(),
+                binaries: paths.iter().map(|p| p.to_string()).collect(),
+                is_mixed: g.is_mixed(),
+                install_roots: detect_install_roots(&paths),
+                uninstall_cmd: config
+                    .get_uninstall_cmd(&g.source)
+                    .map(|cmd| format!("{} {}", cmd, g.package_name))
+                    .or_else(|| go_remove_cmd(&paths)),
+            }
+        })
+        .collect();
+    print_json(&rows)
+}
+
+/// Remove `go install` binaries, which have no uninstaller: each file is
+/// trashed (or deleted) under its package's import path. Returns (removed, failed).
+fn clean_go_installs(
+    pkgs: &[&PackageGroup],
+    source: &str,
+    db: &storage::Database,
+    no_trash: bool,
+    theme: &dialoguer::theme::ColorfulTheme,
+    receipt: &mut CleanReceipt,
+) -> Result<(usize, usize)> {
+    use dialoguer::Confirm;
+
+    let files: Vec<(&str, &str)> = pkgs
+        .iter()
+        .flat_map(|g| {
+            g.binaries
+                .iter()
+                .map(|b| (g.package_name.as_str(), b.path.as_str()))
+        })
+        .collect();
+    if files.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let action = if no_trash { "remove" } else { "trash" };
+    println!();
+    println!(
+        "  {} {} (go install -- {} files):",
+        style("●").yellow(),
+        style(source).yellow().bold(),
+        action
+    );
+    for (pkg_name, path) in &files {
+        println!(
+            "    {} {} {}",
+            style("◦").dim(),
+            path,
+            style(pkg_name).dim()
+        );
+    }
+
+    let prompt = if no_trash {
+        format!("Permanently remove {} files?", files.len())
+    } else {
+        format!("Move {} files to trash?", files.len())
+    };
+    let confirm = Confirm::with_theme(theme)
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("  {} Skipped", style("◦").dim());
+        return Ok((0, 0));
+    }
+
+    let (mut removed, mut failed) = (0, 0);
+    for (pkg_name, path) in files {
+        let mut action = CleanAction::new(pkg_name, source, if no_trash { "rm" } else { "moved" });
+        action.path = Some(path.to_string());
+
+        if no_trash {
+            // Read before the file is gone: the only way back is rebuilding it
+            let reinstall = package::go_build_info(Path::new(path)).map(|i| i.install_cmd());
+            action.command = Some(format!("rm {}", path));
+            match std::fs::remove_file(path) {
+                Ok(()) => {
+                    println!("  {} Removed {}", style("●").green(), path);
+                    action.restore_cmd = reinstall;
+                    action.success = true;
+                    removed += 1;
+                }
+                Err(e) => {
+                    println!("  {} Failed to remove {}: {}", style("●").red(), path, e);
+                    action.error = Some(e.to_string());
+                    failed += 1;
+                }
+            }
+            receipt.push(action);
+            continue;
+        }
+
+        match move_to_trash(path, db, source, pkg_name) {
+            Ok(trash_path) => {
+                println!(
+                    "  {} Trashed {} → {}",
+                    style("●").green(),
+                    path,
+                    style(&trash_path).dim()
+                );
+                action.command = Some(format!("mv {} {}", path, trash_path));
+                action.restore_cmd = Some(format!("dusty restore {}", pkg_name));
+                action.trash_path = Some(trash_path);
+                action.success = true;
+                removed += 1;
+            }
+            Err(e) => {
+                println!("  {} Failed to trash {}: {}", style("●").red(), path, e);
+                action.error = Some(format!("{:#}", e));
+                failed += 1;
+            }
+        }
+        receipt.push(action);
+    }
+
+    Ok((removed, failed))
+}
+
+/// Group binaries without a package manager by their containing directory
+/// (e.g. a pile of old scripts in ~/.local/bin) and offer to remove individual
+/// files rather than whole install roots. Returns (removed, failed).
+fn clean_by_binary_dir(
+    pkgs: &[&PackageGroup],
+    source: &str,
+    db: &storage::Database,
+    no_trash: bool,
+    theme: &dialoguer::theme::ColorfulTheme,
+    receipt: &mut CleanReceipt,
+) -> Result<(usize, usize)> {
+    use dialoguer::MultiSelect;
+
+    let mut by_dir: std::collections::BTreeMap<String, Vec<&storage::BinaryRecord>> =
+        std::collections::BTreeMap::new();
+    for b in pkgs.iter().flat_map(|g| g.binaries.iter()) {
+        // The Nix store is immutable; nix removes its own files
+        if package::is_nix_managed(&b.path) {
+            continue;
+        }
+        let dir = std::path::Path::new(&b.path)
+            .parent()
+            .map(|d| d.display().to_string())
+            .unwrap_or_default();
+        by_dir.entry(dir).or_default().push(b);
+    }
+
+    let (mut removed, mut failed) = (0, 0);
+    let action = if no_trash { "remove" } else { "trash" };
+
+    for (dir, mut bins) in by_dir {
+        bins.sort_by(|a, b| a.path.cmp(&b.path));
+        let names: Vec<String> = bins
+            .iter()
+            .map(|b| {
+                let name = std::path::Path::new(&b.path)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("?");
+                if b.count > 0 {
+                    format!("{} ({}x)", name, b.count)
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        // Pre-select only the never-used files
+        let defaults: Vec<bool> = bins.iter().map(|b| b.count == 0).collect();
+
+        println!();
+        println!(
+            "  {} {} (no package manager -- {} files in {}):",
+            style("●").yellow(),
+            style(source).yellow().bold(),
+            action,
+            style(&dir).bold()
+        );
+
+        let selections = MultiSelect::with_theme(theme)
+            .with_prompt(format!("Select files to {}", action))
+            .items(&names)
+            .defaults(&defaults)
+            .max_length(terminal_fit(10).max(10))
+            .interact_opt()?;
+
+        let indices = match selections {
+            Some(indices) if !indices.is_empty() => indices,
+            _ => {
+                println!("  {} Skipped", style("◦").dim());
+                continue;
+            }
+        };
+
+        for i in indices {
+            let path = bins[i].path.as_str();
+            let name = std::path::Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown");
+
+            let mut action = CleanAction::new(name, source, if no_trash { "rm" } else { "moved" });
+            action.path = Some(path.to_string());
+
+            if no_trash {
+                action.command = Some(format!("rm {}", path));
+                match std::fs::remove_file(path) {
+                    Ok(()) => {
+                        println!("  {} Removed {}", style("●").green(), path);
+                        action.success = true;
+                        removed += 1;
+                    }
+                    Err(e) => {
+                        println!("  {} Failed to remove {}: {}", style("●").red(), path, e);
+                        action.error = Some(e.to_string());
+                        failed += 1;
+                    }
+                }
+                receipt.push(action);
+                continue;
+            }
+
+            match move_to_trash(path, db, source, name) {
+                Ok(trash_path) => {
+                    println!(
+                        "  {} Trashed {} → {}",
+                        style("●").green(),
+                        path,
+                        style(&trash_path).dim()
+                    );
+                    action.command = Some(format!("mv {} {}", path, trash_path));
+                    action.restore_cmd = Some(format!("dusty restore {}", name));
+                    action.trash_path = Some(trash_path);
+                    action.success = true;
+                    removed += 1;
+                }
+                Err(e) => {
+                    println!("  {} Failed to trash {}: {}", style("●").red(), path, e);
+                    action.error = Some(format!("{:#}", e));
+                    failed += 1;
+                }
+            }
+            receipt.push(action);
+        }
+    }
+
+    Ok((removed, failed))
+}
+
+/// Move a directory (or single file) to the trash instead of deleting it.
+/// Returns the trash path on success.
+fn move_to_trash(
+    root: &str,
+    db: &storage::Database,
+    source: &str,
+    package_name: &str,
+) -> Result<String> {
+    if package::is_nix_managed(root) {
+        anyhow::bail!("{} is managed by Nix; remove it with nix-env", root);
+    }
+
+    let trash_dir = storage::data_dir()?.join(defaults::TRASH_DIR);
+    std::fs::create_dir_all(&trash_dir)?;
+
+    let dir_name = std::path::Path::new(root)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    let timestamp = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S");
+    let dest = trash_dir.join(format!("{}_{}", timestamp, dir_name));
+    let dest_str = dest.to_string_lossy().to_string();
+
+    // Try rename (fast if same filesystem), else cross-filesystem or
+    // permission issue: try sudo mv
+    let moved = std::fs::rename(root, &dest).is_ok() || {
+        let status = Command::new(defaults::SUDO)
+            .args(["mv", root, &dest_str])
+            .status()
+            .context("Failed to run sudo mv")?;
+        status.success()
+    };
+    if !moved {
+        anyhow::bail!("Failed to move {} to trash", root)
+    }
+    db.record_trash(root, Some(&dest_str), source, package_name, "moved", None)?;
+    Ok(dest_str)
+}
 
 /// Clean packages from a source that uses list_cmd (e.g., R, pip).
 /// Runs list_cmd to get installed packages, shows MultiSelect, then uninstalls.
@@ -669,10 +2160,38 @@ fn clean_from_list_cmd(
     source: &str,
     list_cmd: &str,
     config: &config::Config,
+    scope: Option<SourceScope>,
     dry_run: bool,
     theme: &dialoguer::theme::ColorfulTheme,
+    receipt: &mut CleanReceipt,
 ) -> Result<()> {
-    use dialoguer::MultiSelect;
+    use dialoguer::{Confirm, MultiSelect};
+
+    let source_scope = config.source_scope(source);
+    if let Some(scope) = scope
+        && source_scope != Some(scope)
+    {
+        println!();
+        println!(
+            "  {} {} is not a {} source (--scope {})",
+            style("●").green().bold(),
+            style(source).bold(),
+            scope.as_str(),
+            scope.as_str()
+        );
+        println!();
+        return Ok(());
+    }
+
+    if platform::is_offline() {
+        println!();
+        println!(
+            "  {} {} packages unknown (offline: list_cmd not run)",
+            style("◦").dim(),
+            style(source).bold()
+        );
+        return Ok(());
+    }
 
     println!();
     println!(
@@ -759,6 +2278,32 @@ fn clean_from_list_cmd(
     };
 
     let selected: Vec<&str> = indices.iter().map(|&i| packages[i].as_str()).collect();
+
+    // System packages can take parts of the OS with them; make that explicit
+    if source_scope == Some(SourceScope::System) {
+        println!();
+        println!(
+            "  {} {} selected packages come from a system source ({}); removing them can break other software:",
+            style("!").magenta().bold(),
+            selected.len(),
+            source
+        );
+        for pkg in &selected {
+            println!("    {} {}", style("•").magenta(), pkg);
+        }
+
+        let confirm = Confirm::with_theme(theme)
+            .with_prompt("Remove these system packages?")
+            .default(false)
+            .interact()?;
+
+        if !confirm {
+            println!("  {} Cancelled", style("◦").dim());
+            println!();
+            return Ok(());
+        }
+    }
+
     let has_template = cmd.contains("%s");
 
     let mut total_removed = 0;
@@ -767,15 +2312,16 @@ fn clean_from_list_cmd(
     if has_template {
         // Template mode: one invocation per package (%s replaced with name)
         for &pkg in &selected {
-            let safe = pkg
-                .chars()
-                .all(|c| c.is_alphanumeric() || "-_.@+".contains(c));
+            let mut action = CleanAction::new(pkg, source, "pm");
+            let safe = is_safe_package_name(pkg);
             if !safe {
                 eprintln!(
                     "  {} Skipping '{}' (unsafe characters)",
                     style("●").red(),
                     pkg
                 );
+                action.error = Some("unsafe characters in name".to_string());
+                receipt.push(action);
                 continue;
             }
 
@@ -787,11 +2333,15 @@ fn clean_from_list_cmd(
                 .status()
                 .context("Failed to run uninstall command")?;
 
+            action.command = Some(full_cmd);
+            action.success = status.success();
             if status.success() {
                 total_removed += 1;
             } else {
+                action.error = Some(format!("uninstall command exited with {}", status));
                 total_failed += 1;
             }
+            receipt.push(action);
         }
     } else {
         // Append mode: batch uninstall
@@ -799,15 +2349,16 @@ fn clean_from_list_cmd(
             .iter()
             .copied()
             .filter(|pkg| {
-                let safe = pkg
-                    .chars()
-                    .all(|c| c.is_alphanumeric() || "-_.@+".contains(c));
+                let safe = is_safe_package_name(pkg);
                 if !safe {
                     eprintln!(
                         "  {} Skipping '{}' (unsafe characters)",
                         style("●").red(),
                         pkg
                     );
+                    let mut action = CleanAction::new(pkg, source, "pm");
+                    action.error = Some("unsafe characters in name".to_string());
+                    receipt.push(action);
                 }
                 safe
             })
@@ -822,6 +2373,16 @@ fn clean_from_list_cmd(
                 .status()
                 .context("Failed to run uninstall command")?;
 
+            for pkg in &safe_pkgs {
+                let mut action = CleanAction::new(pkg, source, "pm");
+                action.command = Some(full_cmd.clone());
+                action.success = status.success();
+                if !status.success() {
+                    action.error = Some(format!("uninstall command exited with {}", status));
+                }
+                receipt.push(action);
+            }
+
             if status.success() {
                 total_removed += safe_pkgs.len();
             } else {
@@ -843,3 +2404,245 @@ fn clean_from_list_cmd(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(source: &str, pkg: &str) -> PackageGroup {
+        PackageGroup {
+            source: source.to_string(),
+            package_name: pkg.to_string(),
+            binaries: vec![storage::BinaryRecord {
+                path: format!("/{}/bin/{}", source, pkg),
+                count: 0,
+                first_seen: None,
+                last_seen: None,
+                source: Some(source.to_string()),
+                package_name: Some(pkg.to_string()),
+                observed: false,
+            }],
+        }
+    }
+
+    /// `user` links a library that `provider` owns
+    fn link(
+        deps: &mut HashMap<String, Vec<String>>,
+        owners: &mut HashMap<String, (String, String)>,
+        user: &PackageGroup,
+        provider: &PackageGroup,
+    ) {
+        let lib = format!("/lib/lib{}.so", provider.package_name);
+        deps.entry(user.binaries[0].path.clone())
+            .or_default()
+            .push(lib.clone());
+        owners.insert(
+            lib,
+            (provider.source.clone(), provider.package_name.clone()),
+        );
+    }
+
+    #[test]
+    fn test_removal_order_across_sources() {
+        // homebrew's tool links apt's libfoo, and apt's app links tool
+        let groups = vec![
+            group("apt", "libfoo"),
+            group("apt", "app"),
+            group("homebrew", "tool"),
+            group("apt", "other"),
+        ];
+        let mut deps = HashMap::new();
+        let mut owners = HashMap::new();
+        link(&mut deps, &mut owners, &groups[2], &groups[0]);
+        link(&mut deps, &mut owners, &groups[1], &groups[2]);
+
+        let order = removal_order(&groups, &[0, 1, 2, 3], &deps, &owners);
+        let pos = |i: usize| order.iter().position(|&o| o == i).unwrap();
+        assert_eq!(order.len(), 4);
+        // app before tool before libfoo, even though two of them are apt's
+        assert!(pos(1) < pos(2));
+        assert!(pos(2) < pos(0));
+        // apt's unrelated package joins app's run instead of splitting it
+        assert_eq!(order, vec![1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_removal_order_groups_sources() {
+        let groups = vec![group("apt", "a"), group("homebrew", "b"), group("apt", "c")];
+        // No constraints: selection order, with each source's packages together
+        let order = removal_order(&groups, &[2, 1, 0], &HashMap::new(), &HashMap::new());
+        assert_eq!(order, vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_version_names() {
+        for (name, expected) in [
+            ("3.11.4", true),
+            ("v20.1.0", true),
+            ("17", true),
+            ("3.13.0rc1", true),
+            ("1x", false),
+            ("2023tools", false),
+            ("v", false),
+            ("", false),
+            ("system", false),
+            ("node-20", false),
+            (".3", false),
+        ] {
+            assert_eq!(is_version_name(name), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_version_dir_and_tool() {
+        for (path, expected) in [
+            (
+                "/home/u/.pyenv/versions/3.11.4/bin/python",
+                Some("/home/u/.pyenv/versions/3.11.4"),
+            ),
+            (
+                "/home/u/.nvm/versions/node/v20.1.0/bin/node",
+                Some("/home/u/.nvm/versions/node/v20.1.0"),
+            ),
+            // The nearest version-named ancestor wins
+            (
+                "/home/u/.asdf/installs/python/3.12.0/lib/2.0/bin/x",
+                Some("/home/u/.asdf/installs/python/3.12.0/lib/2.0"),
+            ),
+            ("/home/u/.cargo/bin/rg", None),
+            // Too close to / to be a version directory
+            ("/opt/1.0/bin", None),
+        ] {
+            assert_eq!(version_dir(path), expected.map(Path::new), "{path}");
+        }
+
+        assert_eq!(
+            tool_at_version(Path::new("/home/u/.asdf/installs/nodejs/20.1.0")).as_deref(),
+            Some("nodejs@20.1.0")
+        );
+        assert_eq!(tool_at_version(Path::new("/")), None);
+    }
+
+    #[test]
+    fn test_is_version_manager_dir() {
+        let roots = [PathBuf::from("/home/u/.pyenv/versions/")];
+        for (dir, expected) in [
+            ("/home/u/.pyenv/versions/3.11.4", true),
+            ("/home/u/.pyenv/versions", false),
+            ("/home/u/.pyenv", false),
+            ("/home/u/.pyenv/versions2/3.11.4", false),
+            ("/usr/local/Cellar/python/3.11.4", false),
+        ] {
+            assert_eq!(
+                is_version_manager_dir(Path::new(dir), &roots),
+                expected,
+                "{dir}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_versions_stays_under_the_root() {
+        // A version-named directory above the root must never be picked
+        let tmp = std::env::temp_dir()
+            .join(format!("dusty-versions-{}", std::process::id()))
+            .join("1.2");
+        let root = tmp.join(".pyenv/versions");
+        for dir in ["3.10.0/bin", "3.11.4/bin", "system/bin", "bin"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("3.9.0"), "not a directory").unwrap();
+        std::fs::create_dir_all(tmp.join(".pyenv/plugins/2.0/bin")).unwrap();
+
+        let binary = |path: PathBuf| storage::BinaryRecord {
+            path: path.display().to_string(),
+            count: 0,
+            first_seen: None,
+            last_seen: None,
+            source: Some("pyenv".to_string()),
+            package_name: Some("python".to_string()),
+            observed: false,
+        };
+        let mut python = group("pyenv", "python");
+        python.binaries = vec![
+            binary(root.join("3.11.4/bin/python")),
+            binary(root.join("bin/pyenv-tool")),
+            binary(tmp.join(".pyenv/plugins/2.0/bin/x")),
+        ];
+
+        let config = config::Config::default();
+        let roots = [root.clone()];
+        let versions = find_versions(&python, &python.binaries, &config, None, &roots);
+        let found: Vec<(&str, Option<PathBuf>)> = versions
+            .iter()
+            .map(|v| (v.name.as_str(), v.dir.as_ref().map(PathBuf::from)))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("3.10.0", Some(root.join("3.10.0"))),
+                ("3.11.4", Some(root.join("3.11.4"))),
+            ]
+        );
+
+        // Binaries outside every root leave nothing to remove
+        python.binaries.remove(0);
+        assert!(find_versions(&python, &python.binaries, &config, None, &roots).is_empty());
+
+        let _ = std::fs::remove_dir_all(tmp.parent().unwrap());
+    }
+
+    #[test]
+    fn test_cleaned_sources_need_a_success() {
+        let mut receipt = CleanReceipt::new(None, None, config::SizeConfig::default());
+        let mut skipped = CleanAction::new("node", "asdf", "pm");
+        skipped.error = Some("no single version to uninstall".to_string());
+        receipt.push(skipped);
+        receipt.push(CleanAction::new("rg", "cargo", "pm"));
+        let mut removed = CleanAction::new("fd", "cargo", "pm");
+        removed.success = true;
+        receipt.push(removed);
+
+        assert_eq!(
+            receipt.cleaned_sources,
+            BTreeSet::from(["cargo".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_receipt_summary() {
+        let key = |source: &str, pkg: &str| (source.to_string(), pkg.to_string());
+        let mut receipt = CleanReceipt::new(None, None, config::SizeConfig::default());
+        receipt.sizes = HashMap::from([
+            (key("cargo", "fd"), Some(1000)),
+            (key("cargo", "rg"), Some(500)),
+            (key("npm", "tsc"), None),
+            (key("apt", "vim"), Some(7000)),
+        ]);
+
+        let action = |pkg: &str, source: &str, method: &'static str, success: bool| {
+            let mut action = CleanAction::new(pkg, source, method);
+            action.success = success;
+            action
+        };
+        let mut skipped = action("node", "asdf", "pm", false);
+        skipped.error = Some("no single version to uninstall".to_string());
+        receipt.actions = vec![
+            // fd went in two steps but counts its size once
+            action("fd", "cargo", "moved", true),
+            action("fd", "cargo", "rm", true),
+            action("rg", "cargo", "pm", true),
+            action("tsc", "npm", "moved", true),
+            // A failed uninstall and a skipped one free nothing
+            action("vim", "apt", "pm", false),
+            skipped,
+        ];
+
+        let summary = receipt.summarize();
+        assert_eq!(summary.removed, 4);
+        assert_eq!(summary.failed, 2);
+        assert_eq!(summary.trashed, 2);
+        assert_eq!(summary.sources, vec!["cargo", "npm"]);
+        assert_eq!(summary.freed_bytes, 1500);
+    }
+}
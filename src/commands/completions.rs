@@ -1,10 +1,198 @@
 use anyhow::Result;
 use clap::CommandFactory;
+use clap_complete::Shell;
+use std::collections::BTreeSet;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, CompleteKind};
+use crate::{config, storage};
 
-pub fn cmd_completions(shell: clap_complete::Shell) -> Result<()> {
+pub fn cmd_completions(shell: Shell) -> Result<()> {
+    print!("{}", completion_script(shell));
+    Ok(())
+}
+
+/// clap's script for `shell`, with the dynamic-completion hooks added
+fn completion_script(shell: Shell) -> String {
     let mut cmd = Cli::command();
-    clap_complete::generate(shell, &mut cmd, "dusty", &mut std::io::stdout());
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, "dusty", &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    match shell {
+        Shell::Bash => bash_dynamic(&script),
+        Shell::Zsh => zsh_dynamic(&script),
+        Shell::Fish => fish_dynamic(&script, &cmd),
+        _ => script.into_owned(),
+    }
+}
+
+/// Print the names for `kind` that start with `prefix`, one per line.
+///
+/// Runs on every <TAB>, so it never syncs, never creates a config or database, and
+/// stays quiet when the config or database can't be read.
+pub fn cmd_complete(kind: CompleteKind, prefix: &str) -> Result<()> {
+    let mut names = BTreeSet::new();
+    if kind == CompleteKind::Sources
+        && config::Config::config_path().is_ok_and(|path| path.exists())
+        && let Ok(config) = config::Config::load()
+    {
+        names.extend(config.sources.into_iter().map(|s| s.name));
+    }
+    if storage::Database::db_path().is_ok_and(|path| path.exists())
+        && let Ok(db) = storage::Database::open()
+        && let Ok(binaries) = db.get_all_binaries()
+    {
+        for binary in binaries {
+            match kind {
+                CompleteKind::Sources => names.extend(binary.source),
+                CompleteKind::Names => {
+                    if let Some(file_name) = binary.path.rsplit('/').next() {
+                        names.insert(file_name.to_string());
+                    }
+                    names.extend(binary.package_name);
+                }
+            }
+        }
+    }
+
+    for name in names.iter().filter(|n| n.starts_with(prefix)) {
+        println!("{}", name);
+    }
     Ok(())
 }
+
+/// Wrap clap's `_dusty` so `--source` values and `why`'s name come from
+/// `dusty __complete`
+fn bash_dynamic(script: &str) -> String {
+    let mut script = script.replace("complete -F _dusty ", "complete -F _dusty_dynamic ");
+    script.push_str(
+        r#"
+_dusty_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local kind="" i words=()
+    case "${prev}" in
+        --source|-s)
+            kind="sources"
+            ;;
+        *)
+            for ((i = 1; i < COMP_CWORD; i++)); do
+                [[ "${COMP_WORDS[i]}" == -* ]] || words+=("${COMP_WORDS[i]}")
+            done
+            if [[ "${words[*]}" == "why" && "${cur}" != -* ]]; then
+                kind="names"
+            fi
+            ;;
+    esac
+    if [[ -n "${kind}" ]]; then
+        COMPREPLY=($(compgen -W "$(dusty __complete "${kind}" "${cur}" 2>/dev/null)" -- "${cur}"))
+        return 0
+    fi
+    _dusty "$@"
+}
+"#,
+    );
+    script
+}
+
+/// Point the `--source` values and `why`'s name at helper functions that call
+/// `dusty __complete`
+fn zsh_dynamic(script: &str) -> String {
+    let mut script = script.replace(":SOURCE:_default'", ":SOURCE:_dusty_sources'");
+
+    // The first `(why)` case is the subcommand itself; later ones are `help why`
+    if let Some(start) = script.find("\n(why)\n") {
+        let end = script[start..]
+            .find("\n;;")
+            .map_or(script.len(), |i| start + i);
+        let case = script[start..end]
+            .lines()
+            .map(|line| match line.strip_suffix(":_default' \\") {
+                Some(head) if line.starts_with("':name -- ") => {
+                    format!("{}:_dusty_names' \\", head)
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        script.replace_range(start..end, &case);
+    }
+
+    let helpers = r#"(( $+functions[_dusty_sources] )) ||
+_dusty_sources() {
+    local -a items
+    items=(${(f)"$(dusty __complete sources "$PREFIX" 2>/dev/null)"})
+    compadd -a items
+}
+(( $+functions[_dusty_names] )) ||
+_dusty_names() {
+    local -a items
+    items=(${(f)"$(dusty __complete names "$PREFIX" 2>/dev/null)"})
+    compadd -a items
+}
+
+"#;
+    match script.find("if [ \"$funcstack[1]\" = \"_dusty\" ]") {
+        Some(at) => script.insert_str(at, helpers),
+        None => script.push_str(helpers),
+    }
+    script
+}
+
+/// Add argument lists to every subcommand's `--source` and to `why`
+fn fish_dynamic(script: &str, cmd: &clap::Command) -> String {
+    let mut script = script.to_string();
+    script.push('\n');
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        if sub.get_arguments().any(|a| a.get_id() == "source") {
+            script.push_str(&format!(
+                "complete -c dusty -n \"__fish_dusty_using_subcommand {}\" -s s -l source -x -a \"(dusty __complete sources 2>/dev/null)\"\n",
+                sub.get_name()
+            ));
+        }
+    }
+    script.push_str(
+        "complete -c dusty -n \"__fish_dusty_using_subcommand why\" -f -a \"(dusty __complete names 2>/dev/null)\"\n",
+    );
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash_hooks() {
+        let script = completion_script(Shell::Bash);
+        assert!(script.contains("complete -F _dusty_dynamic "));
+        assert!(!script.contains("complete -F _dusty "));
+        assert!(script.contains("_dusty_dynamic() {"));
+    }
+
+    #[test]
+    fn test_zsh_hooks() {
+        let script = completion_script(Shell::Zsh);
+        assert!(script.contains(":SOURCE:_dusty_sources'"));
+        assert!(!script.contains(":SOURCE:_default'"));
+        assert!(script.contains(":_dusty_names' \\"));
+
+        // The helpers must be defined before clap's entry point runs
+        let helpers = script.find("_dusty_sources() {").unwrap();
+        let entry = script
+            .find("if [ \"$funcstack[1]\" = \"_dusty\" ]")
+            .unwrap();
+        assert!(helpers < entry);
+    }
+
+    #[test]
+    fn test_fish_hooks() {
+        let script = completion_script(Shell::Fish);
+        assert!(script.contains(
+            "__fish_dusty_using_subcommand report\" -s s -l source -x -a \"(dusty __complete sources 2>/dev/null)\""
+        ));
+        assert!(script.contains(
+            "__fish_dusty_using_subcommand why\" -f -a \"(dusty __complete names 2>/dev/null)\""
+        ));
+        // The function the hooks rely on comes from clap's script
+        assert!(script.contains("function __fish_dusty_using_subcommand"));
+    }
+}
@@ -1,16 +1,43 @@
 use anyhow::{Context, Result};
 use console::style;
+use std::collections::BTreeSet;
+use std::path::Path;
 use std::process::Command;
+use toml::{Table, Value};
 
 use crate::defaults;
 
-pub fn cmd_config(edit: bool) -> Result<()> {
+pub fn cmd_config(edit: bool, reset: bool, default: bool, diff: bool, migrate: bool) -> Result<()> {
     use crate::config::Config;
 
-    // Load config (auto-creates if not exists)
-    let _config = Config::load()?;
     let path = Config::config_path()?;
 
+    // Neither of these loads the current file, so they work even if it no longer parses
+    if default {
+        let content = toml::to_string_pretty(&Config::default())?;
+        println!();
+        println!("  {} (not written)", style("Default config").bold());
+        println!();
+        print_toml(&content);
+        return Ok(());
+    }
+
+    if reset {
+        return reset_config(&path);
+    }
+
+    // Works on the raw file, so a config the current version rejects can still be upgraded
+    if migrate {
+        return migrate_config(&path);
+    }
+
+    // Load config (auto-creates if not exists)
+    let config = Config::load()?;
+
+    if diff {
+        return print_diff(&path, &config);
+    }
+
     if edit {
         let editor =
             std::env::var("EDITOR").unwrap_or_else(|_| defaults::DEFAULT_EDITOR.to_string());
@@ -27,14 +54,290 @@ pub fn cmd_config(edit: bool) -> Result<()> {
     println!();
 
     let content = std::fs::read_to_string(&path)?;
+    print_toml(&content);
+
+    Ok(())
+}
+
+/// One changed line: '+' only in the current config, '-' only in the
+/// defaults, '~' a value that differs.
+type DiffLine = (char, String);
+
+fn print_diff(path: &Path, config: &crate::config::Config) -> Result<()> {
+    let sections = diff_config(config, &crate::config::Config::default())?;
+
+    println!();
+    println!(
+        "  {} {} vs. defaults",
+        style("Config:").bold(),
+        path.display()
+    );
+    println!();
+    if sections.is_empty() {
+        println!(
+            "  {} No differences from the default config",
+            style("◦").dim()
+        );
+        println!();
+        return Ok(());
+    }
+    for (section, lines) in &sections {
+        println!("    {}", style(section).cyan().bold());
+        for (sign, text) in lines {
+            let line = format!("{} {}", sign, text);
+            match sign {
+                '+' => println!("      {}", style(line).green()),
+                '-' => println!("      {}", style(line).red()),
+                _ => println!("      {}", style(line).yellow()),
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Compare two configs field by field, grouped by section. Sources are
+/// matched by name, so reordering them isn't reported as a change.
+fn diff_config(
+    current: &crate::config::Config,
+    default: &crate::config::Config,
+) -> Result<Vec<(String, Vec<DiffLine>)>> {
+    let (Value::Table(current), Value::Table(default)) =
+        (Value::try_from(current)?, Value::try_from(default)?)
+    else {
+        anyhow::bail!("config did not serialize to a table");
+    };
+
+    let mut sections = Vec::new();
+    let mut top_level = Vec::new();
+    for key in union_keys(&current, &default) {
+        let (cur, def) = (current.get(key), default.get(key));
+        let lines = match (cur, def) {
+            _ if key == "sources" => diff_sources(cur, def),
+            (Some(Value::Table(a)), Some(Value::Table(b))) => diff_table(a, b, ""),
+            _ => {
+                diff_value(key, cur, def, &mut top_level);
+                continue;
+            }
+        };
+        if !lines.is_empty() {
+            let header = if key == "sources" {
+                "[[sources]]".to_string()
+            } else {
+                format!("[{}]", key)
+            };
+            sections.push((header, lines));
+        }
+    }
+    if !top_level.is_empty() {
+        sections.insert(0, ("(top level)".to_string(), top_level));
+    }
+    Ok(sections)
+}
+
+fn union_keys<'a>(a: &'a Table, b: &'a Table) -> BTreeSet<&'a str> {
+    a.keys().chain(b.keys()).map(String::as_str).collect()
+}
+
+fn diff_table(current: &Table, default: &Table, prefix: &str) -> Vec<DiffLine> {
+    let mut lines = Vec::new();
+    for key in union_keys(current, default) {
+        let name = format!("{}{}", prefix, key);
+        diff_value(&name, current.get(key), default.get(key), &mut lines);
+    }
+    lines
+}
+
+/// Lists are diffed item by item (extra dirs, ignore patterns, ...)
+fn diff_value(
+    key: &str,
+    current: Option<&Value>,
+    default: Option<&Value>,
+    out: &mut Vec<DiffLine>,
+) {
+    match (current, default) {
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for item in a.iter().filter(|v| !b.contains(v)) {
+                out.push(('+', format!("{} {}", key, inline(item))));
+            }
+            for item in b.iter().filter(|v| !a.contains(v)) {
+                out.push(('-', format!("{} {}", key, inline(item))));
+            }
+        }
+        (Some(a), Some(b)) if a != b => {
+            out.push(('~', format!("{}: {} → {}", key, inline(b), inline(a))));
+        }
+        (Some(a), None) => out.push(('+', format!("{} = {}", key, inline(a)))),
+        (None, Some(b)) => out.push(('-', format!("{} = {}", key, inline(b)))),
+        _ => {}
+    }
+}
+
+fn diff_sources(current: Option<&Value>, default: Option<&Value>) -> Vec<DiffLine> {
+    let by_name = |v: Option<&Value>| -> Vec<(String, Table)> {
+        v.and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.as_table())
+            .map(|t| {
+                let name = t.get("name").and_then(Value::as_str).unwrap_or("?");
+                (name.to_string(), t.clone())
+            })
+            .collect()
+    };
+    let (current, default) = (by_name(current), by_name(default));
+    let find = |list: &[(String, Table)], name: &str| {
+        list.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone())
+    };
+
+    let mut lines = Vec::new();
+    for (name, table) in &current {
+        match find(&default, name) {
+            Some(def) => lines.extend(diff_table(table, &def, &format!("{}.", name))),
+            None => lines.push(('+', source_summary(name, table))),
+        }
+    }
+    for (name, table) in &default {
+        if find(&current, name).is_none() {
+            lines.push(('-', source_summary(name, table)));
+        }
+    }
+    lines
+}
+
+fn source_summary(name: &str, source: &Table) -> String {
+    match source.get("path").and_then(Value::as_str) {
+        Some(path) => format!("{} ({})", name, path),
+        None => name.to_string(),
+    }
+}
+
+/// Render a value on one line; tables (e.g. path shorthands) become inline tables
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Table(t) => {
+            let fields: Vec<String> = t
+                .iter()
+                .map(|(k, v)| format!("{} = {}", k, inline(v)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// `config.toml.<timestamp>.bak`, so a later reset or migration doesn't
+/// overwrite an earlier backup
+fn backup_path(path: &Path) -> std::path::PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    path.with_extension(format!("toml.{}.bak", stamp))
+}
+
+/// Back up the current config to `config.toml.<timestamp>.bak` and write fresh defaults
+/// (re-running source auto-detection).
+fn reset_config(path: &Path) -> Result<()> {
+    use crate::config::Config;
+    use dialoguer::{Confirm, theme::ColorfulTheme};
+
+    let backup = backup_path(path);
+
+    if path.exists() {
+        let confirm = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Replace {} with defaults? (current file is saved to {})",
+                path.display(),
+                backup.display()
+            ))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            println!("  {} Cancelled", style("◦").dim());
+            return Ok(());
+        }
+
+        std::fs::copy(path, &backup)
+            .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    }
+
+    Config::default().save()?;
+
+    println!();
+    println!(
+        "  {} Config reset to defaults: {}",
+        style("●").green(),
+        path.display()
+    );
+    if backup.exists() {
+        println!(
+            "  {} Previous config saved to {}",
+            style("◦").dim(),
+            backup.display()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// Fill in settings and detected sources the config file predates, after
+/// backing it up to `config.toml.<timestamp>.bak`
+fn migrate_config(path: &Path) -> Result<()> {
+    use crate::config::Config;
+
+    println!();
+    if !path.exists() {
+        println!(
+            "  {} No config at {} yet; dusty writes a current one on first use",
+            style("◦").dim(),
+            path.display()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let migration = Config::migrate(&content, &Config::default())
+        .with_context(|| format!("Failed to migrate {}", path.display()))?;
+    if migration.added.is_empty() {
+        println!(
+            "  {} {} is already up to date",
+            style("◦").dim(),
+            path.display()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let backup = backup_path(path);
+    std::fs::copy(path, &backup)
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    std::fs::write(path, &migration.content)?;
+
+    println!("  {} Migrated {}", style("●").green(), path.display());
+    println!();
+    for item in &migration.added {
+        println!("      {}", style(format!("+ {}", item)).green());
+    }
+    println!();
+    println!(
+        "  {} Previous config saved to {}",
+        style("◦").dim(),
+        backup.display()
+    );
+    println!();
+    Ok(())
+}
+
+fn print_toml(content: &str) {
     for line in content.lines() {
         print!("    ");
         print_toml_line(line);
         println!();
     }
     println!();
-
-    Ok(())
 }
 
 fn print_toml_line(line: &str) {
@@ -93,3 +396,100 @@ fn print_toml_value(value: &str) {
         print!("{}", style(value).yellow());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_diff_config() {
+        let default = config(
+            r#"
+            [report]
+            version_suffix = "-rc"
+            [[sources]]
+            name = "cargo"
+            path = "~/.cargo/bin"
+            [[sources]]
+            name = "go"
+            path = "~/go/bin"
+            "#,
+        );
+        let current = config(
+            r#"
+            [scan]
+            extra_dirs = ["/opt/tools/bin"]
+            [report]
+            grace_days = 3
+            [daemon]
+            log_file = "~/dusty.log"
+            [[sources]]
+            name = "npm"
+            path = "~/.npm-global/bin"
+            [[sources]]
+            name = "go"
+            path = "/usr/local/go/bin"
+            uninstall_cmd = "rm"
+            "#,
+        );
+
+        let diff = diff_config(&current, &default).unwrap();
+        let lines = |header: &str| -> Vec<DiffLine> {
+            diff.iter()
+                .find(|(h, _)| h == header)
+                .map(|(_, lines)| lines.clone())
+                .unwrap_or_default()
+        };
+        let headers: Vec<&str> = diff.iter().map(|(h, _)| h.as_str()).collect();
+        assert_eq!(
+            headers,
+            vec!["[daemon]", "[report]", "[scan]", "[[sources]]"]
+        );
+
+        assert_eq!(
+            lines("[daemon]"),
+            vec![('+', "log_file = \"~/dusty.log\"".to_string())]
+        );
+
+        assert_eq!(
+            lines("[scan]"),
+            vec![('+', "extra_dirs \"/opt/tools/bin\"".to_string())]
+        );
+        assert_eq!(
+            lines("[report]"),
+            vec![
+                ('~', "grace_days: 7 → 3".to_string()),
+                ('-', "version_suffix = \"-rc\"".to_string()),
+            ]
+        );
+        assert_eq!(
+            lines("[[sources]]"),
+            vec![
+                ('+', "npm (~/.npm-global/bin)".to_string()),
+                (
+                    '~',
+                    "go.path: \"~/go/bin\" → \"/usr/local/go/bin\"".to_string()
+                ),
+                ('+', "go.uninstall_cmd = \"rm\"".to_string()),
+                ('-', "cargo (~/.cargo/bin)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_config_ignores_source_order() {
+        let a = config(
+            "[[sources]]\nname = \"cargo\"\npath = \"~/.cargo/bin\"\n[[sources]]\nname = \"go\"\npath = \"~/go/bin\"\n",
+        );
+        let b = config(
+            "[[sources]]\nname = \"go\"\npath = \"~/go/bin\"\n[[sources]]\nname = \"cargo\"\npath = \"~/.cargo/bin\"\n",
+        );
+        assert!(diff_config(&a, &b).unwrap().is_empty());
+        assert!(diff_config(&a, &a).unwrap().is_empty());
+    }
+}
@@ -1,94 +1,277 @@
 use anyhow::Result;
 use chrono::Local;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::config;
-use crate::platform::{Monitor, ProcessMonitor};
+use crate::config::{self, LogLevel};
+use crate::platform::{
+    DaemonLogger, ExecEvent, IngestMonitor, Monitor, ProcessMonitor, daemon_log, on_battery,
+};
 use crate::storage::Database;
 
-pub fn cmd_daemon() -> Result<()> {
+/// Exec event waiting to be written: (path, source, unix timestamp, argv[0])
+type PendingExec = (String, String, i64, Option<String>);
+
+/// Flush cadence under `[daemon] power_aware`
+const BATTERY_FLUSH_SECS: u64 = 300;
+const IDLE_FLUSH_SECS: u64 = 60;
+const DEFAULT_FLUSH_SECS: u64 = 30;
+const BUSY_FLUSH_SECS: u64 = 5;
+/// Exec rates (per minute) below/above which the daemon counts as idle/busy
+const IDLE_EXECS_PER_MIN: u64 = 10;
+const BUSY_EXECS_PER_MIN: u64 = 300;
+/// Flush early once this many execs are buffered, whatever the interval
+const MAX_PENDING: usize = 1000;
+/// How often the event loop stamps `daemon_heartbeat_ts` so `dusty status` can tell it's live
+const DB_HEARTBEAT_SECS: u64 = 30;
+
+/// Set by the SIGTERM/SIGINT handler; the main loop polls it between events
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_stop(_signal: libc::c_int) {
+    // Only async-signal-safe work here: flip the flag and let the loop exit
+    STOP_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+fn install_signal_handlers() {
+    let handler = request_stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+pub fn cmd_daemon(
+    ingest: Option<std::path::PathBuf>,
+    log_file: Option<std::path::PathBuf>,
+) -> Result<()> {
     use std::sync::mpsc::RecvTimeoutError;
     use std::time::{Duration, Instant};
 
     let db = Database::open()?;
     let config = config::Config::load()?;
-    let mut monitor = Monitor::new();
+    DaemonLogger::new(
+        &config.daemon,
+        log_file.or_else(|| config.daemon.log_path()),
+    )?
+    .install();
+    let mut monitor: Box<dyn ProcessMonitor> = match (ingest, config.daemon.backend) {
+        (Some(fifo), _) => Box::new(IngestMonitor::with_fifo(Some(fifo))),
+        (None, config::MonitorBackend::Stdin) => Box::new(IngestMonitor::new()),
+        (None, config::MonitorBackend::Native) => Box::new(Monitor::new()),
+    };
 
     let source_names: Vec<&str> = config.sources.iter().map(|s| s.name.as_str()).collect();
-    println!(
-        "[{}] dusty daemon starting (db: {}, sources: {})",
-        Local::now().format("%Y-%m-%d %H:%M:%S"),
-        Database::db_path()
-            .map(|p| p.display().to_string())
-            .unwrap_or_else(|_| "?".into()),
-        source_names.join(", "),
+    daemon_log(
+        LogLevel::Info,
+        format_args!(
+            "dusty daemon starting (db: {}, sources: {})",
+            Database::db_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| "?".into()),
+            source_names.join(", "),
+        ),
     );
 
+    install_signal_handlers();
     let rx = monitor.start()?;
+    record_monitor_meta(&db, monitor.as_ref());
 
-    println!(
-        "[{}] listening for exec events",
-        Local::now().format("%Y-%m-%d %H:%M:%S"),
-    );
+    daemon_log(LogLevel::Info, "listening for exec events");
+
+    let power_aware = config.daemon.power_aware;
+    if power_aware {
+        daemon_log(
+            LogLevel::Info,
+            format_args!(
+                "power-aware mode: buffering execs (power state: {})",
+                power_label(on_battery()),
+            ),
+        );
+    }
 
     let heartbeat = Duration::from_secs(3600);
+    // Wake up regularly so a stop signal is noticed even when no execs arrive
+    let poll = Duration::from_secs(1);
     let mut last_heartbeat = Instant::now();
+    write_heartbeat(&db);
+    let mut last_db_heartbeat = Instant::now();
     let mut period_recorded: u64 = 0;
     let mut period_skipped: u64 = 0;
     let mut total_recorded: u64 = 0;
 
+    let mut pending: Vec<PendingExec> = Vec::new();
+    let mut last_flush = Instant::now();
+    let mut rate_window_start = Instant::now();
+    let mut window_execs: u64 = 0;
+    let mut execs_per_min: u64 = 0;
+    // pmset is a subprocess on macOS, so the power state is re-read once a minute
+    let mut battery = on_battery();
+    let mut last_power_check = Instant::now();
+
     loop {
-        match rx.recv_timeout(heartbeat) {
-            Ok(path) => {
+        if STOP_REQUESTED.load(Ordering::SeqCst) {
+            monitor.stop().ok();
+            flush_pending(&db, &mut pending);
+            daemon_log(
+                LogLevel::Info,
+                format_args!(
+                    "daemon stopping cleanly (total recorded: {})",
+                    total_recorded
+                ),
+            );
+            break;
+        }
+
+        match rx.recv_timeout(poll) {
+            Ok(ExecEvent {
+                path,
+                invoked_as,
+                source,
+            }) => {
                 if should_skip_path(&path, &config) {
+                    daemon_log(LogLevel::Debug, format_args!("skipped {}", path));
                     period_skipped += 1;
                     continue;
                 }
-                let source = config.categorize_path(&path);
-                if let Err(e) = db.record_exec(&path, Some(&source)) {
-                    eprintln!(
-                        "[{}] error recording {}: {}",
-                        Local::now().format("%H:%M:%S"),
-                        path,
-                        e
+                let source = source.unwrap_or_else(|| config.categorize_path(&path));
+                daemon_log(LogLevel::Debug, format_args!("exec {} ({})", path, source));
+                if power_aware {
+                    pending.push((path, source, Local::now().timestamp(), invoked_as));
+                } else if let Err(e) = db.record_exec(&path, Some(&source), invoked_as.as_deref()) {
+                    daemon_log(
+                        LogLevel::Error,
+                        format_args!("failed to record {}: {}", path, e),
                     );
                 }
+                window_execs += 1;
                 period_recorded += 1;
                 total_recorded += 1;
             }
             Err(RecvTimeoutError::Disconnected) => {
-                println!(
-                    "[{}] monitor disconnected, shutting down (total recorded: {})",
-                    Local::now().format("%Y-%m-%d %H:%M:%S"),
-                    total_recorded,
+                flush_pending(&db, &mut pending);
+                daemon_log(
+                    LogLevel::Info,
+                    format_args!(
+                        "monitor disconnected, shutting down (total recorded: {})",
+                        total_recorded,
+                    ),
                 );
                 break;
             }
             Err(RecvTimeoutError::Timeout) => {}
         }
 
+        if power_aware {
+            if rate_window_start.elapsed() >= Duration::from_secs(60) {
+                execs_per_min = window_execs;
+                window_execs = 0;
+                rate_window_start = Instant::now();
+            }
+            if last_power_check.elapsed() >= Duration::from_secs(60) {
+                battery = on_battery();
+                last_power_check = Instant::now();
+            }
+            let interval = flush_interval(battery, execs_per_min);
+            if pending.len() >= MAX_PENDING
+                || (!pending.is_empty() && last_flush.elapsed() >= interval)
+            {
+                flush_pending(&db, &mut pending);
+                last_flush = Instant::now();
+            }
+        }
+
+        if last_db_heartbeat.elapsed() >= Duration::from_secs(DB_HEARTBEAT_SECS) {
+            write_heartbeat(&db);
+            last_db_heartbeat = Instant::now();
+        }
+
         if last_heartbeat.elapsed() >= heartbeat {
-            #[cfg(target_os = "macos")]
             let parse_errors = monitor.take_parse_errors();
-            #[cfg(not(target_os = "macos"))]
-            let parse_errors = 0u64;
-
-            println!(
-                "[{}] heartbeat: {} recorded, {} skipped, {} parse errors this hour (total: {})",
-                Local::now().format("%Y-%m-%d %H:%M:%S"),
-                period_recorded,
-                period_skipped,
-                parse_errors,
-                total_recorded,
+
+            daemon_log(
+                LogLevel::Info,
+                format_args!(
+                    "heartbeat: {} recorded, {} skipped, {} parse errors this hour (total: {})",
+                    period_recorded, period_skipped, parse_errors, total_recorded,
+                ),
             );
             period_recorded = 0;
             period_skipped = 0;
             last_heartbeat = Instant::now();
+            // The monitor may have been respawned with a new pid since startup
+            record_monitor_meta(&db, monitor.as_ref());
+            // Keep the write-ahead log from growing unbounded between restarts
+            if let Err(e) = db.checkpoint() {
+                daemon_log(
+                    LogLevel::Error,
+                    format_args!("failed to checkpoint database: {}", e),
+                );
+            }
         }
     }
 
     Ok(())
 }
 
+/// How long to buffer execs: long on battery or when idle, short under heavy activity
+fn flush_interval(on_battery: Option<bool>, execs_per_min: u64) -> std::time::Duration {
+    let secs = if on_battery == Some(true) {
+        BATTERY_FLUSH_SECS
+    } else if execs_per_min > BUSY_EXECS_PER_MIN {
+        BUSY_FLUSH_SECS
+    } else if execs_per_min < IDLE_EXECS_PER_MIN {
+        IDLE_FLUSH_SECS
+    } else {
+        DEFAULT_FLUSH_SECS
+    };
+    std::time::Duration::from_secs(secs)
+}
+
+fn power_label(on_battery: Option<bool>) -> &'static str {
+    match on_battery {
+        Some(true) => "battery",
+        Some(false) => "AC",
+        None => "unknown",
+    }
+}
+
+/// Write buffered execs in one transaction; on failure they are dropped and logged
+fn flush_pending(db: &Database, pending: &mut Vec<PendingExec>) {
+    if pending.is_empty() {
+        return;
+    }
+    if let Err(e) = db.record_execs(pending) {
+        daemon_log(
+            LogLevel::Error,
+            format_args!("failed to record {} buffered execs: {}", pending.len(), e),
+        );
+    }
+    pending.clear();
+}
+
+fn write_heartbeat(db: &Database) {
+    if let Err(e) = db.set_heartbeat(Local::now().timestamp()) {
+        daemon_log(
+            LogLevel::Error,
+            format_args!("failed to write heartbeat: {}", e),
+        );
+    }
+}
+
+/// Publish the live monitoring backend so `dusty status` can report it
+fn record_monitor_meta(db: &Database, monitor: &dyn ProcessMonitor) {
+    let pid = monitor.pid().map(|p| p.to_string()).unwrap_or_default();
+    let result = db
+        .set_meta("monitor_backend", monitor.backend_name())
+        .and_then(|_| db.set_meta("monitor_pid", &pid));
+    if let Err(e) = result {
+        daemon_log(
+            LogLevel::Error,
+            format_args!("failed to record monitor backend: {}", e),
+        );
+    }
+}
+
 fn should_skip_path(path: &str, config: &config::Config) -> bool {
     let skip_exact = ["/bin/sh", "/bin/bash", "/bin/zsh", "/usr/bin/env"];
 
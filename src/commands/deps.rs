@@ -2,36 +2,67 @@ use anyhow::Result;
 use console::style;
 use std::cell::RefCell;
 
+use crate::cli::GraphFormat;
 use crate::deps;
+use crate::platform::is_offline;
 use crate::storage::Database;
-use crate::ui::{Spinner, format_bytes, shorten_path, truncate_str};
-use crate::utils::sync_binaries;
+use crate::ui::{Spinner, Timer, format_bytes, print_json, shorten_path, truncate_str};
+use crate::utils::{resolve_binary_arg, sync_binaries};
 
 pub fn cmd_deps(
     orphans_only: bool,
     binary: Option<String>,
     refresh: bool,
+    manager: Option<String>,
+    min_size: Option<u64>,
+    graph: Option<GraphFormat>,
     json: bool,
 ) -> Result<()> {
     let db = Database::open()?;
     sync_binaries(&db)?;
 
+    if let Some(format) = graph {
+        let graph = deps::build_dep_graph(&db, manager.as_deref())?;
+        if graph.nodes.is_empty() {
+            eprintln!(
+                "  {} No cached dependency data; run {} first",
+                style("◦").dim(),
+                style("dusty deps").cyan()
+            );
+        }
+        return match format {
+            _ if json => print_json(&graph),
+            GraphFormat::Json => print_json(&graph),
+            GraphFormat::Dot => {
+                print!("{}", render_dot(&graph));
+                Ok(())
+            }
+        };
+    }
+
     // Single binary mode
-    if let Some(binary_path) = binary {
+    if let Some(binary) = binary {
+        let binary_path = resolve_binary_arg(&db, &binary)?;
         let result = deps::analyze_single_binary(&db, &binary_path)?;
 
         if json {
-            println!("{}", serde_json::to_string_pretty(&result)?);
+            print_json(&result)?;
             return Ok(());
         }
 
         println!();
 
         if result.libs.is_empty() {
+            let reason = match result.linkage.as_str() {
+                "static" => "statically linked — no shared library dependencies",
+                "script" => "is a script — no shared library dependencies of its own",
+                _ => "only links system libraries",
+            };
             println!(
-                "  {} {} has no dynamic library dependencies",
+                "  {} {} {}",
                 style("◦").dim(),
-                shorten_path(&result.binary_path)
+                shorten_path(&result.binary_path),
+                reason
             );
             println!();
             return Ok(());
@@ -70,9 +101,11 @@ pub fn cmd_deps(
 
     // Full analysis mode
     let spinner = RefCell::new(Spinner::new());
-    let report = deps::analyze_deps(
+    let timer = Timer::start("dep analysis");
+    let mut report = deps::analyze_deps(
         &db,
         refresh,
+        manager.as_deref(),
         Some(&|current, total| {
             spinner
                 .borrow_mut()
@@ -80,9 +113,19 @@ pub fn cmd_deps(
         }),
     )?;
     spinner.into_inner().finish();
+    drop(timer);
+
+    // Orphans of unknown size stay listed; there's no telling they're small
+    if let Some(min) = min_size {
+        let before = report.orphan_packages.len();
+        report
+            .orphan_packages
+            .retain(|o| o.size_bytes.is_none_or(|size| size >= min));
+        report.hidden_orphans = before - report.orphan_packages.len();
+    }
 
     if json {
-        println!("{}", serde_json::to_string_pretty(&report)?);
+        print_json(&report)?;
         return Ok(());
     }
 
@@ -99,14 +142,42 @@ pub fn cmd_deps(
             style("◦").dim(),
             report.total_lib_packages
         );
+        if report.static_binaries > 0 {
+            println!(
+                "  {} {} statically linked (no shared library dependencies)",
+                style("◦").dim(),
+                report.static_binaries
+            );
+        }
+        if report.failed_binaries > 0 {
+            println!(
+                "  {} {} could not be analyzed (retried next run)",
+                style("!").yellow(),
+                report.failed_binaries
+            );
+        }
     }
 
     if report.orphan_packages.is_empty() {
+        let scope = manager
+            .as_deref()
+            .map(|m| format!(" from {}", m))
+            .unwrap_or_default();
         println!();
-        println!(
-            "  {} No orphan library packages found",
-            style("●").green().bold()
-        );
+        match min_size {
+            Some(min) if report.hidden_orphans > 0 => println!(
+                "  {} {} orphan packages{}, all under {} and hidden by --min-size",
+                style("◦").dim(),
+                report.hidden_orphans,
+                scope,
+                format_bytes(min)
+            ),
+            _ => println!(
+                "  {} No orphan library packages found{}",
+                style("●").green().bold(),
+                scope
+            ),
+        }
         println!();
         return Ok(());
     }
@@ -161,7 +232,75 @@ pub fn cmd_deps(
             .green()
             .bold()
     );
+    print_hidden_orphans(&report, min_size);
+    if is_offline() {
+        println!(
+            "  {} Offline: sizes not already cached are unknown (?) and left out of the total",
+            style("◦").dim()
+        );
+    }
     println!();
 
     Ok(())
 }
+
+fn print_hidden_orphans(report: &deps::DepsReport, min_size: Option<u64>) {
+    if let Some(min) = min_size
+        && report.hidden_orphans > 0
+    {
+        println!(
+            "  {} {} smaller orphan packages (under {}) hidden by --min-size",
+            style("◦").dim(),
+            report.hidden_orphans,
+            format_bytes(min)
+        );
+    }
+}
+
+/// Graphviz rendering: binaries are boxes, libraries ellipses, packages folders.
+/// Dusty binaries and orphan packages are drawn in gray.
+fn render_dot(graph: &deps::DepGraph) -> String {
+    use std::fmt::Write;
+
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph dusty {\n  rankdir=LR;\n");
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            "binary" => "box",
+            "package" => "folder",
+            _ => "ellipse",
+        };
+        // Escape each line, then join with DOT's own line break
+        let mut lines = vec![escape(&node.label)];
+        if let Some(count) = node.count {
+            lines.push(format!("{} uses", count));
+        }
+        if let Some(size) = node.size_bytes {
+            lines.push(format_bytes(size));
+        }
+        let color = match node.status {
+            Some("dusty" | "orphan") => ", color=gray, fontcolor=gray",
+            _ => "",
+        };
+        writeln!(
+            out,
+            "  \"{}\" [shape={}, label=\"{}\"{}];",
+            escape(&node.id),
+            shape,
+            lines.join("\\n"),
+            color
+        )
+        .ok();
+    }
+    for edge in &graph.edges {
+        writeln!(
+            out,
+            "  \"{}\" -> \"{}\";",
+            escape(&edge.from),
+            escape(&edge.to)
+        )
+        .ok();
+    }
+    out.push_str("}\n");
+    out
+}
@@ -1,13 +1,36 @@
 use anyhow::Result;
-use chrono::{DateTime, Local};
 use console::style;
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
 use crate::storage::{self, Database};
-use crate::ui::{print_with_pager, shorten_path, terminal_fit, truncate_str};
-use crate::utils::{local_datetime, sync_binaries};
+use crate::ui::{
+    OutputFormat, emit, print_with_pager, shorten_path, styled_stdout, terminal_fit, truncate_str,
+};
+use crate::utils::{format_date, json_timestamp, sync_binaries};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct DupeGroup {
+    name: String,
+    copies: Vec<DupeCopy>,
+    /// Copy the shell runs: the one in the earliest PATH directory
+    path_winner: Option<String>,
+    /// Most-used copy (None when every copy is unused)
+    usage_winner: Option<String>,
+    /// Every copy that is neither winner
+    recommended_remove: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DupeCopy {
+    path: String,
+    source: Option<String>,
+    count: i64,
+    last_used: Option<String>,
+}
 
-pub fn cmd_dupes(name: Option<String>, all: bool, json: bool) -> Result<()> {
+pub fn cmd_dupes(name: Option<String>, all: bool, format: OutputFormat) -> Result<()> {
     let db = Database::open()?;
     sync_binaries(&db)?;
 
@@ -58,43 +81,54 @@ pub fn cmd_dupes(name: Option<String>, all: bool, json: bool) -> Result<()> {
 
     // Sort copies within each group by count desc
     for (_, copies) in &mut dupes {
-        copies.sort_by(|a, b| b.count.cmp(&a.count));
+        copies.sort_by_key(|c| std::cmp::Reverse(c.count));
     }
 
-    if json {
-        #[derive(serde::Serialize)]
-        struct DupeGroup {
-            name: String,
-            copies: Vec<DupeCopy>,
-        }
-        #[derive(serde::Serialize)]
-        struct DupeCopy {
-            path: String,
-            source: Option<String>,
-            count: i64,
-            last_used: Option<String>,
-        }
-
+    if !format.is_table() {
+        let path_dirs = path_dirs();
         let groups: Vec<DupeGroup> = dupes
             .iter()
-            .map(|(name, copies)| DupeGroup {
-                name: name.clone(),
-                copies: copies
+            .map(|(name, copies)| {
+                let path_winner = copies
                     .iter()
-                    .map(|c| DupeCopy {
-                        path: c.path.clone(),
-                        source: c.source.clone(),
-                        count: c.count,
-                        last_used: c.last_seen.map(|ts| {
-                            let dt: DateTime<Local> = local_datetime(ts);
-                            dt.format("%Y-%m-%d %H:%M").to_string()
-                        }),
-                    })
-                    .collect(),
+                    .filter_map(|c| path_rank(&c.path, &path_dirs).map(|rank| (rank, c)))
+                    .min_by_key(|(rank, _)| *rank)
+                    .map(|(_, c)| c.path.clone());
+                let usage_winner = copies
+                    .first()
+                    .filter(|c| c.count > 0)
+                    .map(|c| c.path.clone());
+                let recommended_remove = if path_winner.is_none() && usage_winner.is_none() {
+                    Vec::new()
+                } else {
+                    copies
+                        .iter()
+                        .filter(|c| {
+                            path_winner.as_ref() != Some(&c.path)
+                                && usage_winner.as_ref() != Some(&c.path)
+                        })
+                        .map(|c| c.path.clone())
+                        .collect()
+                };
+                DupeGroup {
+                    name: name.clone(),
+                    copies: copies
+                        .iter()
+                        .map(|c| DupeCopy {
+                            path: c.path.clone(),
+                            source: c.source.clone(),
+                            count: c.count,
+                            last_used: c.last_seen.map(json_timestamp),
+                        })
+                        .collect(),
+                    path_winner,
+                    usage_winner,
+                    recommended_remove,
+                }
             })
             .collect();
 
-        println!("{}", serde_json::to_string_pretty(&groups)?);
+        emit(&groups, format)?;
         return Ok(());
     }
 
@@ -136,7 +170,7 @@ pub fn cmd_dupes(name: Option<String>, all: bool, json: bool) -> Result<()> {
     // Expanded mode: show full details for all groups (with pager)
     if all {
         use std::fmt::Write;
-        let is_term = console::Term::stdout().is_term();
+        let is_term = styled_stdout();
         let mut out = String::new();
         writeln!(out).unwrap();
         for (name, copies) in &dupes {
@@ -263,6 +297,33 @@ pub fn cmd_dupes(name: Option<String>, all: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Directories of $PATH in lookup order, canonicalized where possible so
+/// symlinked entries (e.g. /bin -> /usr/bin) still match recorded paths
+fn path_dirs() -> Vec<Vec<std::path::PathBuf>> {
+    std::env::var_os("PATH")
+        .map(|path| {
+            std::env::split_paths(&path)
+                .map(|dir| {
+                    let mut forms = vec![dir.clone()];
+                    if let Ok(canonical) = dir.canonicalize()
+                        && canonical != dir
+                    {
+                        forms.push(canonical);
+                    }
+                    forms
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Position of the binary's directory in $PATH, if it is on PATH at all
+fn path_rank(path: &str, dirs: &[Vec<std::path::PathBuf>]) -> Option<usize> {
+    let parent = std::path::Path::new(path).parent()?;
+    dirs.iter()
+        .position(|forms| forms.iter().any(|d| d == parent))
+}
+
 /// Write expanded detail view for one duplicate group to a buffer.
 /// `force_colors` should be true when output is destined for a pager.
 fn write_dupe_expanded(
@@ -289,10 +350,7 @@ fn write_dupe_expanded(
         let source_str = c.source.as_deref().unwrap_or("-");
         let last_used = c
             .last_seen
-            .map(|ts| {
-                let dt: DateTime<Local> = local_datetime(ts);
-                dt.format("%Y-%m-%d").to_string()
-            })
+            .map(format_date)
             .unwrap_or_else(|| "never".to_string());
 
         let is_winner = i == 0 && c.count > 0;
@@ -0,0 +1,237 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::clean::is_safe_package_name;
+use super::report::{aggregate_packages, package_status};
+use crate::cli::ExportFormat;
+use crate::config::Config;
+use crate::defaults;
+use crate::storage::{BinaryRecord, Database};
+use crate::ui::Timer;
+use crate::utils::{start_daemon, sync_binaries};
+
+/// Sources whose packages can be listed in a Brewfile
+const BREW_SOURCES: &[&str] = &["homebrew", "linuxbrew"];
+
+pub fn cmd_export(format: ExportFormat, all: bool, source_filter: Option<String>) -> Result<()> {
+    let db = Database::open()?;
+    let config = Config::load()?;
+    sync_binaries(&db)?;
+    start_daemon(true)?;
+
+    let binaries = {
+        let _timer = Timer::start("db query");
+        db.get_all_binaries()?
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let by_source = group_by_source(&binaries, &config, all, source_filter.as_deref(), now);
+    let _timer = Timer::start("render");
+    let scope = if all { "installed" } else { "active" };
+    let total_pkgs: usize = by_source.values().map(|v| v.len()).sum();
+
+    match format {
+        ExportFormat::Brewfile => print_brewfile(&by_source, scope, total_pkgs),
+        ExportFormat::Ansible => print_ansible(&by_source, &config, scope, total_pkgs),
+        ExportFormat::Script => print_script(&by_source, &config, scope, total_pkgs),
+    }
+    Ok(())
+}
+
+/// Package names per source, sorted. Without `all`, dusty packages are left
+/// out; never-run packages still in their grace period count as kept.
+fn group_by_source(
+    binaries: &[BinaryRecord],
+    config: &Config,
+    all: bool,
+    source_filter: Option<&str>,
+    now: i64,
+) -> BTreeMap<String, Vec<String>> {
+    let visible: Vec<BinaryRecord> = binaries
+        .iter()
+        .filter(|b| {
+            let name = Path::new(&b.path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("");
+            !config.should_ignore_binary(name, b.source.as_deref())
+        })
+        .filter(|b| source_filter.is_none_or(|s| b.package_key().0 == s))
+        .cloned()
+        .collect();
+
+    let mut by_source: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for p in aggregate_packages(&visible) {
+        if all || package_status(&p, now, config.report.grace_days) != "dusty" {
+            by_source.entry(p.source).or_default().push(p.package_name);
+        }
+    }
+    for pkgs in by_source.values_mut() {
+        pkgs.sort();
+    }
+    by_source
+}
+
+/// Split a source's packages into ones safe to put on a command line and the rest
+fn partition_safe(pkgs: &[String]) -> (Vec<&str>, Vec<&str>) {
+    pkgs.iter()
+        .map(String::as_str)
+        .partition(|p| is_safe_package_name(p))
+}
+
+fn print_brewfile(by_source: &BTreeMap<String, Vec<String>>, scope: &str, total_pkgs: usize) {
+    println!(
+        "# Brewfile generated by dusty ({} {} packages)",
+        total_pkgs, scope
+    );
+    println!("# Install with: brew bundle --file <this file>");
+
+    for (source, pkgs) in by_source {
+        println!();
+        if !BREW_SOURCES.contains(&source.as_str()) {
+            println!(
+                "# {} ({} packages, not installable via brew): {}",
+                source,
+                pkgs.len(),
+                pkgs.join(" ")
+            );
+            continue;
+        }
+        println!("# {} ({} packages)", source, pkgs.len());
+        for pkg in pkgs {
+            println!("brew \"{}\"", pkg.replace('"', "\\\""));
+        }
+    }
+}
+
+fn print_ansible(
+    by_source: &BTreeMap<String, Vec<String>>,
+    config: &Config,
+    scope: &str,
+    total_pkgs: usize,
+) {
+    println!("---");
+    println!(
+        "# Ansible tasks generated by dusty ({} {} packages)",
+        total_pkgs, scope
+    );
+
+    for (source, pkgs) in by_source {
+        let install_cmd = config
+            .get_uninstall_cmd(source)
+            .and_then(|cmd| defaults::install_cmd_from_uninstall(&cmd));
+        let (safe, unsafe_names) = partition_safe(pkgs);
+        println!();
+        match install_cmd {
+            Some(cmd) => {
+                if !safe.is_empty() {
+                    println!("- name: Install {} packages", source);
+                    println!("  ansible.builtin.command: {} {{{{ item }}}}", cmd);
+                    println!("  loop:");
+                    for pkg in &safe {
+                        println!("    - \"{}\"", pkg);
+                    }
+                }
+                print_skipped(source, &unsafe_names);
+            }
+            None => println!(
+                "# {} ({} packages, no install command): {}",
+                source,
+                pkgs.len(),
+                pkgs.join(" ")
+            ),
+        }
+    }
+}
+
+fn print_script(
+    by_source: &BTreeMap<String, Vec<String>>,
+    config: &Config,
+    scope: &str,
+    total_pkgs: usize,
+) {
+    println!("#!/bin/sh");
+    println!(
+        "# Install commands generated by dusty ({} {} packages)",
+        total_pkgs, scope
+    );
+
+    for (source, pkgs) in by_source {
+        let install_cmd = config
+            .get_uninstall_cmd(source)
+            .and_then(|cmd| defaults::install_cmd_from_uninstall(&cmd));
+        let (safe, unsafe_names) = partition_safe(pkgs);
+        println!();
+        match install_cmd {
+            Some(cmd) => {
+                println!("# {} ({} packages)", source, pkgs.len());
+                if !safe.is_empty() {
+                    println!("{} {}", cmd, safe.join(" "));
+                }
+                print_skipped(source, &unsafe_names);
+            }
+            None => println!(
+                "# {} ({} packages, no install command): {}",
+                source,
+                pkgs.len(),
+                pkgs.join(" ")
+            ),
+        }
+    }
+}
+
+fn print_skipped(source: &str, unsafe_names: &[&str]) {
+    if !unsafe_names.is_empty() {
+        println!(
+            "# {} skipped (unsafe characters): {}",
+            source,
+            unsafe_names.join(" ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn make_binary(
+        path: &str,
+        count: i64,
+        first_seen: i64,
+        source: &str,
+        pkg: &str,
+    ) -> BinaryRecord {
+        BinaryRecord {
+            path: path.to_string(),
+            count,
+            first_seen: Some(first_seen),
+            last_seen: (count > 0).then_some(first_seen),
+            source: Some(source.to_string()),
+            package_name: Some(pkg.to_string()),
+            observed: count > 0,
+        }
+    }
+
+    #[test]
+    fn test_group_by_source_uses_grace_period() {
+        let now = 100 * DAY;
+        let binaries = vec![
+            make_binary("/opt/homebrew/bin/git", 12, 0, "homebrew", "git"),
+            make_binary("/opt/homebrew/bin/pnmtopng", 0, 0, "homebrew", "netpbm"),
+            make_binary("/opt/homebrew/bin/jq", 0, now - DAY, "homebrew", "jq"),
+            make_binary("/home/u/.cargo/bin/rg", 3, 0, "cargo", "ripgrep"),
+        ];
+        let config = Config::default();
+
+        let active = group_by_source(&binaries, &config, false, None, now);
+        assert_eq!(active["homebrew"], ["git", "jq"]);
+        assert_eq!(active["cargo"], ["ripgrep"]);
+
+        let all = group_by_source(&binaries, &config, true, Some("homebrew"), now);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all["homebrew"], ["git", "jq", "netpbm"]);
+    }
+}
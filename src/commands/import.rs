@@ -0,0 +1,165 @@
+use anyhow::Result;
+use console::style;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::defaults;
+use crate::history::{HistoryEntry, HistorySource};
+use crate::storage::Database;
+use crate::ui::shorten_path;
+use crate::utils::sync_binaries;
+
+pub fn cmd_import(from_history: String, dry_run: bool) -> Result<()> {
+    let source = HistorySource::detect(&from_history)?;
+    let db = Database::open()?;
+    sync_binaries(&db)?;
+
+    let entries = source.read()?;
+    let sources: HashMap<String, String> = db
+        .get_all_binaries()?
+        .into_iter()
+        .map(|b| {
+            let source = b.source.unwrap_or_else(|| "other".to_string());
+            (b.path, source)
+        })
+        .collect();
+
+    // Entries up to the newest timestamp of a previous import were already
+    // counted; undated ones can only be imported once. Anything since tracking
+    // started was already seen by the daemon.
+    let meta_key = format!("history_import:{}", source.path().display());
+    let imported_until: Option<i64> = db.get_meta(&meta_key)?.and_then(|v| v.parse().ok());
+    let tracking_since = db.get_tracking_since()?;
+    let entries: Vec<HistoryEntry> = entries
+        .into_iter()
+        .filter(|e| match (imported_until, e.timestamp) {
+            (None, _) => true,
+            (Some(until), Some(ts)) => ts > until,
+            (Some(_), None) => false,
+        })
+        .filter(|e| match (tracking_since, e.timestamp) {
+            (Some(since), Some(ts)) => ts < since,
+            _ => true,
+        })
+        .collect();
+
+    let mut lookup: HashMap<String, Option<String>> = HashMap::new();
+    let mut dated: HashMap<String, Vec<i64>> = HashMap::new();
+    let mut undated: HashMap<String, i64> = HashMap::new();
+    let mut unresolved: HashSet<String> = HashSet::new();
+    for entry in &entries {
+        let path = lookup
+            .entry(entry.program.clone())
+            .or_insert_with(|| resolve_program(&entry.program, &sources));
+        match (path, entry.timestamp) {
+            (Some(path), Some(ts)) => dated.entry(path.clone()).or_default().push(ts),
+            (Some(path), None) => *undated.entry(path.clone()).or_default() += 1,
+            (None, _) => {
+                unresolved.insert(entry.program.clone());
+            }
+        }
+    }
+
+    // Cap each binary, keeping its newest dated execs
+    let mut execs: Vec<(String, String, i64, Option<String>)> = Vec::new();
+    let mut totals: HashMap<String, i64> = HashMap::new();
+    for (path, mut stamps) in dated {
+        stamps.sort_unstable();
+        let keep = &stamps[stamps.len().saturating_sub(defaults::HISTORY_IMPORT_CAP)..];
+        *totals.entry(path.clone()).or_default() += keep.len() as i64;
+        let source = sources[&path].clone();
+        execs.extend(
+            keep.iter()
+                .map(|&ts| (path.clone(), source.clone(), ts, None)),
+        );
+    }
+    let mut counts: Vec<(String, i64)> = Vec::new();
+    for (path, count) in undated {
+        let room = defaults::HISTORY_IMPORT_CAP as i64 - totals.get(&path).copied().unwrap_or(0);
+        let count = count.min(room.max(0));
+        if count > 0 {
+            *totals.entry(path.clone()).or_default() += count;
+            counts.push((path, count));
+        }
+    }
+    execs.sort_by_key(|(_, _, ts, _)| *ts);
+
+    let total: i64 = totals.values().sum();
+    let display = shorten_path(&source.path().display().to_string());
+    println!();
+    if total == 0 {
+        let reason = if imported_until.is_some() {
+            "nothing new since the last import"
+        } else {
+            "no commands matched a tracked binary"
+        };
+        println!("  {} {}: {}", style("◦").dim(), display, reason);
+        println!();
+        return Ok(());
+    }
+
+    let mut top: Vec<(&String, &i64)> = totals.iter().collect();
+    top.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    for (path, count) in top.iter().take(10) {
+        println!(
+            "    {:>6}  {}",
+            style(count).bold(),
+            style(shorten_path(path)).dim()
+        );
+    }
+    if top.len() > 10 {
+        println!("    {:>6}  ... and {} more binaries", "", top.len() - 10);
+    }
+    println!();
+
+    if dry_run {
+        println!(
+            "  {} Would import {} execs for {} binaries from {}",
+            style("◦").dim(),
+            total,
+            totals.len(),
+            display
+        );
+    } else {
+        db.import_execs(&execs)?;
+        db.add_exec_counts(&counts)?;
+
+        let newest = entries.iter().filter_map(|e| e.timestamp).max();
+        let mark = newest.max(imported_until).unwrap_or(0);
+        db.set_meta(&meta_key, &mark.to_string())?;
+
+        println!(
+            "  {} Imported {} execs for {} binaries from {}",
+            style("●").green(),
+            style(total).bold(),
+            totals.len(),
+            display
+        );
+    }
+    if !unresolved.is_empty() {
+        println!(
+            "  {} {} commands didn't match a tracked binary (aliases, functions, builtins)",
+            style("◦").dim(),
+            unresolved.len()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// The tracked binary a history command runs: absolute paths as-is,
+/// bare names through $PATH like the shell would
+fn resolve_program(program: &str, tracked: &HashMap<String, String>) -> Option<String> {
+    if program.contains('/') {
+        return Path::new(program)
+            .is_absolute()
+            .then(|| program.to_string())
+            .filter(|p| tracked.contains_key(p));
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir: PathBuf| dir.join(program))
+        .find(|candidate| candidate.is_file())
+        .map(|candidate| candidate.to_string_lossy().to_string())
+        .filter(|p| tracked.contains_key(p))
+}
@@ -1,10 +1,32 @@
 use anyhow::Result;
 use console::style;
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::process::Command;
 
 use crate::config::Config;
 use crate::defaults;
-use crate::ui::truncate_str;
+use crate::platform::is_offline;
+use crate::ui::{print_json, truncate_str};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct InventoryJson {
+    source: String,
+    /// "ok"; "offline" when `--offline` kept list_cmd from running, "failed"
+    /// when it errored. Packages are only listed for "ok".
+    status: &'static str,
+    packages: Vec<String>,
+    count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// What running one source's list_cmd gave
+enum Listing {
+    Packages(Vec<String>),
+    Offline,
+    Failed(String),
+}
 
 pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Result<()> {
     let config = Config::load()?;
@@ -70,9 +92,21 @@ pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Re
     }
 
     // Run list_cmd for each source
-    let mut results: Vec<(&str, Vec<String>)> = Vec::new();
+    let mut listings: Vec<(&str, Listing)> = Vec::new();
 
     for source in &sources {
+        if is_offline() {
+            if !json {
+                println!();
+                println!(
+                    "  {} {} unknown (offline: list_cmd not run)",
+                    style("◦").dim(),
+                    style(&source.name).bold()
+                );
+            }
+            listings.push((&source.name, Listing::Offline));
+            continue;
+        }
         let cmd = source.list_cmd.as_deref().unwrap();
         let output = Command::new(defaults::SHELL)
             .args([defaults::SHELL_CMD_FLAG, cmd])
@@ -86,7 +120,7 @@ pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Re
                     .map(|l| l.trim().to_string())
                     .filter(|l| !l.is_empty())
                     .collect();
-                results.push((&source.name, packages));
+                listings.push((&source.name, Listing::Packages(packages)));
             }
             Ok(output) => {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -99,6 +133,8 @@ pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Re
                         stderr.trim()
                     );
                 }
+                let error = format!("list_cmd exited with {}: {}", output.status, stderr.trim());
+                listings.push((&source.name, Listing::Failed(error)));
             }
             Err(e) => {
                 if !json {
@@ -110,31 +146,24 @@ pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Re
                         e
                     );
                 }
+                listings.push((&source.name, Listing::Failed(e.to_string())));
             }
         }
     }
 
     if json {
-        #[derive(serde::Serialize)]
-        struct InventoryJson {
-            source: String,
-            packages: Vec<String>,
-            count: usize,
-        }
-
-        let rows: Vec<InventoryJson> = results
-            .iter()
-            .map(|(source, pkgs)| InventoryJson {
-                source: source.to_string(),
-                packages: pkgs.clone(),
-                count: pkgs.len(),
-            })
-            .collect();
-
-        println!("{}", serde_json::to_string_pretty(&rows)?);
+        print_json(&inventory_json(listings))?;
         return Ok(());
     }
 
+    let results: Vec<(&str, Vec<String>)> = listings
+        .into_iter()
+        .filter_map(|(source, listing)| match listing {
+            Listing::Packages(packages) => Some((source, packages)),
+            _ => None,
+        })
+        .collect();
+
     if results.is_empty() {
         println!();
         println!("  {} No packages found", style("●").yellow());
@@ -192,3 +221,55 @@ pub fn cmd_inventory(source_filter: Option<String>, all: bool, json: bool) -> Re
 
     Ok(())
 }
+
+/// One `--json` row per source, including the ones that couldn't be listed
+fn inventory_json(listings: Vec<(&str, Listing)>) -> Vec<InventoryJson> {
+    listings
+        .into_iter()
+        .map(|(source, listing)| {
+            let (status, packages, error) = match listing {
+                Listing::Packages(packages) => ("ok", packages, None),
+                Listing::Offline => ("offline", Vec::new(), None),
+                Listing::Failed(e) => ("failed", Vec::new(), Some(e)),
+            };
+            InventoryJson {
+                source: source.to_string(),
+                status,
+                count: packages.len(),
+                packages,
+                error,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory_json_keeps_unlisted_sources() {
+        let rows = inventory_json(vec![
+            ("pip", Listing::Packages(vec!["requests".to_string()])),
+            ("r", Listing::Packages(Vec::new())),
+            ("gem", Listing::Offline),
+            ("npm", Listing::Failed("list_cmd exited with 1".to_string())),
+        ]);
+        let json = serde_json::to_value(&rows).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "source": "pip", "status": "ok", "packages": ["requests"], "count": 1 },
+                { "source": "r", "status": "ok", "packages": [], "count": 0 },
+                { "source": "gem", "status": "offline", "packages": [], "count": 0 },
+                {
+                    "source": "npm",
+                    "status": "failed",
+                    "packages": [],
+                    "count": 0,
+                    "error": "list_cmd exited with 1"
+                },
+            ])
+        );
+    }
+}
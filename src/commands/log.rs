@@ -1,7 +1,31 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::process::Command;
 
+use crate::config;
 use crate::platform::{Daemon, DaemonManager};
 
 pub fn cmd_log(lines: usize, follow: bool) -> Result<()> {
-    Daemon::view_logs(lines, follow)
+    // A `[daemon] log_file` replaces the platform's log destination
+    let Some(log_file) = config::Config::load()
+        .ok()
+        .and_then(|c| c.daemon.log_path())
+    else {
+        return Daemon::view_logs(lines, follow);
+    };
+
+    if !log_file.exists() {
+        anyhow::bail!(
+            "No log file found at {}. Is the daemon running?",
+            log_file.display()
+        );
+    }
+    let mut cmd = Command::new("tail");
+    cmd.arg("-n").arg(lines.to_string());
+    if follow {
+        // Keep following across rotations
+        cmd.arg("-F");
+    }
+    cmd.arg(&log_file);
+    cmd.status().context("Failed to run tail")?;
+    Ok(())
 }
@@ -1,33 +1,47 @@
+mod alias;
+mod audit;
 mod clean;
 mod completions;
 mod config;
 mod daemon;
 mod deps;
 mod dupes;
+mod export;
+mod import;
 mod inventory;
 mod lifecycle;
 mod log;
+mod recategorize;
 mod report;
 mod restore;
+mod schema;
 mod size;
 mod stats;
 mod status;
 mod trash;
+mod vacuum;
 mod why;
 
+pub use alias::cmd_alias;
+pub use audit::cmd_audit;
 pub use clean::cmd_clean;
-pub use completions::cmd_completions;
+pub use completions::{cmd_complete, cmd_completions};
 pub use config::cmd_config;
 pub use daemon::cmd_daemon;
 pub use deps::cmd_deps;
 pub use dupes::cmd_dupes;
+pub use export::cmd_export;
+pub use import::cmd_import;
 pub use inventory::cmd_inventory;
 pub use lifecycle::{cmd_start, cmd_stop};
 pub use log::cmd_log;
-pub use report::cmd_report;
+pub use recategorize::cmd_recategorize;
+pub use report::{RowTemplate, cmd_report};
 pub use restore::cmd_restore;
+pub use schema::cmd_schema;
 pub use size::cmd_size;
 pub use stats::cmd_stats;
 pub use status::cmd_status;
 pub use trash::cmd_trash;
+pub use vacuum::cmd_vacuum;
 pub use why::cmd_why;
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use console::style;
+
+use crate::config;
+use crate::package::scan_all_binaries;
+use crate::storage::Database;
+use crate::ui::Spinner;
+use crate::utils::recategorize_binaries;
+
+/// Re-apply the current `[[sources]]` rules to every stored binary now,
+/// rather than on the first sync after the config file changes
+pub fn cmd_recategorize() -> Result<()> {
+    let db = Database::open()?;
+    let config = config::Config::load()?;
+
+    let spinner = Spinner::new();
+    spinner.message("Scanning PATH");
+    let scanned = scan_all_binaries()?;
+    spinner.message("Re-categorizing");
+    let total = db.get_all_binaries()?.len();
+    let changed = recategorize_binaries(&db, &config, &scanned)?;
+    spinner.finish();
+
+    println!();
+    println!(
+        "  {} Re-categorized {} of {} binaries",
+        style("●").green(),
+        style(changed).bold(),
+        total
+    );
+    println!();
+    Ok(())
+}
@@ -1,63 +1,110 @@
 use anyhow::Result;
-use chrono::{DateTime, Local};
 use console::style;
-use serde::Serialize;
-use std::collections::HashMap;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
+use super::size::batch_dir_sizes;
+use crate::cli::{ColorBy, ExecSource, ReportArgs, SortKey};
 use crate::config;
-use crate::storage::{BinaryRecord, Database};
-use crate::ui::{print_with_pager, terminal_fit};
-use crate::utils::{local_datetime, start_daemon, sync_binaries};
+use crate::deps;
+use crate::package;
+use crate::platform::boot_time;
+use crate::storage::{BinaryRecord, Database, InvocationRecord};
+use crate::ui::{
+    OutputFormat, Spinner, Timer, emit, format_bytes, humanize_ago, print_with_pager, shorten_path,
+    styled_stdout, terminal_fit, truncate_str,
+};
+use crate::utils::{
+    detect_install_roots, format_timestamp, json_timestamp, resolve_binary_name, start_daemon,
+    sync_binaries, usage_status,
+};
 
-#[derive(Serialize)]
-struct PackageJson {
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub(super) struct PackageJson {
     package_name: String,
     source: String,
     binaries: usize,
     total_uses: i64,
     last_used: Option<String>,
+    /// Earliest first_seen of the member binaries; null when any predates tracking
+    #[serde(default)]
+    first_seen: Option<String>,
+    /// `first_seen` as a Unix timestamp
+    #[serde(default)]
+    first_seen_ts: Option<i64>,
     status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+    /// Recency-weighted usage (structured output or --sort recency)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    /// Uses per week since the package was first seen (or tracking started)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rate: Option<f64>,
+    /// Directory the package lives in (structured output only)
+    install_root: Option<String>,
+    /// "user" or "system", from the source's configured or inferred scope
+    #[serde(default)]
+    scope: Option<String>,
+    /// Ignore pattern that would normally hide this package, set only when every
+    /// one of its binaries is ignored (--include-ignored)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignored_by: Option<String>,
+    /// Number of versioned packages folded into this row (--merge-versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    merged_versions: Option<usize>,
+    /// Full `<uninstall_cmd> <package>` command (structured output only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uninstall_cmd: Option<String>,
 }
 
 /// Aggregate binaries into packages
-struct PackageInfo {
-    package_name: String,
-    source: String,
+pub(super) struct PackageInfo {
+    pub(super) package_name: String,
+    pub(super) source: String,
     binaries: usize,
     total_uses: i64,
     last_seen: Option<i64>,
+    /// Earliest first_seen of the member binaries; None if any predates tracking
+    first_seen: Option<i64>,
+}
+
+impl PackageInfo {
+    /// (source, package), the key binaries are grouped under
+    fn key(&self) -> (String, String) {
+        (self.source.clone(), self.package_name.clone())
+    }
 }
 
-fn aggregate_packages(binaries: &[BinaryRecord]) -> Vec<PackageInfo> {
-    let mut map: HashMap<(String, String), (usize, i64, Option<i64>)> = HashMap::new();
+pub(super) fn aggregate_packages(binaries: &[BinaryRecord]) -> Vec<PackageInfo> {
+    type Totals = (usize, i64, Option<i64>, Option<i64>);
+    let mut map: HashMap<(String, String), Totals> = HashMap::new();
 
     for b in binaries {
-        let pkg = b.package_name.clone().unwrap_or_else(|| {
-            std::path::Path::new(&b.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
-        let source = b.source.clone().unwrap_or_else(|| "other".to_string());
-
-        let entry = map.entry((pkg, source)).or_insert((0, 0, None));
+        let entry = map
+            .entry(b.package_key())
+            .or_insert((0, 0, None, b.first_seen));
         entry.0 += 1;
         entry.1 += b.count;
         entry.2 = match (entry.2, b.last_seen) {
             (Some(a), Some(b)) => Some(a.max(b)),
             (a, b) => a.or(b),
         };
+        entry.3 = entry.3.zip(b.first_seen).map(|(a, b)| a.min(b));
     }
 
     let mut packages: Vec<PackageInfo> = map
         .into_iter()
-        .map(|((pkg, source), (bins, uses, last))| PackageInfo {
+        .map(|((source, pkg), (bins, uses, last, first))| PackageInfo {
             package_name: pkg,
             source,
             binaries: bins,
             total_uses: uses,
             last_seen: last,
+            first_seen: first,
         })
         .collect();
 
@@ -72,26 +119,462 @@ fn aggregate_packages(binaries: &[BinaryRecord]) -> Vec<PackageInfo> {
     packages
 }
 
-pub fn cmd_report(
-    dust: bool,
-    low: Option<u32>,
-    stale: Option<u32>,
-    source: Option<String>,
-    all: bool,
-    json: bool,
-    export: bool,
-) -> Result<()> {
+/// `--merge-versions`: rename each binary's package to its version base name.
+/// Returns the renamed binaries and, per merged (source, package) key, how many
+/// distinct versioned packages went into it.
+fn merge_versions(
+    binaries: &[BinaryRecord],
+    pattern: Option<&Regex>,
+) -> (Vec<BinaryRecord>, HashMap<(String, String), usize>) {
+    let mut members: HashMap<(String, String), HashSet<String>> = HashMap::new();
+    let merged = binaries
+        .iter()
+        .map(|b| {
+            let (source, pkg) = b.package_key();
+            let base = package::version_base(&pkg, pattern).to_string();
+            members
+                .entry((source, base.clone()))
+                .or_default()
+                .insert(pkg);
+            BinaryRecord {
+                package_name: Some(base),
+                ..b.clone()
+            }
+        })
+        .collect();
+    let counts = members
+        .into_iter()
+        .map(|(key, pkgs)| (key, pkgs.len()))
+        .collect();
+    (merged, counts)
+}
+
+type SizeMap = HashMap<(String, String), Option<u64>>;
+
+/// Disk size of each given package, keyed by (source, package) like `dusty size`
+///
+/// With `merge` (`--merge-versions`), `binaries` are the unmerged records and each
+/// versioned package is measured on its own, then summed under its base name.
+fn package_sizes(
+    db: &Database,
+    packages: &[PackageInfo],
+    binaries: &[BinaryRecord],
+    size_config: &config::SizeConfig,
+    merge: Option<Option<&Regex>>,
+) -> SizeMap {
+    let wanted: HashSet<(String, String)> = packages.iter().map(|p| p.key()).collect();
+    let mut groups: HashMap<(String, String), Vec<&BinaryRecord>> = HashMap::new();
+    for b in binaries {
+        let (source, pkg) = b.package_key();
+        let base = match merge {
+            Some(pattern) => package::version_base(&pkg, pattern).to_string(),
+            None => pkg.clone(),
+        };
+        if wanted.contains(&(source.clone(), base)) {
+            groups.entry((source, pkg)).or_default().push(b);
+        }
+    }
+
+    let spinner = Spinner::new();
+    spinner.message("Calculating sizes");
+    let sizes = batch_dir_sizes(db, &groups, size_config);
+    spinner.finish();
+
+    let Some(pattern) = merge else {
+        return sizes;
+    };
+    let mut merged = SizeMap::new();
+    for ((source, pkg), size) in sizes {
+        let base = package::version_base(&pkg, pattern).to_string();
+        let total = merged.entry((source, base)).or_insert(None);
+        if let Some(size) = size {
+            *total = Some(total.unwrap_or(0) + size);
+        }
+    }
+    merged
+}
+
+fn size_of(sizes: &SizeMap, p: &PackageInfo) -> Option<u64> {
+    sizes.get(&p.key()).copied().flatten()
+}
+
+type ScoreMap = HashMap<(String, String), f64>;
+
+/// Recency-weighted usage of each package, keyed by (source, package).
+/// Every exec counts `0.5^(age / half_life)`, so a tool used steadily this month
+/// outranks one used heavily a year ago.
+fn package_scores(
+    binaries: &[BinaryRecord],
+    history: &HashMap<String, Vec<(i64, i64)>>,
+    now: i64,
+    half_life_days: f64,
+) -> ScoreMap {
+    let today = now / 86400;
+    let weight = |day: i64| 0.5f64.powf((today - day).max(0) as f64 / half_life_days.max(0.1));
+
+    let mut scores = ScoreMap::new();
+    for b in binaries {
+        let days = history.get(&b.path).map(Vec::as_slice).unwrap_or(&[]);
+        let mut score: f64 = days.iter().map(|&(day, n)| n as f64 * weight(day)).sum();
+
+        // Counts recorded before daily history existed are placed at the oldest
+        // known day (or last_seen), which is the most generous honest guess
+        let untracked = b.count - days.iter().map(|&(_, n)| n).sum::<i64>();
+        let anchor = days
+            .first()
+            .map(|&(day, _)| day)
+            .or(b.last_seen.map(|ts| ts / 86400));
+        if untracked > 0
+            && let Some(day) = anchor
+        {
+            score += untracked as f64 * weight(day);
+        }
+
+        *scores.entry(b.package_key()).or_insert(0.0) += score;
+    }
+    scores
+}
+
+fn score_of(scores: &ScoreMap, p: &PackageInfo) -> Option<f64> {
+    scores.get(&p.key()).map(|s| (s * 100.0).round() / 100.0)
+}
+
+/// Uses per week of each package since it was first seen, keyed by (source, package).
+/// Packages that predate tracking count from `tracking_since`. Anything younger
+/// than a week counts as a week, so same-day installs don't divide by zero.
+fn package_rates(packages: &[PackageInfo], now: i64, tracking_since: Option<i64>) -> ScoreMap {
+    const WEEK: f64 = 7.0 * 86400.0;
+    packages
+        .iter()
+        .filter_map(|p| {
+            let start = p.first_seen.or(tracking_since)?;
+            let weeks = ((now - start) as f64 / WEEK).max(1.0);
+            Some((p.key(), p.total_uses as f64 / weeks))
+        })
+        .collect()
+}
+
+/// Reorder packages for display; ties keep the default usage ordering
+fn sort_packages(
+    packages: &mut [PackageInfo],
+    key: SortKey,
+    sizes: &SizeMap,
+    scores: &ScoreMap,
+    rates: &ScoreMap,
+) {
+    match key {
+        SortKey::Uses => {}
+        SortKey::Size => packages.sort_by_key(|p| Reverse(size_of(sizes, p).unwrap_or(0))),
+        SortKey::Name => packages.sort_by(|a, b| a.package_name.cmp(&b.package_name)),
+        SortKey::LastUsed => packages.sort_by_key(|p| Reverse(p.last_seen)),
+        SortKey::Bins => packages.sort_by_key(|p| Reverse(p.binaries)),
+        SortKey::Source => packages.sort_by(|a, b| {
+            a.source
+                .cmp(&b.source)
+                .then_with(|| a.package_name.cmp(&b.package_name))
+        }),
+        SortKey::Recency => packages.sort_by(|a, b| {
+            let score = |p| score_of(scores, p).unwrap_or(0.0);
+            score(b).total_cmp(&score(a))
+        }),
+        SortKey::Rate => packages.sort_by(|a, b| {
+            let rate = |p| score_of(rates, p).unwrap_or(0.0);
+            rate(b).total_cmp(&rate(a))
+        }),
+    }
+}
+
+/// Name of the executable itself (last path component)
+fn binary_name(path: &str) -> &str {
+    std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+}
+
+/// Packages `--find` selects: those with any binary whose path or package
+/// name matches. `None` when `--find` isn't given.
+fn find_packages(
+    binaries: &[BinaryRecord],
+    find: Option<&regex::Regex>,
+) -> Option<HashSet<(String, String)>> {
+    let re = find?;
+    Some(
+        binaries
+            .iter()
+            .filter(|b| {
+                re.is_match(&b.path) || b.package_name.as_deref().is_some_and(|p| re.is_match(p))
+            })
+            .map(BinaryRecord::package_key)
+            .collect(),
+    )
+}
+
+/// Binary-level filter: why a binary is dropped before aggregation, if it is.
+/// `found` is what `find_packages` selected.
+fn binary_exclusion(
+    b: &BinaryRecord,
+    config: &config::Config,
+    args: &ReportArgs,
+    found: Option<&HashSet<(String, String)>>,
+) -> Option<String> {
+    if !args.include_ignored
+        && let Some(pattern) = config.ignored_by(binary_name(&b.path), b.source.as_deref())
+    {
+        return Some(format!("ignored by pattern {}", pattern));
+    }
+    match args.exec_source {
+        Some(ExecSource::Observed) if !b.observed => {
+            return Some("excluded by --exec-source observed: never seen running".to_string());
+        }
+        Some(ExecSource::Scanned) if b.observed => {
+            return Some(
+                "excluded by --exec-source scanned: seen running by the daemon".to_string(),
+            );
+        }
+        _ => {}
+    }
+    if let (Some(re), Some(found)) = (&args.find, found)
+        && !found.contains(&b.package_key())
+    {
+        return Some(format!(
+            "excluded: no binary path or package name in its package matches --find {}",
+            re
+        ));
+    }
+    match args.source.as_deref() {
+        Some(s) if b.source.as_deref() != Some(s) => Some(format!(
+            "excluded: source {} != {}",
+            b.source.as_deref().unwrap_or("none"),
+            s
+        )),
+        _ => None,
+    }
+}
+
+/// Use counts of the packages named by --less-used-than / --more-used-than
+#[derive(Default)]
+struct RelativeUsage {
+    less_than: Option<(String, i64)>,
+    more_than: Option<(String, i64)>,
+}
+
+impl RelativeUsage {
+    fn resolve(args: &ReportArgs, binaries: &[BinaryRecord]) -> Result<Self> {
+        let lookup = |name: &Option<String>| -> Result<Option<(String, i64)>> {
+            name.as_deref()
+                .map(|n| Ok((n.to_string(), reference_uses(binaries, n)?)))
+                .transpose()
+        };
+        Ok(Self {
+            less_than: lookup(&args.less_used_than)?,
+            more_than: lookup(&args.more_used_than)?,
+        })
+    }
+}
+
+/// Total uses of the package a reference name points at. Like `dusty why`, a
+/// binary name wins over a package name; a binary name is resolved like
+/// `dusty deps --binary`, and a package name in several sources is an error.
+fn reference_uses(binaries: &[BinaryRecord], name: &str) -> Result<i64> {
+    let key = if name.contains('/') || binaries.iter().any(|b| binary_name(&b.path) == name) {
+        let path = if name.contains('/') {
+            name.to_string()
+        } else {
+            resolve_binary_name(binaries, name)?
+        };
+        binaries
+            .iter()
+            .find(|b| b.path == path)
+            .map(BinaryRecord::package_key)
+            .ok_or_else(|| anyhow::anyhow!("no tracked binary at '{}'", path))?
+    } else {
+        let mut keys: Vec<(String, String)> = binaries
+            .iter()
+            .filter(|b| b.package_name.as_deref() == Some(name))
+            .map(BinaryRecord::package_key)
+            .collect();
+        keys.sort();
+        keys.dedup();
+        match keys.len() {
+            0 => anyhow::bail!("no tracked binary or package named '{}'", name),
+            1 => keys.remove(0),
+            _ => anyhow::bail!(
+                "'{}' is ambiguous; it matches {}",
+                name,
+                keys.iter()
+                    .map(|(source, pkg)| format!("{} ({})", pkg, source))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    };
+    Ok(binaries
+        .iter()
+        .filter(|b| b.package_key() == key)
+        .map(|b| b.count)
+        .sum())
+}
+
+/// Package-level usage filter (--dust, --low, --stale, --since-boot)
+fn package_exclusion(
+    p: &PackageInfo,
+    args: &ReportArgs,
+    relative: &RelativeUsage,
+    now: i64,
+    boot: Option<i64>,
+    grace_days: u32,
+) -> Option<String> {
+    if let Some(boot) = boot
+        && p.last_seen.is_none_or(|ts| ts < boot)
+    {
+        return Some(format!(
+            "excluded by --since-boot: not used since boot at {}",
+            format_timestamp(boot)
+        ));
+    }
+    if args.dust && p.total_uses != 0 {
+        return Some(format!("excluded by --dust: {} uses", p.total_uses));
+    }
+    if args.dust && is_new(p, now, grace_days) {
+        return Some(format!(
+            "excluded by --dust: new (first seen within the {}-day grace period)",
+            grace_days
+        ));
+    }
+    if !args.dust
+        && let Some(threshold) = args.low
+        && p.total_uses >= threshold as i64
+    {
+        return Some(format!(
+            "excluded by --low {}: {} uses",
+            threshold, p.total_uses
+        ));
+    }
+    if let Some(days) = args.stale {
+        let threshold = now - (days as i64 * 24 * 60 * 60);
+        if let Some(ts) = p.last_seen
+            && ts >= threshold
+        {
+            return Some(format!(
+                "excluded by --stale {}: last used {}",
+                days,
+                format_timestamp(ts)
+            ));
+        }
+    }
+    if let Some(days) = args.active_in {
+        let threshold = now - (days as i64 * 24 * 60 * 60);
+        match p.last_seen {
+            Some(ts) if ts >= threshold => {}
+            Some(ts) => {
+                return Some(format!(
+                    "excluded by --active-in {}: last used {}",
+                    days,
+                    format_timestamp(ts)
+                ));
+            }
+            None => return Some(format!("excluded by --active-in {}: never used", days)),
+        }
+    }
+    if let Some((name, uses)) = &relative.less_than
+        && p.total_uses >= *uses
+    {
+        return Some(format!(
+            "excluded by --less-used-than {}: {} uses ({} has {})",
+            name, p.total_uses, name, uses
+        ));
+    }
+    if let Some((name, uses)) = &relative.more_than
+        && p.total_uses <= *uses
+    {
+        return Some(format!(
+            "excluded by --more-used-than {}: {} uses ({} has {})",
+            name, p.total_uses, name, uses
+        ));
+    }
+    if let Some(min) = args.min_binaries
+        && p.binaries < min
+    {
+        return Some(format!(
+            "excluded by --min-binaries {}: {} binaries",
+            min, p.binaries
+        ));
+    }
+    if let Some(max) = args.max_binaries
+        && p.binaries > max
+    {
+        return Some(format!(
+            "excluded by --max-binaries {}: {} binaries",
+            max, p.binaries
+        ));
+    }
+    None
+}
+
+/// Whether any binary-level filter narrows the run beyond the ignore patterns
+fn has_binary_filter(args: &ReportArgs) -> bool {
+    args.include_ignored
+        || args.source.is_some()
+        || args.exec_source.is_some()
+        || args.find.is_some()
+}
+
+fn has_explicit_filter(args: &ReportArgs) -> bool {
+    args.dust
+        || args.low.is_some()
+        || args.stale.is_some()
+        || args.active_in.is_some()
+        || args.source.is_some()
+        || args.exec_source.is_some()
+        || args.find.is_some()
+        || args.less_used_than.is_some()
+        || args.more_used_than.is_some()
+        || args.min_binaries.is_some()
+        || args.max_binaries.is_some()
+        || args.since_boot
+}
+
+pub fn cmd_report(args: ReportArgs) -> Result<()> {
+    let format = OutputFormat::from_flags(args.format, args.json);
     let db = Database::open()?;
     let config = crate::config::Config::load()?;
+    let grace_days = args.grace_days.unwrap_or(config.report.grace_days);
 
     sync_binaries(&db)?;
     start_daemon(true)?;
 
-    let binaries = db.get_all_binaries()?;
+    let binaries = {
+        let _timer = Timer::start("db query");
+        db.get_all_binaries()?
+    };
+    let now = chrono::Utc::now().timestamp();
+    let boot = if args.since_boot {
+        Some(boot_time().ok_or_else(|| anyhow::anyhow!("Could not determine system boot time"))?)
+    } else {
+        None
+    };
+
+    let relative = RelativeUsage::resolve(&args, &binaries)?;
+
+    if let Some(name) = &args.explain {
+        let history = if args.sort == SortKey::Recency {
+            db.get_exec_history()?
+        } else {
+            HashMap::new()
+        };
+        explain_report(
+            &db, name, &args, &relative, &config, &binaries, &history, now, boot,
+        );
+        return Ok(());
+    }
 
     if binaries.is_empty() {
-        if json {
-            println!("[]");
+        if args.names_only || args.template.is_some() {
+            return Ok(());
+        }
+        if !format.is_table() {
+            emit(&Vec::<PackageJson>::new(), format)?;
         } else {
             println!();
             println!("  {} No binaries found in PATH.", style("●").yellow());
@@ -100,57 +583,107 @@ pub fn cmd_report(
         return Ok(());
     }
 
-    let now = chrono::Utc::now().timestamp();
+    let dust = args.dust;
+    let all = args.all;
+
+    if args.unused_deps {
+        return unused_deps_report(&db, &binaries, &config, &args, format);
+    }
+
+    if args.by_invocation {
+        return invocation_report(&db, &binaries, &config, &args, format);
+    }
+
+    // Interactive runs move the `--since-last` baseline forward; scripted ones opt in
+    let update_baseline = args.update_baseline
+        || (format.is_table()
+            && !args.export
+            && !args.names_only
+            && args.template.is_none()
+            && !args.merge_versions
+            && console::Term::stdout().is_term());
+    // The baseline always covers what a plain `dusty report` sees, so a run with
+    // --source, --find, --exec-source or --include-ignored can't shrink it
+    let default_view =
+        (update_baseline && (has_binary_filter(&args) || args.merge_versions)).then(|| {
+            let visible: Vec<_> = binaries
+                .iter()
+                .filter(|b| {
+                    config
+                        .ignored_by(binary_name(&b.path), b.source.as_deref())
+                        .is_none()
+                })
+                .cloned()
+                .collect();
+            aggregate_packages(&visible)
+        });
 
     // Filter binaries before aggregation
+    let found = find_packages(&binaries, args.find.as_ref());
     let filtered: Vec<_> = binaries
         .into_iter()
-        .filter(|b| {
-            let binary_name = std::path::Path::new(&b.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("");
-            if config.should_ignore_binary(binary_name) {
-                return false;
-            }
+        .filter(|b| binary_exclusion(b, &config, &args, found.as_ref()).is_none())
+        .collect();
 
-            match &source {
-                Some(s) => b.source.as_ref().map(|bs| bs == s).unwrap_or(false),
-                None => true,
+    // --merge-versions folds versioned packages into their base name; sizes are
+    // still measured per versioned package, from the unmerged records
+    let version_pattern = config.version_suffix_regex()?;
+    let merge = args.merge_versions.then_some(version_pattern.as_ref());
+    let (filtered, unmerged, merged_counts) = if args.merge_versions {
+        let (merged, counts) = merge_versions(&filtered, version_pattern.as_ref());
+        (merged, filtered, counts)
+    } else {
+        (filtered, Vec::new(), HashMap::new())
+    };
+    let size_binaries = if args.merge_versions {
+        &unmerged
+    } else {
+        &filtered
+    };
+
+    // Remember which packages are only visible because of --include-ignored:
+    // every one of their binaries is ignored
+    let mut ignored: HashMap<(String, String), String> = HashMap::new();
+    if args.include_ignored {
+        let mut shown: HashSet<(String, String)> = HashSet::new();
+        for b in &filtered {
+            match config.ignored_by(binary_name(&b.path), b.source.as_deref()) {
+                Some(pattern) => {
+                    ignored
+                        .entry(b.package_key())
+                        .or_insert_with(|| pattern.to_string());
+                }
+                None => {
+                    shown.insert(b.package_key());
+                }
             }
-        })
-        .collect();
+        }
+        ignored.retain(|key, _| !shown.contains(key));
+    }
 
     // Aggregate into packages
     let packages = aggregate_packages(&filtered);
 
+    let baseline = update_baseline.then(|| default_view.as_deref().unwrap_or(&packages));
+    if args.since_last {
+        return since_last_report(&db, &packages, &args, format, baseline, now, grace_days);
+    }
+    if let Some(baseline) = baseline {
+        save_baseline(&db, baseline, now, grace_days)?;
+    }
+
     // Apply usage filters at the package level
     let filtered_pkgs: Vec<_> = packages
         .into_iter()
-        .filter(|p| {
-            let usage_match = if dust {
-                p.total_uses == 0
-            } else if let Some(threshold) = low {
-                p.total_uses < threshold as i64
-            } else {
-                true
-            };
-
-            let stale_match = match stale {
-                Some(days) => {
-                    let threshold = now - (days as i64 * 24 * 60 * 60);
-                    p.last_seen.map(|ts| ts < threshold).unwrap_or(true)
-                }
-                None => true,
-            };
-
-            usage_match && stale_match
-        })
+        .filter(|p| package_exclusion(p, &args, &relative, now, boot, grace_days).is_none())
         .collect();
 
     if filtered_pkgs.is_empty() {
-        if json {
-            println!("[]");
+        if args.names_only || args.template.is_some() {
+            return Ok(());
+        }
+        if !format.is_table() {
+            emit(&Vec::<PackageJson>::new(), format)?;
         } else {
             println!();
             if dust {
@@ -169,21 +702,63 @@ pub fn cmd_report(
         .iter()
         .filter(|p| p.total_uses > 0 && p.total_uses < 5)
         .count();
-    let total_dusty = filtered_pkgs.iter().filter(|p| p.total_uses == 0).count();
+    let total_new = filtered_pkgs
+        .iter()
+        .filter(|p| is_new(p, now, grace_days))
+        .count();
+    let total_dusty = filtered_pkgs.iter().filter(|p| p.total_uses == 0).count() - total_new;
+    let gate_failure = fail_gate(&args, &filtered_pkgs, total_dusty, now);
 
     // Default mode: hide dusty unless --dust, --all, --low, --stale, or --source
-    let has_explicit_filter = dust || low.is_some() || stale.is_some() || source.is_some();
-    let display: Vec<_> = if all || has_explicit_filter {
+    let has_explicit_filter = has_explicit_filter(&args);
+    let mut display: Vec<_> = if all || has_explicit_filter {
         filtered_pkgs
     } else {
         filtered_pkgs
             .into_iter()
-            .filter(|p| p.total_uses > 0)
+            .filter(|p| p.total_uses > 0 || is_new(p, now, grace_days))
             .collect()
     };
 
+    // Sorting by size needs every candidate's size before truncation
+    let columns = report_columns(
+        &config.report.columns,
+        args.with_size || args.sort == SortKey::Size,
+        args.rate || args.sort == SortKey::Rate,
+    );
+    let with_size = args.with_size
+        || args.sort == SortKey::Size
+        || (format.is_table() && columns.contains(&"size"));
+    let mut sizes = if args.sort == SortKey::Size {
+        package_sizes(&db, &display, size_binaries, &config.size, merge)
+    } else {
+        SizeMap::new()
+    };
+    let scores =
+        if args.sort == SortKey::Recency || !format.is_table() || columns.contains(&"score") {
+            package_scores(
+                &filtered,
+                &db.get_exec_history()?,
+                now,
+                config.history.half_life_days,
+            )
+        } else {
+            ScoreMap::new()
+        };
+    let rates = package_rates(&display, now, db.get_tracking_since()?);
+    sort_packages(&mut display, args.sort, &sizes, &scores, &rates);
+
+    if args.names_only {
+        print_names(&display);
+        return gate_result(gate_failure);
+    }
+
     // Terminal height limit
-    let effective_limit = if all { 0 } else { terminal_fit(8) };
+    let effective_limit = if all || args.template.is_some() {
+        0
+    } else {
+        terminal_fit(8)
+    };
     let limited: Vec<_> = if effective_limit > 0 && display.len() > effective_limit {
         display.into_iter().take(effective_limit).collect()
     } else {
@@ -191,22 +766,48 @@ pub fn cmd_report(
     };
     let display_count = limited.len();
 
+    if with_size && sizes.is_empty() {
+        sizes = package_sizes(&db, &limited, size_binaries, &config.size, merge);
+    }
+
+    // Install roots are only needed by structured output; skip the work for tables
+    let mut install_roots: HashMap<(String, String), String> = HashMap::new();
+    if !format.is_table() || args.template.as_ref().is_some_and(RowTemplate::uses_path) {
+        let mut paths: HashMap<(String, String), Vec<&str>> = HashMap::new();
+        for b in &filtered {
+            paths.entry(b.package_key()).or_default().push(&b.path);
+        }
+        for p in &limited {
+            let key = p.key();
+            if let Some(root) = paths
+                .get(&key)
+                .and_then(|bins| detect_install_roots(bins).into_iter().next())
+            {
+                install_roots.insert(key, root);
+            }
+        }
+    }
+
     // Build output rows
+    let stamp = if format.is_table() {
+        format_timestamp
+    } else {
+        json_timestamp
+    };
     let rows: Vec<PackageJson> = limited
         .iter()
         .map(|p| {
             let last_used = p.last_seen.map(|ts| {
-                let dt: DateTime<Local> = local_datetime(ts);
-                dt.format("%Y-%m-%d %H:%M").to_string()
+                if args.relative_time && format.is_table() {
+                    humanize_ago(ts)
+                } else {
+                    stamp(ts)
+                }
             });
 
-            let status = if p.total_uses == 0 {
-                "dusty"
-            } else if p.total_uses < 5 {
-                "low"
-            } else {
-                "active"
-            };
+            // Merged rows carry the base name, which no single command removes,
+            // even when only one version was folded into them
+            let merged_versions = merged_counts.get(&p.key()).copied().filter(|&n| n > 1);
 
             PackageJson {
                 package_name: p.package_name.clone(),
@@ -214,32 +815,72 @@ pub fn cmd_report(
                 binaries: p.binaries,
                 total_uses: p.total_uses,
                 last_used,
-                status: status.to_string(),
+                first_seen: p.first_seen.map(stamp),
+                first_seen_ts: p.first_seen,
+                status: package_status(p, now, grace_days).to_string(),
+                size_bytes: size_of(&sizes, p),
+                score: score_of(&scores, p),
+                rate: score_of(&rates, p),
+                install_root: install_roots.get(&p.key()).cloned(),
+                scope: config
+                    .source_scope(&p.source)
+                    .map(|s| s.as_str().to_string()),
+                ignored_by: ignored.get(&p.key()).cloned(),
+                merged_versions,
+                uninstall_cmd: (!format.is_table()
+                    && !args.merge_versions
+                    && !config.is_read_only(&p.source))
+                .then(|| config.get_uninstall_cmd(&p.source))
+                .flatten()
+                .map(|cmd| format!("{} {}", cmd, p.package_name)),
             }
         })
         .collect();
 
-    if json {
-        println!("{}", serde_json::to_string(&rows)?);
-        return Ok(());
+    if let Some(template) = &args.template {
+        print_templated(template, &rows);
+        return gate_result(gate_failure);
     }
 
-    if export {
+    if !format.is_table() {
+        if args.source_summary_footer && format == OutputFormat::Json {
+            let counts: serde_json::Map<String, serde_json::Value> = source_counts(&rows)
+                .into_iter()
+                .map(|(source, n)| (source, n.into()))
+                .collect();
+            emit(
+                &serde_json::json!({ "packages": rows, "source_counts": counts }),
+                format,
+            )?;
+        } else {
+            emit(&rows, format)?;
+        }
+        return gate_result(gate_failure);
+    }
+
+    if args.export {
         export_uninstall_commands(&rows);
-        return Ok(());
+        return gate_result(gate_failure);
     }
 
     let use_pager = all && console::Term::stdout().is_term();
+    let _timer = Timer::start("render");
     let output = format_report_table(
         &rows,
+        &columns,
         total_active,
         total_low,
         total_dusty,
+        total_new,
         all,
         has_explicit_filter,
         effective_limit,
         display_count,
         total_count,
+        args.source_summary_footer,
+        args.source_first,
+        args.color_by,
+        args.no_truncate_names,
     );
 
     if use_pager {
@@ -248,24 +889,377 @@ pub fn cmd_report(
         print!("{}", output);
     }
 
-    Ok(())
+    gate_result(gate_failure)
+}
+
+/// `--fail-on-dusty` / `--fail-on-stale`: why the run should fail, if a
+/// threshold is exceeded by the filtered packages
+fn fail_gate(
+    args: &ReportArgs,
+    packages: &[PackageInfo],
+    total_dusty: usize,
+    now: i64,
+) -> Option<String> {
+    if let Some(max) = args.fail_on_dusty
+        && total_dusty > max
+    {
+        return Some(format!(
+            "{} dusty packages, more than --fail-on-dusty {}",
+            total_dusty, max
+        ));
+    }
+    if let Some((days, max)) = args.fail_on_stale {
+        let threshold = now - days as i64 * 24 * 60 * 60;
+        let stale = packages
+            .iter()
+            .filter(|p| p.last_seen.is_none_or(|ts| ts < threshold))
+            .count();
+        if stale > max {
+            return Some(format!(
+                "{} packages unused for {} days, more than --fail-on-stale {},{}",
+                stale, days, days, max
+            ));
+        }
+    }
+    None
+}
+
+/// Turn a `fail_gate` result into the command's exit status, once output is done
+fn gate_result(failure: Option<String>) -> Result<()> {
+    match failure {
+        Some(reason) => Err(anyhow::anyhow!(reason)),
+        None => Ok(()),
+    }
+}
+
+/// A `--template` string, parsed once up front and rendered per row
+#[derive(Debug, Clone)]
+pub struct RowTemplate(Vec<TemplatePart>);
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Field(TemplateField),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TemplateField {
+    Name,
+    Source,
+    Uses,
+    Bins,
+    LastUsed,
+    Path,
+    Status,
+}
+
+const TEMPLATE_FIELDS: &[(&str, TemplateField)] = &[
+    ("name", TemplateField::Name),
+    ("source", TemplateField::Source),
+    ("uses", TemplateField::Uses),
+    ("bins", TemplateField::Bins),
+    ("last_used", TemplateField::LastUsed),
+    ("path", TemplateField::Path),
+    ("status", TemplateField::Status),
+];
+
+impl RowTemplate {
+    /// Split `{placeholder}`s from literal text; `{{` and `}}` are literal
+    /// braces, and `\t` / `\n` a tab and a newline
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    let Some(&(_, field)) = TEMPLATE_FIELDS.iter().find(|(n, _)| *n == name) else {
+                        let known: Vec<&str> = TEMPLATE_FIELDS.iter().map(|(n, _)| *n).collect();
+                        return Err(format!(
+                            "unknown placeholder {{{}}} (expected one of: {})",
+                            name,
+                            known.join(", ")
+                        ));
+                    };
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field(field));
+                }
+                '}' => return Err("unmatched '}' (write '}}' for a literal brace)".to_string()),
+                // Shell quoting makes real tabs awkward to type
+                '\\' if chars.peek() == Some(&'t') => {
+                    chars.next();
+                    text.push('\t');
+                }
+                '\\' if chars.peek() == Some(&'n') => {
+                    chars.next();
+                    text.push('\n');
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        Ok(Self(parts))
+    }
+
+    /// `{path}` needs install roots, which are otherwise only found for JSON
+    fn uses_path(&self) -> bool {
+        self.0.contains(&TemplatePart::Field(TemplateField::Path))
+    }
+
+    /// One unstyled line; missing values (never used, no install root) are `-`
+    fn render(&self, row: &PackageJson) -> String {
+        let mut line = String::new();
+        for part in &self.0 {
+            match part {
+                TemplatePart::Text(text) => line.push_str(text),
+                TemplatePart::Field(field) => line.push_str(&match field {
+                    TemplateField::Name => row.package_name.clone(),
+                    TemplateField::Source => row.source.clone(),
+                    TemplateField::Uses => row.total_uses.to_string(),
+                    TemplateField::Bins => row.binaries.to_string(),
+                    TemplateField::LastUsed => row.last_used.clone().unwrap_or("-".to_string()),
+                    TemplateField::Path => row.install_root.clone().unwrap_or("-".to_string()),
+                    TemplateField::Status => row.status.clone(),
+                }),
+            }
+        }
+        line
+    }
+}
+
+fn print_templated(template: &RowTemplate, rows: &[PackageJson]) {
+    use std::io::Write;
+    let mut out = std::io::stdout().lock();
+    for row in rows {
+        if writeln!(out, "{}", template.render(row)).is_err() {
+            break;
+        }
+    }
+}
+
+/// One package name per line, stopping quietly if the reader goes away
+fn print_names(packages: &[PackageInfo]) {
+    use std::io::Write;
+    let mut out = std::io::stdout().lock();
+    for p in packages {
+        if writeln!(out, "{}", p.package_name).is_err() {
+            break;
+        }
+    }
+}
+
+/// Walk one binary or package through every report stage and print the verdicts
+#[allow(clippy::too_many_arguments)]
+fn explain_report(
+    db: &Database,
+    name: &str,
+    args: &ReportArgs,
+    relative: &RelativeUsage,
+    config: &config::Config,
+    binaries: &[BinaryRecord],
+    history: &HashMap<String, Vec<(i64, i64)>>,
+    now: i64,
+    boot: Option<i64>,
+) {
+    let grace_days = args.grace_days.unwrap_or(config.report.grace_days);
+    let pass = style("✓").green();
+    let fail = style("✗").red();
+
+    let matches: Vec<&BinaryRecord> = binaries
+        .iter()
+        .filter(|b| binary_name(&b.path) == name || b.package_name.as_deref() == Some(name))
+        .collect();
+
+    println!();
+    if matches.is_empty() {
+        println!(
+            "  {} {} is not tracked (no scanned binary or package has that name)",
+            fail,
+            style(name).bold()
+        );
+        println!();
+        return;
+    }
+
+    // Stage 1: binary-level filters
+    let found = find_packages(binaries, args.find.as_ref());
+    let mut package_keys: Vec<(String, String)> = Vec::new();
+    for b in &matches {
+        println!(
+            "  {} {}",
+            style(binary_name(&b.path)).bold(),
+            style(shorten_path(&b.path)).dim()
+        );
+        match binary_exclusion(b, config, args, found.as_ref()) {
+            Some(reason) => println!("    {} {}", fail, reason),
+            None => {
+                let pkg = aggregate_packages(std::slice::from_ref(*b)).remove(0);
+                match config.ignored_by(binary_name(&b.path), b.source.as_deref()) {
+                    Some(pattern) => println!(
+                        "    {} ignored by pattern {}, kept by --include-ignored (package {})",
+                        pass, pattern, pkg.package_name
+                    ),
+                    None => println!(
+                        "    {} passes ignore list and source filter (package {})",
+                        pass, pkg.package_name
+                    ),
+                }
+                let key = pkg.key();
+                if !package_keys.contains(&key) {
+                    package_keys.push(key);
+                }
+            }
+        }
+    }
+
+    if package_keys.is_empty() {
+        println!();
+        return;
+    }
+
+    // Re-run the report pipeline to place the package(s) in context
+    let filtered: Vec<BinaryRecord> = binaries
+        .iter()
+        .filter(|b| binary_exclusion(b, config, args, found.as_ref()).is_none())
+        .cloned()
+        .collect();
+    let mut packages = aggregate_packages(&filtered);
+    let sizes = if args.sort == SortKey::Size {
+        package_sizes(db, &packages, &filtered, &config.size, None)
+    } else {
+        SizeMap::new()
+    };
+    let scores = package_scores(&filtered, history, now, config.history.half_life_days);
+    let rates = package_rates(&packages, now, db.get_tracking_since().ok().flatten());
+    sort_packages(&mut packages, args.sort, &sizes, &scores, &rates);
+    let explicit = has_explicit_filter(args);
+    let effective_limit = if args.all { 0 } else { terminal_fit(8) };
+    let mut position = 0;
+
+    for p in &packages {
+        let excluded = package_exclusion(p, args, relative, now, boot, grace_days);
+        let hidden = excluded.is_none()
+            && !args.all
+            && !explicit
+            && p.total_uses == 0
+            && !is_new(p, now, grace_days);
+        if excluded.is_none() && !hidden {
+            position += 1;
+        }
+
+        if !package_keys.contains(&p.key()) {
+            continue;
+        }
+
+        println!();
+        println!(
+            "  package {} ({}, {} binaries, {} uses)",
+            style(&p.package_name).bold(),
+            p.source,
+            p.binaries,
+            p.total_uses
+        );
+        if let Some(reason) = excluded {
+            println!("    {} {}", fail, reason);
+        } else if hidden {
+            println!(
+                "    {} would show but hidden because dusty and no --all",
+                fail
+            );
+        } else if effective_limit > 0 && position > effective_limit {
+            println!(
+                "    {} passes filters but row {} is past the terminal height ({} rows, use --all)",
+                fail, position, effective_limit
+            );
+        } else {
+            println!("    {} shown (row {})", pass, position);
+        }
+    }
+    println!();
+}
+
+/// Colors for `--color-by source`, picked from the 256-color palette to stay
+/// apart from each other on dark and light terminals
+const SOURCE_PALETTE: &[u8] = &[33, 36, 70, 136, 166, 169, 98, 130, 31, 64, 161, 105];
+
+/// The color of one table cell. By status, name and uses carry the row's
+/// status and system sources stand out; by source, name, source and uses share
+/// a color hashed from the source name, so it is the same on every run.
+fn cell_color(col: &str, row: &PackageJson, color_by: ColorBy) -> Option<console::Color> {
+    use console::Color;
+
+    match color_by {
+        ColorBy::Source => matches!(col, "name" | "source" | "uses").then(|| {
+            // FNV-1a: stable across runs and Rust versions, unlike the std hasher
+            let hash = row.source.bytes().fold(0xcbf29ce484222325_u64, |h, b| {
+                (h ^ b as u64).wrapping_mul(0x100000001b3)
+            });
+            Color::Color256(SOURCE_PALETTE[(hash % SOURCE_PALETTE.len() as u64) as usize])
+        }),
+        ColorBy::Status => {
+            if col == "source" && row.scope.as_deref() == Some("system") {
+                return Some(Color::Magenta);
+            }
+            match (col, row.status.as_str()) {
+                ("name" | "uses", "dusty") => Some(Color::Red),
+                ("name" | "uses", "low") => Some(Color::Yellow),
+                ("name" | "uses", "new") => Some(Color::Cyan),
+                _ => None,
+            }
+        }
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
 fn format_report_table(
     rows: &[PackageJson],
+    columns: &[&str],
     total_active: usize,
     total_low: usize,
     total_dusty: usize,
+    total_new: usize,
     all: bool,
     has_explicit_filter: bool,
     effective_limit: usize,
     display_count: usize,
     total_count: usize,
+    source_summary: bool,
+    source_first: bool,
+    color_by: ColorBy,
+    no_truncate_names: bool,
 ) -> String {
     use std::fmt::Write;
-    let is_term = console::Term::stdout().is_term();
+    let is_term = styled_stdout();
     let mut out = String::new();
+    let name_width = name_column_width(
+        rows.iter().map(table_name),
+        column_spec("name").1,
+        no_truncate_names,
+    );
+    let width_of = |col: &str| match col {
+        "name" => name_width,
+        _ => column_spec(col).1,
+    };
 
     macro_rules! s {
         ($expr:expr) => {
@@ -278,45 +1272,98 @@ fn format_report_table(
     }
 
     writeln!(out).unwrap();
-    writeln!(
-        out,
-        "  {:<30} {:>10} {:>8} {:>8} {:>16}",
-        s!(style("Package").bold().underlined()),
-        s!(style("Source").bold().underlined()),
-        s!(style("Bins").bold().underlined()),
-        s!(style("Uses").bold().underlined()),
-        s!(style("Last Used").bold().underlined())
-    )
-    .unwrap();
+    let headers: Vec<String> = columns
+        .iter()
+        .map(|&col| {
+            let (header, _, right) = column_spec(col);
+            s!(style(pad_cell(header, width_of(col), right))
+                .bold()
+                .underlined())
+            .to_string()
+        })
+        .collect();
+    writeln!(out, "  {}", headers.join(" ")).unwrap();
     writeln!(out).unwrap();
 
-    for row in rows {
-        let uses_styled = match row.status.as_str() {
-            "dusty" => s!(style(format!("{:>8}", row.total_uses)).red()),
-            "low" => s!(style(format!("{:>8}", row.total_uses)).yellow()),
-            _ => s!(style(format!("{:>8}", row.total_uses))),
-        };
-
-        let name_display = if row.package_name.len() > 30 {
-            format!("{}...", &row.package_name[..27])
-        } else {
-            row.package_name.clone()
-        };
-
-        let name_styled = match row.status.as_str() {
-            "dusty" => s!(style(format!("{:<30}", name_display)).red()),
-            "low" => s!(style(format!("{:<30}", name_display)).yellow()),
-            _ => s!(style(format!("{:<30}", name_display))),
-        };
-
-        let last_used = row.last_used.as_deref().unwrap_or("never");
+    let write_row = |out: &mut String, row: &PackageJson| {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|&col| {
+                let (_, _, right) = column_spec(col);
+                let width = width_of(col);
+                let text = match col {
+                    "name" => {
+                        let name = table_name(row);
+                        if name.len() > width {
+                            format!("{}...", &name[..width - 3])
+                        } else {
+                            name
+                        }
+                    }
+                    "source" => row.source.clone(),
+                    "bins" => row.binaries.to_string(),
+                    "uses" => row.total_uses.to_string(),
+                    "last-used" => row.last_used.clone().unwrap_or_else(|| "never".into()),
+                    "size" => row
+                        .size_bytes
+                        .map(format_bytes)
+                        .unwrap_or_else(|| "?".into()),
+                    "rate" => row
+                        .rate
+                        .map(|v| format!("{:.1}", v))
+                        .unwrap_or_else(|| "-".into()),
+                    "score" => row
+                        .score
+                        .map(|v| format!("{:.2}", v))
+                        .unwrap_or_else(|| "-".into()),
+                    _ => String::new(),
+                };
+                let cell = style(pad_cell(&text, width, right));
+                match cell_color(col, row, color_by) {
+                    Some(color) => s!(cell.fg(color)).to_string(),
+                    None => s!(cell).to_string(),
+                }
+            })
+            .collect();
+        write!(out, "  {}", cells.join(" ")).unwrap();
+        if let Some(pattern) = &row.ignored_by {
+            write!(
+                out,
+                " {}",
+                s!(style(format!("(ignored: {})", pattern)).dim())
+            )
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+    };
 
-        writeln!(
-            out,
-            "  {} {:>10} {:>8} {} {:>16}",
-            name_styled, row.source, row.binaries, uses_styled, last_used
-        )
-        .unwrap();
+    if source_first {
+        for (i, (source, count)) in source_counts(rows).into_iter().enumerate() {
+            let group: Vec<&PackageJson> = rows.iter().filter(|r| r.source == source).collect();
+            let uses: i64 = group.iter().map(|r| r.total_uses).sum();
+            let mut totals = format!("{} packages, {} uses", count, uses);
+            if columns.contains(&"size") {
+                let bytes: u64 = group.iter().filter_map(|r| r.size_bytes).sum();
+                write!(totals, ", {}", format_bytes(bytes)).unwrap();
+            }
+            if i > 0 {
+                writeln!(out).unwrap();
+            }
+            writeln!(
+                out,
+                "  {} {}",
+                s!(style(format!("── {} ──", source)).bold()),
+                s!(style(totals).dim())
+            )
+            .unwrap();
+            for row in group {
+                write_row(&mut out, row);
+            }
+        }
+    } else {
+        for row in rows {
+            write_row(&mut out, row);
+        }
     }
 
     writeln!(out).unwrap();
@@ -339,11 +1386,28 @@ fn format_report_table(
         )
         .unwrap();
     }
+    if total_new > 0 {
+        write!(out, "{} new  ", s!(style(format!("{}", total_new)).cyan())).unwrap();
+    }
     if total_dusty > 0 {
         write!(out, "{} dusty", s!(style(format!("{}", total_dusty)).red())).unwrap();
     }
     writeln!(out).unwrap();
 
+    if source_summary {
+        let parts: Vec<String> = source_counts(rows)
+            .into_iter()
+            .map(|(source, n)| format!("{} {}", n, source))
+            .collect();
+        writeln!(
+            out,
+            "  {} {}",
+            s!(style("◦").dim()),
+            s!(style(parts.join(", ")).dim())
+        )
+        .unwrap();
+    }
+
     if !all && !has_explicit_filter && total_dusty > 0 {
         writeln!(
             out,
@@ -371,6 +1435,532 @@ fn format_report_table(
     out
 }
 
+/// Table columns: `[report] columns` if set, else the built-in layout.
+/// `--with-size` adds a size column when the configured list lacks one.
+fn report_columns(configured: &[String], with_size: bool, with_rate: bool) -> Vec<&str> {
+    let mut columns: Vec<&str> = if configured.is_empty() {
+        vec!["name", "source", "bins", "uses", "last-used"]
+    } else {
+        configured.iter().map(String::as_str).collect()
+    };
+    if with_size && !columns.contains(&"size") {
+        columns.push("size");
+    }
+    if with_rate && !columns.contains(&"rate") {
+        columns.push("rate");
+    }
+    columns
+}
+
+/// Header, width and right-alignment of a report column
+fn column_spec(column: &str) -> (&'static str, usize, bool) {
+    match column {
+        "name" => ("Package", 30, false),
+        "source" => ("Source", 10, true),
+        "bins" => ("Bins", 8, true),
+        "uses" => ("Uses", 8, true),
+        "last-used" => ("Last Used", 16, true),
+        "size" => ("Size", 10, true),
+        "score" => ("Score", 8, true),
+        "rate" => ("Uses/wk", 8, true),
+        _ => ("", 0, false),
+    }
+}
+
+/// The name cell of a table row
+fn table_name(row: &PackageJson) -> String {
+    match row.merged_versions {
+        Some(n) => format!("{} ({} versions)", row.package_name, n),
+        None => row.package_name.clone(),
+    }
+}
+
+/// Width of a name column: `default`, or with `--no-truncate-names` as wide as
+/// the longest name so none gets cut
+fn name_column_width(
+    names: impl Iterator<Item = impl AsRef<str>>,
+    default: usize,
+    no_truncate: bool,
+) -> usize {
+    if !no_truncate {
+        return default;
+    }
+    names
+        .map(|n| n.as_ref().len())
+        .max()
+        .unwrap_or(0)
+        .max(default)
+}
+
+fn pad_cell(text: &str, width: usize, right: bool) -> String {
+    if right {
+        format!("{:>width$}", text)
+    } else {
+        format!("{:<width$}", text)
+    }
+}
+
+/// Never run, and every binary was first seen within the grace period
+fn is_new(p: &PackageInfo, now: i64, grace_days: u32) -> bool {
+    package_status(p, now, grace_days) == "new"
+}
+
+pub(super) fn package_status(p: &PackageInfo, now: i64, grace_days: u32) -> &'static str {
+    usage_status(p.total_uses, p.first_seen, now, grace_days)
+}
+
+/// `reports` kind holding the package list `--since-last` diffs against
+const BASELINE_REPORT: &str = "last_report";
+
+/// Store every package (before usage filters) so the next diff sees the whole picture
+fn save_baseline(db: &Database, packages: &[PackageInfo], now: i64, grace_days: u32) -> Result<()> {
+    let rows: Vec<PackageJson> = packages
+        .iter()
+        .map(|p| PackageJson {
+            package_name: p.package_name.clone(),
+            source: p.source.clone(),
+            binaries: p.binaries,
+            total_uses: p.total_uses,
+            last_used: p.last_seen.map(format_timestamp),
+            first_seen: p.first_seen.map(format_timestamp),
+            first_seen_ts: p.first_seen,
+            status: package_status(p, now, grace_days).to_string(),
+            size_bytes: None,
+            score: None,
+            rate: None,
+            install_root: None,
+            scope: None,
+            ignored_by: None,
+            merged_versions: None,
+            uninstall_cmd: None,
+        })
+        .collect();
+    db.save_report(BASELINE_REPORT, &serde_json::to_string(&rows)?)
+}
+
+/// One package that differs from the `--since-last` baseline
+#[derive(Serialize)]
+struct PackageChange {
+    package_name: String,
+    source: String,
+    /// "new", "removed" or "used"
+    change: &'static str,
+    uses_before: Option<i64>,
+    uses_after: Option<i64>,
+    status_before: Option<String>,
+    status_after: Option<String>,
+}
+
+/// Diff the current packages against the saved baseline, then optionally
+/// replace the baseline with them
+fn since_last_report(
+    db: &Database,
+    packages: &[PackageInfo],
+    args: &ReportArgs,
+    format: OutputFormat,
+    new_baseline: Option<&[PackageInfo]>,
+    now: i64,
+    grace_days: u32,
+) -> Result<()> {
+    let baseline = match db.get_report(BASELINE_REPORT)? {
+        Some((ts, json)) => Some((ts, serde_json::from_str::<Vec<PackageJson>>(&json)?)),
+        None => None,
+    };
+    if let Some(new_baseline) = new_baseline {
+        save_baseline(db, new_baseline, now, grace_days)?;
+    }
+
+    let Some((baseline_ts, before)) = baseline else {
+        if !format.is_table() {
+            emit(
+                &serde_json::json!({ "baseline_at": null, "changes": [] }),
+                format,
+            )?;
+        } else {
+            println!();
+            println!(
+                "  {} No earlier report to compare against{}",
+                style("◦").dim(),
+                if new_baseline.is_some() {
+                    "; saved this one as the baseline"
+                } else {
+                    ""
+                }
+            );
+            println!();
+        }
+        return Ok(());
+    };
+
+    let mut old: HashMap<(&str, &str), &PackageJson> = before
+        .iter()
+        .map(|p| ((p.package_name.as_str(), p.source.as_str()), p))
+        .collect();
+    let mut changes = Vec::new();
+    for p in packages {
+        let status = package_status(p, now, grace_days);
+        match old.remove(&(p.package_name.as_str(), p.source.as_str())) {
+            Some(prev) if prev.total_uses == p.total_uses => {}
+            prev => changes.push(PackageChange {
+                package_name: p.package_name.clone(),
+                source: p.source.clone(),
+                change: if prev.is_some() { "used" } else { "new" },
+                uses_before: prev.map(|b| b.total_uses),
+                uses_after: Some(p.total_uses),
+                status_before: prev.map(|b| b.status.clone()),
+                status_after: Some(status.to_string()),
+            }),
+        }
+    }
+    for prev in old.into_values() {
+        changes.push(PackageChange {
+            package_name: prev.package_name.clone(),
+            source: prev.source.clone(),
+            change: "removed",
+            uses_before: Some(prev.total_uses),
+            uses_after: None,
+            status_before: Some(prev.status.clone()),
+            status_after: None,
+        });
+    }
+    changes.sort_by(|a, b| {
+        a.change
+            .cmp(b.change)
+            .then_with(|| a.package_name.cmp(&b.package_name))
+            .then_with(|| a.source.cmp(&b.source))
+    });
+
+    if !format.is_table() {
+        let baseline_at = json_timestamp(baseline_ts);
+        return emit(
+            &serde_json::json!({ "baseline_at": baseline_at, "changes": changes }),
+            format,
+        );
+    }
+
+    println!();
+    println!(
+        "  Changes since {} {}",
+        style(format_timestamp(baseline_ts)).bold(),
+        style(format!("({})", humanize_ago(baseline_ts))).dim()
+    );
+    println!();
+    if changes.is_empty() {
+        println!("  {} Nothing changed", style("●").green());
+    }
+    let name_width = name_column_width(
+        changes
+            .iter()
+            .map(|c| format!("{} ({})", c.package_name, c.source)),
+        40,
+        args.no_truncate_names,
+    );
+    for c in &changes {
+        let name = format!("{} ({})", c.package_name, c.source);
+        let detail = match c.change {
+            "new" => "new".to_string(),
+            "removed" => "removed".to_string(),
+            _ => format!(
+                "{} → {} uses",
+                c.uses_before.unwrap_or(0),
+                c.uses_after.unwrap_or(0)
+            ),
+        };
+        let marker = match c.change {
+            "new" => style("+").green(),
+            "removed" => style("-").red(),
+            _ => style("~").yellow(),
+        };
+        let status_change = match (&c.status_before, &c.status_after) {
+            (Some(before), Some(after)) if before != after => {
+                format!("  {}", style(format!("{} → {}", before, after)).dim())
+            }
+            _ => String::new(),
+        };
+        println!(
+            "  {} {:<name_width$} {}{}",
+            marker,
+            truncate_str(&name, name_width),
+            detail,
+            status_change
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// `--unused-deps`: active binaries that are the only active user of some
+/// library packages, with the dusty binaries that share those libraries
+fn unused_deps_report(
+    db: &Database,
+    binaries: &[BinaryRecord],
+    config: &config::Config,
+    args: &ReportArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let spinner = std::cell::RefCell::new(Spinner::new());
+    let timer = Timer::start("dep analysis");
+    deps::update_dep_cache(
+        db,
+        binaries,
+        false,
+        Some(&|current, total| {
+            spinner
+                .borrow_mut()
+                .update("Analyzing binaries", current + 1, total)
+        }),
+    )?;
+    spinner.into_inner().finish();
+    drop(timer);
+
+    // Every binary decides who is active; only the shown ones get rows
+    let found = find_packages(binaries, args.find.as_ref());
+    let entries: Vec<_> = deps::find_unused_deps(db, binaries)?
+        .into_iter()
+        .filter(|e| {
+            binaries
+                .iter()
+                .find(|b| b.path == e.binary_path)
+                .is_some_and(|b| binary_exclusion(b, config, args, found.as_ref()).is_none())
+        })
+        .collect();
+
+    if !format.is_table() {
+        return emit(&entries, format);
+    }
+
+    println!();
+    if entries.is_empty() {
+        println!(
+            "  {} No library packages depend on a single active binary",
+            style("●").green()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let lib_width = name_column_width(
+        entries
+            .iter()
+            .flat_map(|e| e.libs.iter().map(|l| l.package_name.as_str())),
+        30,
+        args.no_truncate_names,
+    );
+    for entry in &entries {
+        println!(
+            "  {}  {} {}",
+            style(binary_name(&entry.binary_path)).bold(),
+            style(format!("{} uses", entry.uses)).green(),
+            style(shorten_path(&entry.binary_path)).dim()
+        );
+        for lib in &entry.libs {
+            let size = lib
+                .size_bytes
+                .map(format_bytes)
+                .unwrap_or_else(|| "?".into());
+            let dusty = match lib.used_by_dusty.len() {
+                0 => String::new(),
+                n => {
+                    let names: Vec<&str> = lib
+                        .used_by_dusty
+                        .iter()
+                        .map(|p| binary_name(p))
+                        .take(3)
+                        .collect();
+                    let more = if n > 3 { ", ..." } else { "" };
+                    format!("+ {} dusty: {}{}", n, names.join(", "), more)
+                }
+            };
+            println!(
+                "    {:<lib_width$} {:>10} {:>10}  {}",
+                truncate_str(&lib.package_name, lib_width),
+                lib.manager,
+                size,
+                style(dusty).red()
+            );
+        }
+        println!();
+    }
+
+    let lib_count: usize = entries.iter().map(|e| e.libs.len()).sum();
+    let total_bytes: u64 = entries.iter().map(|e| e.total_bytes).sum();
+    println!(
+        "  {} {} library packages kept by a single active binary ({})",
+        style("◦").dim(),
+        lib_count,
+        format_bytes(total_bytes)
+    );
+    println!();
+    Ok(())
+}
+
+/// Uses of one typed command name, across the binaries it ran
+#[derive(Serialize)]
+struct InvocationJson {
+    name: String,
+    uses: i64,
+    last_used: Option<i64>,
+    /// Resolved binaries this name ran, most used first
+    targets: Vec<String>,
+}
+
+/// Group uses by the name that was typed. Execs recorded under another name
+/// (argv[0]) count for that name; the rest of a binary's uses count for its
+/// own file name.
+fn invocation_groups(
+    binaries: &[&BinaryRecord],
+    invocations: &[InvocationRecord],
+) -> Vec<InvocationJson> {
+    let mut by_path: HashMap<&str, Vec<&InvocationRecord>> = HashMap::new();
+    for inv in invocations {
+        by_path.entry(inv.path.as_str()).or_default().push(inv);
+    }
+
+    #[derive(Default)]
+    struct Group<'a> {
+        uses: i64,
+        last_used: Option<i64>,
+        targets: HashMap<&'a str, i64>,
+    }
+    let mut groups: HashMap<&str, Group> = HashMap::new();
+    let mut add = |name, path, uses, last_used: Option<i64>| {
+        if uses <= 0 {
+            return;
+        }
+        let group = groups.entry(name).or_default();
+        group.uses += uses;
+        group.last_used = group.last_used.max(last_used);
+        *group.targets.entry(path).or_default() += uses;
+    };
+    for b in binaries {
+        let renamed = by_path
+            .get(b.path.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for inv in renamed {
+            add(
+                inv.name.as_str(),
+                b.path.as_str(),
+                inv.count,
+                Some(inv.last_seen),
+            );
+        }
+        let renamed_uses: i64 = renamed.iter().map(|inv| inv.count).sum();
+        add(
+            binary_name(&b.path),
+            b.path.as_str(),
+            b.count - renamed_uses,
+            b.last_seen,
+        );
+    }
+
+    let mut rows: Vec<InvocationJson> = groups
+        .into_iter()
+        .map(|(name, group)| {
+            let mut targets: Vec<(&str, i64)> = group.targets.into_iter().collect();
+            targets.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            InvocationJson {
+                name: name.to_string(),
+                uses: group.uses,
+                last_used: group.last_used,
+                targets: targets.into_iter().map(|(p, _)| p.to_string()).collect(),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.name.cmp(&b.name)));
+    rows
+}
+
+/// `--by-invocation`: usage by the command name typed rather than the binary run
+fn invocation_report(
+    db: &Database,
+    binaries: &[BinaryRecord],
+    config: &config::Config,
+    args: &ReportArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let found = find_packages(binaries, args.find.as_ref());
+    let shown: Vec<&BinaryRecord> = binaries
+        .iter()
+        .filter(|b| binary_exclusion(b, config, args, found.as_ref()).is_none())
+        .collect();
+    let rows = invocation_groups(&shown, &db.get_invocations()?);
+
+    if !format.is_table() {
+        return emit(&rows, format);
+    }
+
+    println!();
+    if rows.is_empty() {
+        println!("  {} No recorded uses yet", style("●").yellow());
+        println!();
+        return Ok(());
+    }
+
+    let limit = match terminal_fit(6) {
+        n if n > 0 && !args.all => n,
+        _ => rows.len(),
+    };
+    let name_width = name_column_width(
+        rows.iter().take(limit).map(|r| r.name.as_str()),
+        20,
+        args.no_truncate_names,
+    );
+    for row in rows.iter().take(limit) {
+        let last_used = row
+            .last_used
+            .map(|ts| {
+                if args.relative_time {
+                    humanize_ago(ts)
+                } else {
+                    format_timestamp(ts)
+                }
+            })
+            .unwrap_or_else(|| "-".into());
+        // Only worth spelling out when the name isn't simply the binary's own
+        let targets: Vec<String> = row
+            .targets
+            .iter()
+            .filter(|path| row.targets.len() > 1 || binary_name(path) != row.name)
+            .map(|path| shorten_path(path))
+            .collect();
+        let arrow = if targets.is_empty() { "" } else { "→ " };
+        println!(
+            "  {:<name_width$} {:>8}  {:<16}  {}",
+            style(truncate_str(&row.name, name_width)).bold(),
+            style(row.uses).green(),
+            last_used,
+            style(format!("{}{}", arrow, targets.join(", "))).dim()
+        );
+    }
+    if rows.len() > limit {
+        println!(
+            "  {}",
+            style(format!(
+                "... {} more names (--all to show)",
+                rows.len() - limit
+            ))
+            .dim()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// How many of the given rows come from each source, largest first
+fn source_counts(rows: &[PackageJson]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for row in rows {
+        *counts.entry(row.source.as_str()).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(source, n)| (source.to_string(), n))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
 /// Export uninstall commands for the given packages
 fn export_uninstall_commands(rows: &[PackageJson]) {
     let config = config::Config::load().unwrap_or_default();
@@ -430,6 +2020,7 @@ mod tests {
             last_seen: if count > 0 { Some(1000) } else { None },
             source: Some(source.to_string()),
             package_name: Some(pkg.to_string()),
+            observed: count > 0,
         }
     }
 
@@ -470,6 +2061,7 @@ mod tests {
                 last_seen: Some(100),
                 source: Some("s".to_string()),
                 package_name: Some("pkg".to_string()),
+                observed: true,
             },
             BinaryRecord {
                 path: "/b".to_string(),
@@ -478,6 +2070,7 @@ mod tests {
                 last_seen: Some(200),
                 source: Some("s".to_string()),
                 package_name: Some("pkg".to_string()),
+                observed: true,
             },
         ];
 
@@ -487,6 +2080,34 @@ mod tests {
         assert_eq!(packages[0].last_seen, Some(200)); // takes the max
     }
 
+    #[test]
+    fn test_aggregate_packages_first_seen() {
+        let seen = |path: &str, pkg: &str, first_seen: Option<i64>| BinaryRecord {
+            first_seen,
+            ..make_binary(path, 0, "s", pkg)
+        };
+        let binaries = vec![
+            seen("/a", "pkg", Some(300)),
+            seen("/b", "pkg", Some(100)),
+            seen("/c", "pkg", Some(200)),
+            seen("/d", "old", Some(100)),
+            seen("/e", "old", None),
+        ];
+
+        let packages = aggregate_packages(&binaries);
+        let first_seen = |name: &str| {
+            packages
+                .iter()
+                .find(|p| p.package_name == name)
+                .unwrap()
+                .first_seen
+        };
+        // first_seen and first_seen_ts take the earliest member
+        assert_eq!(first_seen("pkg"), Some(100));
+        // A member from before tracking started makes the package's age unknown
+        assert_eq!(first_seen("old"), None);
+    }
+
     #[test]
     fn test_aggregate_packages_missing_fields() {
         let binaries = vec![BinaryRecord {
@@ -496,6 +2117,7 @@ mod tests {
             last_seen: None,
             source: None,
             package_name: None,
+            observed: false,
         }];
 
         let packages = aggregate_packages(&binaries);
@@ -503,4 +2125,259 @@ mod tests {
         assert_eq!(packages[0].package_name, "mytool"); // falls back to filename
         assert_eq!(packages[0].source, "other");
     }
+
+    #[test]
+    fn test_merge_versions_folds_into_base_name() {
+        let binaries = vec![
+            make_binary("/opt/homebrew/bin/python3.12", 4, "homebrew", "python@3.12"),
+            make_binary("/opt/homebrew/bin/python3.13", 6, "homebrew", "python@3.13"),
+            make_binary("/opt/homebrew/bin/pip3.13", 1, "homebrew", "python@3.13"),
+            make_binary("/opt/homebrew/bin/jq", 2, "homebrew", "jq"),
+        ];
+
+        let (merged, counts) = merge_versions(&binaries, None);
+        let packages = aggregate_packages(&merged);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].package_name, "python");
+        assert_eq!(packages[0].total_uses, 11);
+        assert_eq!(packages[0].binaries, 3);
+        let key = |p: &str| ("homebrew".to_string(), p.to_string());
+        assert_eq!(counts[&key("python")], 2);
+        assert_eq!(counts[&key("jq")], 1);
+    }
+
+    #[test]
+    fn test_reference_uses_resolves_binary_then_package() {
+        let binaries = vec![
+            make_binary("/usr/bin/git", 40, "apt", "git"),
+            make_binary("/usr/bin/git-shell", 2, "apt", "git"),
+            make_binary("/usr/bin/vim", 10, "apt", "vim"),
+            make_binary("/opt/homebrew/bin/vim", 5, "homebrew", "vim"),
+        ];
+
+        // A binary name counts its whole package
+        assert_eq!(reference_uses(&binaries, "git-shell").unwrap(), 42);
+        assert_eq!(reference_uses(&binaries, "git").unwrap(), 42);
+        // Two packages share the name
+        assert!(reference_uses(&binaries, "vim").is_err());
+        assert!(reference_uses(&binaries, "emacs").is_err());
+    }
+
+    #[test]
+    fn test_invocation_groups_split_renamed_execs() {
+        let nvim = make_binary("/usr/bin/nvim", 10, "apt", "neovim");
+        let vim = make_binary("/usr/bin/vim", 2, "apt", "vim");
+        let invocations = vec![InvocationRecord {
+            path: "/usr/bin/nvim".into(),
+            name: "vi".into(),
+            count: 7,
+            last_seen: 100,
+        }];
+        let rows = invocation_groups(&[&nvim, &vim], &invocations);
+        let summary: Vec<(&str, i64)> = rows.iter().map(|r| (r.name.as_str(), r.uses)).collect();
+        assert_eq!(summary, vec![("vi", 7), ("nvim", 3), ("vim", 2)]);
+        assert_eq!(rows[0].targets, vec!["/usr/bin/nvim".to_string()]);
+    }
+
+    #[test]
+    fn test_cell_color_by_source_is_stable() {
+        let row = |source: &str, status: &str| -> PackageJson {
+            serde_json::from_value(serde_json::json!({
+                "package_name": "pkg",
+                "source": source,
+                "binaries": 1,
+                "total_uses": 0,
+                "last_used": null,
+                "status": status,
+                "install_root": null,
+            }))
+            .unwrap()
+        };
+        let brew = cell_color("name", &row("homebrew", "dusty"), ColorBy::Source);
+        assert!(brew.is_some());
+        assert_eq!(
+            brew,
+            cell_color("uses", &row("homebrew", "active"), ColorBy::Source)
+        );
+        assert_eq!(
+            cell_color("bins", &row("homebrew", "dusty"), ColorBy::Source),
+            None
+        );
+        assert_eq!(
+            cell_color("name", &row("homebrew", "dusty"), ColorBy::Status),
+            Some(console::Color::Red)
+        );
+    }
+
+    #[test]
+    fn test_row_template_renders_placeholders() {
+        let row: PackageJson = serde_json::from_value(serde_json::json!({
+            "package_name": "ripgrep",
+            "source": "cargo",
+            "binaries": 1,
+            "total_uses": 42,
+            "last_used": null,
+            "status": "active",
+            "install_root": "/home/me/.cargo",
+        }))
+        .unwrap();
+
+        let template =
+            RowTemplate::parse("{name}\t{source} {uses}x {{{status}}} {last_used} {path}").unwrap();
+        assert!(template.uses_path());
+        assert_eq!(
+            template.render(&row),
+            "ripgrep\tcargo 42x {active} - /home/me/.cargo"
+        );
+        assert_eq!(
+            RowTemplate::parse(r"{name}\t{bins}\n")
+                .unwrap()
+                .render(&row),
+            "ripgrep\t1\n"
+        );
+        assert!(!RowTemplate::parse("{name} {bins}").unwrap().uses_path());
+
+        let err = RowTemplate::parse("{name} {size}").unwrap_err();
+        assert!(err.contains("unknown placeholder {size}"), "{}", err);
+        assert!(RowTemplate::parse("{name").is_err());
+        assert!(RowTemplate::parse("name}").is_err());
+    }
+
+    #[test]
+    fn test_name_column_width_grows_only_without_truncation() {
+        let names = ["node", "@aws-sdk/client-cloudformation-toolkit"];
+        assert_eq!(name_column_width(names.iter(), 30, false), 30);
+        assert_eq!(name_column_width(names.iter(), 30, true), names[1].len());
+        assert_eq!(name_column_width(["git"].iter(), 30, true), 30);
+        assert_eq!(name_column_width(std::iter::empty::<&str>(), 20, true), 20);
+    }
+
+    #[test]
+    fn test_package_scores_prefer_recent_use() {
+        let now = 400 * 86400;
+        let binaries = vec![
+            make_binary("/opt/homebrew/bin/old", 50, "homebrew", "old"),
+            make_binary("/opt/homebrew/bin/steady", 20, "homebrew", "steady"),
+        ];
+        let mut history = HashMap::new();
+        history.insert("/opt/homebrew/bin/old".to_string(), vec![(35, 50)]);
+        history.insert(
+            "/opt/homebrew/bin/steady".to_string(),
+            (0..20).map(|i| (380 + i, 1)).collect(),
+        );
+
+        let scores = package_scores(&binaries, &history, now, 30.0);
+        let old = scores[&("homebrew".to_string(), "old".to_string())];
+        let steady = scores[&("homebrew".to_string(), "steady".to_string())];
+        assert!(steady > old, "steady {steady} should outrank old {old}");
+        assert!(old < 1.0);
+    }
+
+    #[test]
+    fn test_package_rates() {
+        const DAY: i64 = 86400;
+        let now = 100 * DAY;
+        let pkg = |name: &str, uses: i64, first_seen: Option<i64>| PackageInfo {
+            package_name: name.to_string(),
+            source: "s".to_string(),
+            binaries: 1,
+            total_uses: uses,
+            last_seen: None,
+            first_seen,
+        };
+        let packages = vec![
+            pkg("steady", 20, Some(now - 14 * DAY)),
+            // Installed yesterday: a week at least, not 7x the rate
+            pkg("fresh", 7, Some(now - DAY)),
+            // Predates tracking, counts from when tracking started
+            pkg("old", 30, None),
+        ];
+        let key = |name: &str| ("s".to_string(), name.to_string());
+
+        let rates = package_rates(&packages, now, Some(now - 30 * DAY));
+        assert_eq!(rates[&key("steady")], 10.0);
+        assert_eq!(rates[&key("fresh")], 7.0);
+        assert_eq!(rates[&key("old")], 7.0);
+
+        // Without a tracking start there's nothing to count an old package from
+        let rates = package_rates(&packages, now, None);
+        assert!(!rates.contains_key(&key("old")));
+        assert_eq!(rates.len(), 2);
+    }
+
+    #[test]
+    fn test_fail_gate() {
+        use clap::Parser;
+
+        const DAY: i64 = 86400;
+        let now = 100 * DAY;
+        let report_args = |flags: &[&str]| {
+            let cli =
+                crate::cli::Cli::try_parse_from(["dusty", "report"].iter().chain(flags)).unwrap();
+            match cli.command {
+                crate::cli::Commands::Report(args) => args,
+                _ => unreachable!(),
+            }
+        };
+        let pkg = |name: &str, last_seen: Option<i64>| PackageInfo {
+            package_name: name.to_string(),
+            source: "s".to_string(),
+            binaries: 1,
+            total_uses: last_seen.map_or(0, |_| 1),
+            last_seen,
+            first_seen: None,
+        };
+        let packages = vec![
+            pkg("never", None),
+            pkg("old", Some(now - 120 * DAY)),
+            pkg("recent", Some(now - 10 * DAY)),
+        ];
+
+        let args = report_args(&["--fail-on-dusty", "1"]);
+        assert!(fail_gate(&args, &packages, 1, now).is_none());
+        assert_eq!(
+            fail_gate(&args, &packages, 2, now).unwrap(),
+            "2 dusty packages, more than --fail-on-dusty 1"
+        );
+
+        // Never used counts as stale, as does last use before the cutoff
+        let args = report_args(&["--fail-on-stale", " 90 , 2 "]);
+        assert_eq!(args.fail_on_stale, Some((90, 2)));
+        assert!(fail_gate(&args, &packages, 0, now).is_none());
+        let args = report_args(&["--fail-on-stale", "90,1"]);
+        assert_eq!(
+            fail_gate(&args, &packages, 0, now).unwrap(),
+            "2 packages unused for 90 days, more than --fail-on-stale 90,1"
+        );
+        let args = report_args(&["--fail-on-stale", "5,1"]);
+        assert!(
+            fail_gate(&args, &packages, 0, now)
+                .unwrap()
+                .starts_with("3 packages")
+        );
+
+        for bad in ["90", "x,1", "90,-1"] {
+            let flags = ["dusty", "report", "--fail-on-stale", bad];
+            assert!(crate::cli::Cli::try_parse_from(flags).is_err(), "{bad}");
+        }
+        assert!(fail_gate(&report_args(&[]), &packages, 3, now).is_none());
+    }
+
+    #[test]
+    fn test_find_selects_whole_packages() {
+        let binaries = vec![
+            make_binary("/usr/bin/ffmpeg", 3, "apt", "ffmpeg"),
+            make_binary("/usr/bin/ffprobe", 1, "apt", "ffmpeg"),
+            make_binary("/usr/bin/git", 9, "apt", "git"),
+        ];
+        let re = regex::Regex::new("^/usr/bin/ffmpeg$").unwrap();
+        let found = find_packages(&binaries, Some(&re)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found.contains(&("apt".to_string(), "ffmpeg".to_string())));
+
+        let re = regex::Regex::new("^gi").unwrap();
+        let found = find_packages(&binaries, Some(&re)).unwrap();
+        assert!(found.contains(&("apt".to_string(), "git".to_string())));
+        assert!(find_packages(&binaries, None).is_none());
+    }
 }
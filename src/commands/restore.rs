@@ -1,11 +1,15 @@
 use anyhow::Result;
 use console::style;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::defaults;
 use crate::storage::Database;
+use crate::ui::format_bytes;
+use crate::utils::du_sizes;
+use crate::{config, defaults};
 
-pub fn cmd_restore(name: String) -> Result<()> {
+pub fn cmd_restore(name: String, to: Option<PathBuf>) -> Result<()> {
     let db = Database::open()?;
     let matches = db.get_trash_by_name(&name)?;
 
@@ -44,42 +48,69 @@ pub fn cmd_restore(name: String) -> Result<()> {
                 return Ok(());
             }
 
-            if std::path::Path::new(original).exists() {
+            let dest = match &to {
+                Some(to) => std::path::absolute(to)?.to_string_lossy().into_owned(),
+                None => original.clone(),
+            };
+
+            if std::path::Path::new(&dest).exists() {
+                let what = if to.is_some() {
+                    "Target"
+                } else {
+                    "Original path"
+                };
+                println!("  {} {} already exists: {}", style("●").red(), what, dest);
+                println!("  {} Trash location: {}", style("◦").dim(), trash_path);
+                if to.is_none() {
+                    println!(
+                        "  {} Use {} to restore it somewhere else",
+                        style("◦").dim(),
+                        style("--to <path>").cyan()
+                    );
+                }
+                println!();
+                return Ok(());
+            }
+
+            if to.is_some()
+                && let Some(problem) = target_problem(Path::new(trash_path), Path::new(&dest))?
+            {
                 println!(
-                    "  {} Original path already exists: {}",
+                    "  {} Can't restore to {}: {}",
                     style("●").red(),
-                    original
+                    dest,
+                    problem
                 );
-                println!("  {} Trash location: {}", style("◦").dim(), trash_path);
                 println!();
                 return Ok(());
             }
 
             // Try rename first
-            if std::fs::rename(trash_path, original).is_ok() {
+            if std::fs::rename(trash_path, &dest).is_ok() {
                 db.delete_trash(item.id)?;
                 println!(
                     "  {} Restored {} → {}",
                     style("●").green(),
                     style(&item.package_name).bold(),
-                    original
+                    dest
                 );
                 println!();
                 return Ok(());
             }
 
-            // Fallback: sudo mv
-            let status = Command::new(defaults::SUDO)
-                .args(["mv", trash_path, original])
-                .status();
-
-            if status.map(|s| s.success()).unwrap_or(false) {
+            // Fallback: mv (copies across filesystems), then sudo mv
+            let moved = |cmd: &mut Command| {
+                cmd.args([trash_path, dest.as_str()])
+                    .status()
+                    .is_ok_and(|s| s.success())
+            };
+            if moved(&mut Command::new("mv")) || moved(Command::new(defaults::SUDO).arg("mv")) {
                 db.delete_trash(item.id)?;
                 println!(
                     "  {} Restored {} → {}",
                     style("●").green(),
                     style(&item.package_name).bold(),
-                    original
+                    dest
                 );
             } else {
                 println!(
@@ -90,6 +121,13 @@ pub fn cmd_restore(name: String) -> Result<()> {
                 );
             }
         }
+        "package_manager" if to.is_some() => {
+            println!(
+                "  {} {} was uninstalled via package manager; --to only applies to moved items",
+                style("●").yellow(),
+                style(&item.package_name).bold()
+            );
+        }
         "package_manager" => {
             if let Some(ref cmd) = item.restore_cmd {
                 println!(
@@ -126,3 +164,39 @@ pub fn cmd_restore(name: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Why a trashed directory can't be moved to `dest`: a missing parent, or
+/// too little free space when the move crosses filesystems
+fn target_problem(trash_path: &Path, dest: &Path) -> Result<Option<String>> {
+    let Some(parent) = dest.parent().filter(|p| p.is_dir()) else {
+        return Ok(Some("its parent directory doesn't exist".to_string()));
+    };
+    if std::fs::metadata(trash_path)?.dev() == std::fs::metadata(parent)?.dev() {
+        return Ok(None);
+    }
+
+    let config = config::Config::load()?;
+    let trash_str = trash_path.to_string_lossy();
+    let Some(&needed) = du_sizes(&[trash_str.as_ref()], &config.size).get(trash_str.as_ref())
+    else {
+        return Ok(None);
+    };
+    match available_space(parent) {
+        Some(free) if free < needed => Ok(Some(format!(
+            "needs {} but only {} is free there",
+            format_bytes(needed),
+            format_bytes(free)
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+fn available_space(path: &Path) -> Option<u64> {
+    let c_path = std::ffi::CString::new(path.as_os_str().as_encoded_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
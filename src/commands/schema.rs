@@ -0,0 +1,41 @@
+use anyhow::Result;
+use schemars::schema_for;
+
+use super::alias::AliasJson;
+use super::audit::AuditEntry;
+use super::clean::{CleanSummary, PlannedGroup};
+use super::dupes::DupeGroup;
+use super::inventory::InventoryJson;
+use super::report::PackageJson;
+use super::size::SizeEntry;
+use super::stats::StatsJson;
+use super::status::StatusJson;
+use super::trash::TrashJson;
+use super::why::WhyOutput;
+use crate::cli::SchemaCommand;
+use crate::deps::DepsReport;
+use crate::ui::stamp_schema_version;
+
+/// Print the JSON Schema of `command`'s `--json` output, including the
+/// `schema_version` every output is stamped with
+pub fn cmd_schema(command: SchemaCommand) -> Result<()> {
+    let schema = match command {
+        SchemaCommand::Report => schema_for!(Vec<PackageJson>),
+        SchemaCommand::Stats => schema_for!(StatsJson),
+        SchemaCommand::Status => schema_for!(StatusJson),
+        SchemaCommand::Deps => schema_for!(DepsReport),
+        SchemaCommand::Audit => schema_for!(Vec<AuditEntry>),
+        SchemaCommand::Dupes => schema_for!(Vec<DupeGroup>),
+        SchemaCommand::Size => schema_for!(Vec<SizeEntry>),
+        SchemaCommand::Trash => schema_for!(Vec<TrashJson>),
+        SchemaCommand::Inventory => schema_for!(Vec<InventoryJson>),
+        SchemaCommand::Alias => schema_for!(Vec<AliasJson>),
+        SchemaCommand::Why => schema_for!(WhyOutput),
+        SchemaCommand::Clean => schema_for!(Vec<PlannedGroup>),
+        SchemaCommand::CleanSummary => schema_for!(CleanSummary),
+    };
+    let mut schema = serde_json::to_value(schema)?;
+    stamp_schema_version(&mut schema);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -1,25 +1,73 @@
 use anyhow::Result;
 use console::style;
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::process::Command;
+use std::os::unix::fs::MetadataExt;
 
+use crate::cli::SortKey;
 use crate::config;
+use crate::defaults;
+use crate::deps;
 use crate::storage::{self, Database};
-use crate::ui::{Spinner, format_bytes, print_with_pager, terminal_fit, truncate_str};
-use crate::utils::{detect_install_roots, start_daemon, sync_binaries};
+use crate::ui::{
+    OutputFormat, Spinner, Timer, emit, format_bytes, print_with_pager, terminal_fit, truncate_str,
+};
+use crate::utils::{detect_install_roots, du_sizes, start_daemon, sync_binaries};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct SizeEntry {
+    source: String,
+    package_name: String,
+    size_bytes: Option<u64>,
+    size_display: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    apparent_bytes: Option<u64>,
+    /// Library packages only this package links (--include-deps)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclusive_deps: Option<Vec<deps::ExclusiveLib>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deps_bytes: Option<u64>,
+    /// Own size plus `deps_bytes`; what removing the package and its exclusive libraries frees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    with_deps_bytes: Option<u64>,
+    binary_count: usize,
+    status: String,
+    #[serde(skip)]
+    uses: i64,
+    #[serde(skip)]
+    last_seen: Option<i64>,
+}
+
+pub fn cmd_size(
+    dust: bool,
+    source_filter: Option<String>,
+    apparent: bool,
+    include_deps: bool,
+    sort: SortKey,
+    reverse: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if matches!(sort, SortKey::Recency | SortKey::Rate) {
+        anyhow::bail!("--sort recency and --sort rate are only available in dusty report");
+    }
 
-pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result<()> {
     let db = Database::open()?;
     let config = config::Config::load()?;
     sync_binaries(&db)?;
     start_daemon(true)?;
 
-    let binaries = db.get_all_binaries()?;
+    let binaries = {
+        let _timer = Timer::start("db query");
+        db.get_all_binaries()?
+    };
 
     if binaries.is_empty() {
-        if json {
-            println!("[]");
+        if !format.is_table() {
+            emit(&Vec::<()>::new(), format)?;
         } else {
             println!();
             println!("  {} No binaries found.", style("●").yellow());
@@ -35,45 +83,54 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("");
-        if config.should_ignore_binary(binary_name) {
+        if config.should_ignore_binary(binary_name, b.source.as_deref()) {
             continue;
         }
-        let source = b.source.clone().unwrap_or_else(|| "other".to_string());
-        let pkg = b.package_name.clone().unwrap_or_else(|| {
-            std::path::Path::new(&b.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
+        let key = b.package_key();
 
         if let Some(ref sf) = source_filter {
-            if &source != sf {
+            if &key.0 != sf {
                 continue;
             }
         }
 
-        groups.entry((source, pkg)).or_default().push(b);
+        groups.entry(key).or_default().push(b);
     }
 
     if dust {
         groups.retain(|_, bins| bins.iter().all(|b| b.count == 0));
     }
 
-    #[derive(Serialize)]
-    struct SizeEntry {
-        source: String,
-        package_name: String,
-        size_bytes: Option<u64>,
-        size_display: String,
-        binary_count: usize,
-        status: String,
-    }
+    let mut size_config = config.size.clone();
+    size_config.apparent_size |= apparent;
 
-    // Batch-compute sizes: collect all install roots, run one `du -sk` call
+    // Batch-compute sizes: collect all install roots, run one `du -sk` call.
+    // Structured output in apparent mode also measures disk usage so both are exported.
     let spinner = Spinner::new();
     spinner.message("Calculating sizes");
-    let size_map = batch_dir_sizes(&groups);
+    let size_map = batch_dir_sizes(&db, &groups, &size_config);
+    let disk_map = if size_config.apparent_size && !format.is_table() {
+        let disk_config = config::SizeConfig {
+            apparent_size: false,
+            ..size_config.clone()
+        };
+        Some(batch_dir_sizes(&db, &groups, &disk_config))
+    } else {
+        None
+    };
+    // Every tracked binary's package, so libraries shared with filtered-out
+    // packages don't count as exclusive
+    let mut exclusive = if include_deps {
+        spinner.message("Resolving library dependencies");
+        deps::update_dep_cache(&db, &binaries, false, None)?;
+        let package_of: HashMap<String, (String, String)> = binaries
+            .iter()
+            .map(|b| (b.path.clone(), b.package_key()))
+            .collect();
+        deps::exclusive_lib_packages(&db, &package_of)?
+    } else {
+        HashMap::new()
+    };
     spinner.finish();
 
     let mut entries: Vec<SizeEntry> = Vec::new();
@@ -92,20 +149,56 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
             "dusty"
         };
 
+        let (disk_bytes, apparent_bytes) = if size_config.apparent_size {
+            let disk = disk_map
+                .as_ref()
+                .and_then(|m| m.get(&key).copied().flatten());
+            (disk, size)
+        } else {
+            (size, None)
+        };
+
+        let exclusive_deps = include_deps.then(|| exclusive.remove(&key).unwrap_or_default());
+        let (deps_bytes, with_deps_bytes) = deps_totals(size, exclusive_deps.as_deref());
+
         entries.push(SizeEntry {
             source: source.clone(),
             package_name: pkg.clone(),
             size_bytes: size,
             size_display: size.map(format_bytes).unwrap_or_else(|| "?".to_string()),
+            disk_bytes,
+            apparent_bytes,
+            exclusive_deps,
+            deps_bytes,
+            with_deps_bytes,
             binary_count: bins.len(),
             status: status.to_string(),
+            uses: bins.iter().map(|b| b.count).sum(),
+            last_seen: bins.iter().filter_map(|b| b.last_seen).max(),
         });
     }
 
-    entries.sort_by(|a, b| b.size_bytes.unwrap_or(0).cmp(&a.size_bytes.unwrap_or(0)));
+    // Same keys and directions as `dusty report --sort`; ties fall back to name
+    entries.sort_by(|a, b| {
+        let by_key = match sort {
+            SortKey::Size | SortKey::Recency | SortKey::Rate => {
+                let size = |e: &SizeEntry| e.with_deps_bytes.or(e.size_bytes).unwrap_or(0);
+                size(b).cmp(&size(a))
+            }
+            SortKey::Uses => b.uses.cmp(&a.uses),
+            SortKey::Name => Ordering::Equal,
+            SortKey::LastUsed => b.last_seen.cmp(&a.last_seen),
+            SortKey::Bins => b.binary_count.cmp(&a.binary_count),
+            SortKey::Source => a.source.cmp(&b.source),
+        };
+        by_key.then_with(|| a.package_name.cmp(&b.package_name))
+    });
+    if reverse {
+        entries.reverse();
+    }
 
-    if json {
-        println!("{}", serde_json::to_string_pretty(&entries)?);
+    if !format.is_table() {
+        emit(&entries, format)?;
         return Ok(());
     }
 
@@ -115,12 +208,20 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
     use std::fmt::Write;
 
     writeln!(out).unwrap();
+    let deps_header = if include_deps {
+        format!(" {:>10}", style("With deps").bold().underlined())
+    } else {
+        String::new()
+    };
     writeln!(
         out,
-        "  {:<30} {:<12} {:>10} {:>6} {:>8}",
+        "  {:<30} {:<12} {:>10}{} {:>6} {:>8}",
         style("Package").bold().underlined(),
         style("Source").bold().underlined(),
-        style("Size").bold().underlined(),
+        style(if include_deps { "Own" } else { "Size" })
+            .bold()
+            .underlined(),
+        deps_header,
         style("Bins").bold().underlined(),
         style("Status").bold().underlined(),
     )
@@ -133,12 +234,14 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
             _ => style(&entry.status).green().to_string(),
         };
 
+        let with_deps = with_deps_cell(entry);
         writeln!(
             out,
-            "  {:<30} {:<12} {:>10} {:>6} {:>8}",
+            "  {:<30} {:<12} {:>10}{} {:>6} {:>8}",
             truncate_str(&entry.package_name, 30),
             &entry.source,
             &entry.size_display,
+            with_deps,
             entry.binary_count,
             status_styled,
         )
@@ -170,6 +273,53 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
         )
         .unwrap();
     }
+    if include_deps {
+        let dusty_deps: u64 = entries
+            .iter()
+            .filter(|e| e.status == "dusty")
+            .filter_map(|e| e.deps_bytes)
+            .sum();
+        if dusty_deps > 0 {
+            writeln!(
+                out,
+                "  {} {} more in libraries only dusty packages link",
+                style("●").red(),
+                style(format_bytes(dusty_deps)).red().bold(),
+            )
+            .unwrap();
+        }
+        // The cache was just brought up to date, so empty means nothing to count
+        if db.get_all_dylib_deps()?.is_empty() {
+            writeln!(
+                out,
+                "  {} No binary could be analyzed for linked libraries",
+                style("◦").dim(),
+            )
+            .unwrap();
+        } else if db.get_all_lib_packages()?.is_empty() {
+            writeln!(
+                out,
+                "  {} No linked library belongs to a package dusty can look up",
+                style("◦").dim(),
+            )
+            .unwrap();
+        }
+    }
+    if size_config.apparent_size {
+        writeln!(
+            out,
+            "  {} Apparent (logical) sizes; sparse or compressed files may use less disk",
+            style("◦").dim(),
+        )
+        .unwrap();
+    }
+    #[cfg(target_os = "macos")]
+    writeln!(
+        out,
+        "  {} APFS clones share blocks, so freed space can be less than the total shown",
+        style("◦").dim(),
+    )
+    .unwrap();
     writeln!(out).unwrap();
 
     if use_pager {
@@ -183,8 +333,32 @@ pub fn cmd_size(dust: bool, source_filter: Option<String>, json: bool) -> Result
 
 /// Batch-compute sizes for all package groups using a single `du -sk` call.
 /// Returns a map from (source, package_name) to Option<u64> bytes.
-fn batch_dir_sizes(
+/// Homebrew Cellar sizes go through the shared `package_sizes` cache (the same
+/// one `dusty deps` uses), so only stale or unmeasured Cellars are passed to `du`.
+/// `deps_bytes` and `with_deps_bytes` of a package of `size` whose exclusive
+/// libraries are `libs`; both `None` without --include-deps
+fn deps_totals(
+    size: Option<u64>,
+    libs: Option<&[deps::ExclusiveLib]>,
+) -> (Option<u64>, Option<u64>) {
+    let deps_bytes = libs.map(|libs| libs.iter().filter_map(|l| l.size_bytes).sum::<u64>());
+    (deps_bytes, deps_bytes.map(|d| size.unwrap_or(0) + d))
+}
+
+/// The "With deps" cell: empty without --include-deps, `-` when the package
+/// has no exclusive library size to add
+fn with_deps_cell(entry: &SizeEntry) -> String {
+    match (entry.with_deps_bytes, entry.deps_bytes) {
+        (Some(total), Some(deps)) if deps > 0 => format!(" {:>10}", format_bytes(total)),
+        (Some(_), _) => format!(" {:>10}", style("-").dim()),
+        _ => String::new(),
+    }
+}
+
+pub(super) fn batch_dir_sizes(
+    db: &Database,
     groups: &HashMap<(String, String), Vec<&storage::BinaryRecord>>,
+    size_config: &config::SizeConfig,
 ) -> HashMap<(String, String), Option<u64>> {
     let mut result: HashMap<(String, String), Option<u64>> = HashMap::new();
 
@@ -198,6 +372,9 @@ fn batch_dir_sizes(
     // Collect du targets: either Cellar paths (for homebrew) or install roots (for /opt/*)
     let mut du_path_to_key: HashMap<String, (String, String)> = HashMap::new();
     let mut binary_sum_keys: Vec<(String, String)> = Vec::new();
+    // The cache holds allocated sizes, matching `dusty deps`
+    let use_cache = !size_config.apparent_size;
+    let mut cellar_paths: Vec<String> = Vec::new();
 
     for ((source, pkg), bins) in groups {
         let key = (source.clone(), pkg.clone());
@@ -208,8 +385,16 @@ fn batch_dir_sizes(
             for prefix in &cellar_prefixes {
                 let cellar_path = format!("{}/{}", prefix, pkg);
                 if std::path::Path::new(&cellar_path).exists() {
-                    du_path_to_key.insert(cellar_path, key.clone());
                     found_cellar = true;
+                    if use_cache
+                        && let Ok(Some(size)) =
+                            db.get_package_size(source, pkg, defaults::PACKAGE_SIZE_TTL_SECS)
+                    {
+                        result.insert(key.clone(), Some(size));
+                        break;
+                    }
+                    cellar_paths.push(cellar_path.clone());
+                    du_path_to_key.insert(cellar_path, key.clone());
                     break;
                 }
             }
@@ -237,36 +422,37 @@ fn batch_dir_sizes(
     // Single `du -sk` call for all collected paths
     if !du_path_to_key.is_empty() {
         let du_paths: Vec<&str> = du_path_to_key.keys().map(|s| s.as_str()).collect();
-        let output = Command::new("du").arg("-sk").args(&du_paths).output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                let mut parts = line.split_whitespace();
-                if let (Some(size_str), Some(path)) = (parts.next(), parts.next()) {
-                    if let Ok(kb) = size_str.parse::<u64>() {
-                        if let Some(key) = du_path_to_key.get(path) {
-                            result.insert(key.clone(), Some(kb * 1024));
-                        }
-                    }
-                }
-            }
+        let sizes = du_sizes(&du_paths, size_config);
+
+        // Paths du didn't report (or timed out on) stay unknown
+        for (path, key) in &du_path_to_key {
+            result.insert(key.clone(), sizes.get(path).copied());
         }
 
-        // Fill in any paths that du didn't report
-        for key in du_path_to_key.values() {
-            result.entry(key.clone()).or_insert(None);
+        if use_cache {
+            for path in &cellar_paths {
+                if let (Some(&size), Some((source, pkg))) =
+                    (sizes.get(path), du_path_to_key.get(path))
+                {
+                    let _ = db.store_package_size(source, pkg, size);
+                }
+            }
         }
     }
 
     // For remaining packages, sum individual binary file sizes
+    // (allocated blocks unless apparent size was asked for, matching `du`)
     for key in &binary_sum_keys {
         if let Some(bins) = groups.get(key) {
             let mut total = 0u64;
             let mut found_any = false;
             for b in bins {
                 if let Ok(meta) = std::fs::metadata(&b.path) {
-                    total += meta.len();
+                    total += if size_config.apparent_size {
+                        meta.len()
+                    } else {
+                        meta.blocks() * 512
+                    };
                     found_any = true;
                 }
             }
@@ -276,3 +462,66 @@ fn batch_dir_sizes(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lib(name: &str, size_bytes: Option<u64>) -> deps::ExclusiveLib {
+        deps::ExclusiveLib {
+            manager: "apt".to_string(),
+            package_name: name.to_string(),
+            size_bytes,
+        }
+    }
+
+    fn entry(size_bytes: Option<u64>, libs: Option<&[deps::ExclusiveLib]>) -> SizeEntry {
+        let (deps_bytes, with_deps_bytes) = deps_totals(size_bytes, libs);
+        SizeEntry {
+            source: "apt".to_string(),
+            package_name: "pkg".to_string(),
+            size_bytes,
+            size_display: String::new(),
+            disk_bytes: None,
+            apparent_bytes: None,
+            exclusive_deps: None,
+            deps_bytes,
+            with_deps_bytes,
+            binary_count: 1,
+            status: "dusty".to_string(),
+            uses: 0,
+            last_seen: None,
+        }
+    }
+
+    #[test]
+    fn test_deps_totals() {
+        let libs = [lib("libfoo", Some(3000)), lib("libbar", None)];
+        assert_eq!(
+            deps_totals(Some(1000), Some(&libs)),
+            (Some(3000), Some(4000))
+        );
+        // An unmeasured package still gets its libraries' size
+        assert_eq!(deps_totals(None, Some(&libs)), (Some(3000), Some(3000)));
+        assert_eq!(deps_totals(Some(1000), Some(&[])), (Some(0), Some(1000)));
+        assert_eq!(deps_totals(Some(1000), None), (None, None));
+    }
+
+    #[test]
+    fn test_with_deps_cell() {
+        let cell = |e: &SizeEntry| console::strip_ansi_codes(&with_deps_cell(e)).to_string();
+        let libs = [lib("libfoo", Some(2048))];
+        assert_eq!(
+            cell(&entry(Some(1024), Some(&libs))),
+            format!(" {:>10}", format_bytes(3072))
+        );
+        assert_eq!(cell(&entry(Some(1024), Some(&[]))), format!(" {:>10}", "-"));
+        // Libraries of unknown size add nothing to show
+        let unmeasured = [lib("libbar", None)];
+        assert_eq!(
+            cell(&entry(Some(1024), Some(&unmeasured))),
+            format!(" {:>10}", "-")
+        );
+        assert_eq!(cell(&entry(Some(1024), None)), "");
+    }
+}
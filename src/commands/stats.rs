@@ -1,33 +1,222 @@
 use anyhow::Result;
 use console::style;
-use serde::Serialize;
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
+use crate::platform::boot_time;
 use crate::storage::Database;
-use crate::utils::sync_binaries;
+use crate::ui::{OutputFormat, Timer, emit};
+use crate::utils::{format_timestamp, sync_binaries};
 
-#[derive(Serialize)]
-struct StatsJson {
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub(super) struct StatsJson {
     tracking_days: i64,
     total_packages: usize,
     total_binaries: usize,
     active: usize,
     low: usize,
     dusty: usize,
+    /// Never run but first seen within the grace period (not counted as dusty)
+    #[serde(default)]
+    new: usize,
     by_source: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    delta: Option<StatsDelta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    projection: Option<Projection>,
 }
 
-pub fn cmd_stats(json: bool) -> Result<()> {
+/// Most days of recent history `--projection` takes its rates from
+const PROJECTION_WINDOW_DAYS: i64 = 90;
+/// Less history than this past the horizon gives no meaningful rate
+const PROJECTION_MIN_WINDOW_DAYS: i64 = 7;
+
+/// `--projection`: recent rates of packages going unused and of installs,
+/// extrapolated over `horizon_days`
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Projection {
+    horizon_days: u32,
+    /// Packages never used, or not used in the last `horizon_days`
+    unused_now: usize,
+    /// Days of history the rates are taken from
+    window_days: i64,
+    /// Packages whose last use slipped past `horizon_days` ago during the window
+    went_unused: usize,
+    projected_unused: usize,
+    /// Packages first seen during the window
+    installed: usize,
+    projected_installs: usize,
+}
+
+impl Projection {
+    /// None until tracking has run long enough to see anything go unused
+    fn compute(
+        packages: &HashMap<(String, String), PackageSpan>,
+        now: i64,
+        tracking_since: Option<i64>,
+        horizon_days: u32,
+    ) -> Option<Self> {
+        const DAY: i64 = 24 * 60 * 60;
+        let horizon = horizon_days as i64 * DAY;
+        let tracked_days = (now - tracking_since?) / DAY;
+        let window_days = (tracked_days - horizon_days as i64).min(PROJECTION_WINDOW_DAYS);
+        if window_days < PROJECTION_MIN_WINDOW_DAYS {
+            return None;
+        }
+        let window = window_days * DAY;
+
+        let unused_now = packages
+            .values()
+            .filter(|p| p.last_seen.is_none_or(|ts| ts < now - horizon))
+            .count();
+        let went_unused = packages
+            .values()
+            .filter(|p| {
+                p.last_seen
+                    .is_some_and(|ts| ts < now - horizon && ts >= now - horizon - window)
+            })
+            .count();
+        let installed = packages
+            .values()
+            .filter(|p| !p.predates_tracking && p.first_seen.is_some_and(|ts| ts >= now - window))
+            .count();
+
+        let extrapolate =
+            |n: usize| (n as f64 * horizon_days as f64 / window_days as f64).round() as usize;
+        Some(Self {
+            horizon_days,
+            unused_now,
+            window_days,
+            went_unused,
+            projected_unused: extrapolate(went_unused),
+            installed,
+            projected_installs: extrapolate(installed),
+        })
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "Over the last {} days, {} packages went {}+ days without use. At that rate about {} more \
+             will be unused {} days from now, on top of the {} unused today. {} packages were \
+             installed in the same period, so expect around {} new ones.",
+            self.window_days,
+            self.went_unused,
+            self.horizon_days,
+            self.projected_unused,
+            self.horizon_days,
+            self.unused_now,
+            self.installed,
+            self.projected_installs
+        )
+    }
+}
+
+/// When a package was last used and first seen, across its binaries
+#[derive(Default)]
+struct PackageSpan {
+    last_seen: Option<i64>,
+    first_seen: Option<i64>,
+    /// Some binary was already there when tracking started (no first_seen)
+    predates_tracking: bool,
+}
+
+/// Change relative to a previously saved `dusty stats --json` snapshot
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct StatsDelta {
+    total_packages: i64,
+    total_binaries: i64,
+    active: i64,
+    low: i64,
+    dusty: i64,
+    /// Only sources whose package count changed
+    by_source: BTreeMap<String, i64>,
+}
+
+impl StatsDelta {
+    fn between(prev: &StatsJson, cur: &StatsJson) -> Self {
+        let diff = |a: usize, b: usize| b as i64 - a as i64;
+        let mut by_source = BTreeMap::new();
+        for source in prev.by_source.keys().chain(cur.by_source.keys()) {
+            let before = prev.by_source.get(source).copied().unwrap_or(0);
+            let after = cur.by_source.get(source).copied().unwrap_or(0);
+            if before != after {
+                by_source.insert(source.clone(), diff(before, after));
+            }
+        }
+        Self {
+            total_packages: diff(prev.total_packages, cur.total_packages),
+            total_binaries: diff(prev.total_binaries, cur.total_binaries),
+            active: diff(prev.active, cur.active),
+            low: diff(prev.low, cur.low),
+            dusty: diff(prev.dusty, cur.dusty),
+            by_source,
+        }
+    }
+
+    /// One-line summary, e.g. "freed 12 dusty packages, homebrew down by 8"
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.dusty < 0 {
+            parts.push(format!("freed {} dusty packages", -self.dusty));
+        } else if self.dusty > 0 {
+            parts.push(format!("{} more dusty packages", self.dusty));
+        }
+        let mut sources: Vec<_> = self.by_source.iter().collect();
+        sources.sort_by_key(|(_, d)| std::cmp::Reverse(d.abs()));
+        for (source, d) in sources.into_iter().take(3) {
+            let dir = if *d < 0 { "down" } else { "up" };
+            parts.push(format!("{} {} by {}", source, dir, d.abs()));
+        }
+        if parts.is_empty() {
+            "no change".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+fn load_snapshot(path: &str) -> Result<StatsJson> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("{} is not a `dusty stats --json` snapshot: {}", path, e))
+}
+
+fn signed(n: i64) -> String {
+    if n > 0 {
+        format!("+{}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+pub fn cmd_stats(
+    format: OutputFormat,
+    compare: Option<String>,
+    since_boot: bool,
+    projection: Option<u32>,
+) -> Result<()> {
+    let previous = compare.as_deref().map(load_snapshot).transpose()?;
+    let boot = if since_boot {
+        Some(boot_time().ok_or_else(|| anyhow::anyhow!("Could not determine system boot time"))?)
+    } else {
+        None
+    };
     let db = Database::open()?;
+    let grace_days = crate::config::Config::load()?.report.grace_days;
 
     // Auto-sync binaries
     sync_binaries(&db)?;
 
-    let binaries = db.get_all_binaries()?;
+    let binaries = {
+        let _timer = Timer::start("db query");
+        db.get_all_binaries()?
+    };
     let tracking_since = db.get_tracking_since()?;
+    let now = chrono::Utc::now().timestamp();
 
     let days = if let Some(since) = tracking_since {
-        let now = chrono::Utc::now().timestamp();
         (now - since) / (24 * 60 * 60)
     } else {
         0
@@ -36,18 +225,29 @@ pub fn cmd_stats(json: bool) -> Result<()> {
     let total_binaries = binaries.len();
 
     // Aggregate into packages
-    let mut pkg_map: HashMap<(String, String), (i64, Option<i64>)> = HashMap::new();
+    // (uses, every binary is new)
+    let mut pkg_map: HashMap<(String, String), (i64, bool)> = HashMap::new();
+    let mut spans: HashMap<(String, String), PackageSpan> = HashMap::new();
     for b in &binaries {
-        let pkg = b.package_name.clone().unwrap_or_else(|| {
-            std::path::Path::new(&b.path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
-        let source = b.source.clone().unwrap_or_else(|| "other".to_string());
-        let entry = pkg_map.entry((pkg, source)).or_insert((0, None));
-        entry.0 += b.count;
+        let (source, pkg) = b.package_key();
+        if projection.is_some() {
+            let span = spans.entry((pkg.clone(), source.clone())).or_default();
+            span.last_seen = span.last_seen.max(b.last_seen);
+            match b.first_seen {
+                Some(ts) => span.first_seen = Some(span.first_seen.map_or(ts, |f| f.min(ts))),
+                None => span.predates_tracking = true,
+            }
+        }
+        let entry = pkg_map.entry((pkg, source)).or_insert((0, true));
+        entry.1 &= b.is_new(now, grace_days);
+        // With --since-boot, anything not run since the reboot counts as unused
+        let used_since_boot = match boot {
+            Some(boot) => b.last_seen.is_some_and(|ts| ts >= boot),
+            None => true,
+        };
+        if used_since_boot {
+            entry.0 += b.count;
+        }
     }
 
     let total_packages = pkg_map.len();
@@ -56,7 +256,11 @@ pub fn cmd_stats(json: bool) -> Result<()> {
         .values()
         .filter(|(uses, _)| *uses > 0 && *uses < 5)
         .count();
-    let dusty = pkg_map.values().filter(|(uses, _)| *uses == 0).count();
+    let new = pkg_map
+        .values()
+        .filter(|(uses, all_new)| *uses == 0 && *all_new)
+        .count();
+    let dusty = pkg_map.values().filter(|(uses, _)| *uses == 0).count() - new;
 
     // Count packages by source
     let mut by_source: HashMap<String, usize> = HashMap::new();
@@ -64,19 +268,28 @@ pub fn cmd_stats(json: bool) -> Result<()> {
         *by_source.entry(source.clone()).or_insert(0) += 1;
     }
 
-    if json {
-        let stats = StatsJson {
-            tracking_days: days,
-            total_packages,
-            total_binaries,
-            active,
-            low,
-            dusty,
-            by_source,
-        };
-        println!("{}", serde_json::to_string_pretty(&stats)?);
+    let mut stats = StatsJson {
+        tracking_days: days,
+        total_packages,
+        total_binaries,
+        active,
+        low,
+        dusty,
+        new,
+        by_source,
+        delta: None,
+        projection: projection
+            .and_then(|horizon| Projection::compute(&spans, now, tracking_since, horizon)),
+    };
+    stats.delta = previous
+        .as_ref()
+        .map(|prev| StatsDelta::between(prev, &stats));
+
+    if !format.is_table() {
+        emit(&stats, format)?;
         return Ok(());
     }
+    let by_source = &stats.by_source;
 
     // Pretty output
     println!();
@@ -84,7 +297,12 @@ pub fn cmd_stats(json: bool) -> Result<()> {
     println!("  {}", style("─".repeat(40)).dim());
     println!();
 
-    if days > 0 {
+    if let Some(boot) = boot {
+        println!(
+            "  Usage since boot at {}",
+            style(format_timestamp(boot)).cyan().bold()
+        );
+    } else if days > 0 {
         println!(
             "  Tracking for {} days",
             style(format!("{}", days)).cyan().bold()
@@ -97,24 +315,19 @@ pub fn cmd_stats(json: bool) -> Result<()> {
     // Usage bar
     let bar_width: usize = 30;
     let total = total_packages;
-    let active_width = if total > 0 {
-        active * bar_width / total
-    } else {
-        0
-    };
-    let low_width = if total > 0 {
-        low * bar_width / total
-    } else {
-        0
-    };
+    let active_width = (active * bar_width).checked_div(total).unwrap_or(0);
+    let low_width = (low * bar_width).checked_div(total).unwrap_or(0);
+    let new_width = (new * bar_width).checked_div(total).unwrap_or(0);
     let dusty_width = bar_width
         .saturating_sub(active_width)
-        .saturating_sub(low_width);
+        .saturating_sub(low_width)
+        .saturating_sub(new_width);
 
     let bar = format!(
-        "{}{}{}",
+        "{}{}{}{}",
         style("█".repeat(active_width)).green(),
         style("█".repeat(low_width)).yellow(),
+        style("█".repeat(new_width)).cyan(),
         style("█".repeat(dusty_width)).red()
     );
 
@@ -128,6 +341,14 @@ pub fn cmd_stats(json: bool) -> Result<()> {
 
     println!("  {} {:>5}  active (5+ uses)", style("■").green(), active);
     println!("  {} {:>5}  low (1-4 uses)", style("■").yellow(), low);
+    if new > 0 {
+        println!(
+            "  {} {:>5}  new (never used, first seen < {} days ago)",
+            style("■").cyan(),
+            new,
+            grace_days
+        );
+    }
     println!("  {} {:>5}  dusty (never used)", style("■").red(), dusty);
     println!();
 
@@ -163,5 +384,134 @@ pub fn cmd_stats(json: bool) -> Result<()> {
 
     println!();
 
+    if let (Some(delta), Some(path)) = (&stats.delta, &compare) {
+        print_comparison(delta, path);
+    }
+
+    if let Some(horizon) = projection {
+        println!("  {}", style("Projection").dim());
+        println!("  {}", style("─".repeat(25)).dim());
+        match &stats.projection {
+            Some(p) => print_paragraph(&p.summary()),
+            None => println!(
+                "  Not enough history yet: projecting {} days ahead needs {} days of tracking",
+                horizon,
+                horizon as i64 + PROJECTION_MIN_WINDOW_DAYS
+            ),
+        }
+        println!();
+    }
+
     Ok(())
 }
+
+/// Word-wrap `text` to the terminal (at most 78 columns), indented like the rest
+fn print_paragraph(text: &str) {
+    let width = console::Term::stdout()
+        .size_checked()
+        .map_or(78, |(_, cols)| (cols as usize).saturating_sub(4).min(78))
+        .max(20);
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            println!("  {}", line);
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        println!("  {}", line);
+    }
+}
+
+fn print_comparison(delta: &StatsDelta, path: &str) {
+    let colored = |n: i64| {
+        let text = format!("{:>6}", signed(n));
+        if n < 0 {
+            style(text).green()
+        } else if n > 0 {
+            style(text).red()
+        } else {
+            style(text).dim()
+        }
+    };
+
+    println!("  {} {}", style("Compared to").dim(), style(path).dim());
+    println!("  {}", style("─".repeat(25)).dim());
+    println!("  {:>10}  {}", "packages", colored(delta.total_packages));
+    println!("  {:>10}  {}", "binaries", colored(delta.total_binaries));
+    println!(
+        "  {:>10}  {}",
+        "active",
+        style(format!("{:>6}", signed(delta.active))).dim()
+    );
+    println!(
+        "  {:>10}  {}",
+        "low",
+        style(format!("{:>6}", signed(delta.low))).dim()
+    );
+    println!("  {:>10}  {}", "dusty", colored(delta.dusty));
+    for (source, d) in &delta.by_source {
+        println!("  {:>10}  {}", source, colored(*d));
+    }
+    println!();
+    println!("  {} {}", style("●").cyan(), delta.summary());
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn span(last_seen: Option<i64>, first_seen: Option<i64>) -> PackageSpan {
+        PackageSpan {
+            last_seen,
+            first_seen,
+            predates_tracking: first_seen.is_none(),
+        }
+    }
+
+    #[test]
+    fn test_projection_compute() {
+        let now = 1000 * DAY;
+        let packages: HashMap<(String, String), PackageSpan> = [
+            // Went unused 40 days ago: inside the 90-day window past the horizon
+            ("slipped", span(Some(now - 70 * DAY), None)),
+            // Unused for longer than horizon + window
+            ("ancient", span(Some(now - 200 * DAY), None)),
+            ("never", span(None, None)),
+            ("active", span(Some(now - DAY), Some(now - 10 * DAY))),
+            ("fresh", span(None, Some(now - 5 * DAY))),
+        ]
+        .into_iter()
+        .map(|(name, span)| (("apt".to_string(), name.to_string()), span))
+        .collect();
+
+        // A year of tracking: the window is capped at PROJECTION_WINDOW_DAYS
+        let p = Projection::compute(&packages, now, Some(now - 365 * DAY), 30).unwrap();
+        assert_eq!(p.window_days, PROJECTION_WINDOW_DAYS);
+        assert_eq!(p.unused_now, 4);
+        assert_eq!(p.went_unused, 1);
+        assert_eq!(p.installed, 2);
+        assert_eq!(p.projected_installs, 1);
+
+        // 50 days of tracking leaves a 20-day window past a 30-day horizon
+        let p = Projection::compute(&packages, now, Some(now - 50 * DAY), 30).unwrap();
+        assert_eq!(p.window_days, 20);
+        assert_eq!(p.installed, 2);
+        assert_eq!(p.projected_installs, 3);
+
+        // Less than PROJECTION_MIN_WINDOW_DAYS of history past the horizon
+        let short = 30 + PROJECTION_MIN_WINDOW_DAYS - 1;
+        assert!(Projection::compute(&packages, now, Some(now - short * DAY), 30).is_none());
+        let enough = 30 + PROJECTION_MIN_WINDOW_DAYS;
+        let p = Projection::compute(&packages, now, Some(now - enough * DAY), 30).unwrap();
+        assert_eq!(p.window_days, PROJECTION_MIN_WINDOW_DAYS);
+        assert!(Projection::compute(&packages, now, None, 30).is_none());
+    }
+}
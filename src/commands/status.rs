@@ -1,15 +1,20 @@
 use anyhow::Result;
-use chrono::{DateTime, Local};
+use chrono::Local;
 use console::style;
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::config;
 use crate::platform::{Daemon, DaemonManager};
 use crate::storage::Database;
-use crate::utils::{local_datetime, start_daemon, sync_binaries};
+use crate::ui::{humanize_secs, print_json};
+use crate::utils::{format_date, start_daemon, sync_binaries};
 
-#[derive(Serialize)]
-struct StatusJson {
+/// A daemon whose heartbeat is older than this is treated as wedged
+const HEARTBEAT_STALE_SECS: i64 = 120;
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct StatusJson {
     daemon_running: bool,
     daemon_healthy: bool,
     has_permissions: bool,
@@ -17,6 +22,11 @@ struct StatusJson {
     first_scan_days: i64,
     binaries_tracked: i64,
     dusty_count: i64,
+    monitor_backend: Option<String>,
+    monitor_pid: Option<u32>,
+    /// Seconds since the daemon's event loop last wrote its heartbeat
+    last_heartbeat_secs: Option<i64>,
+    heartbeat_stale: bool,
     db_path: Option<String>,
     config_path: Option<String>,
     log_path: Option<String>,
@@ -31,15 +41,20 @@ pub fn cmd_status(json: bool) -> Result<()> {
     // Auto-start daemon if not running
     let just_started = start_daemon(true)?;
     let running = Daemon::is_daemon_running();
-    let healthy = running && is_daemon_healthy();
+    let heartbeat_age = db
+        .get_heartbeat()?
+        .map(|ts| (Local::now().timestamp() - ts).max(0));
+    let stale = running && !just_started && heartbeat_age.is_some_and(|a| a > HEARTBEAT_STALE_SECS);
+    let healthy = running && !stale && is_daemon_healthy();
     let dusty_count = db.get_dusty_count()?;
     let binary_count = db.get_binary_count()?;
 
+    let monitor_backend = db.get_meta("monitor_backend")?;
+    let monitor_pid: Option<u32> = db.get_meta("monitor_pid")?.and_then(|p| p.parse().ok());
+
     let (first_scan, days) = if let Some(since) = db.get_tracking_since()? {
-        let dt: DateTime<Local> = local_datetime(since);
-        let now = Local::now();
-        let duration = now.signed_duration_since(dt);
-        (Some(dt.format("%Y-%m-%d").to_string()), duration.num_days())
+        let days = (Local::now().timestamp() - since) / 86400;
+        (Some(format_date(since)), days)
     } else {
         (None, 0)
     };
@@ -53,13 +68,17 @@ pub fn cmd_status(json: bool) -> Result<()> {
             first_scan_days: days,
             binaries_tracked: binary_count,
             dusty_count,
+            monitor_backend: monitor_backend.clone(),
+            monitor_pid,
+            last_heartbeat_secs: heartbeat_age,
+            heartbeat_stale: stale,
             db_path: Database::db_path().ok().map(|p| p.display().to_string()),
             config_path: config::Config::config_path()
                 .ok()
                 .map(|p| p.display().to_string()),
             log_path: Some(Daemon::log_hint()),
         };
-        println!("{}", serde_json::to_string_pretty(&status)?);
+        print_json(&status)?;
         return Ok(());
     }
 
@@ -69,7 +88,17 @@ pub fn cmd_status(json: bool) -> Result<()> {
     if just_started {
         println!("  {} Daemon started automatically", style("●").green());
     } else if running {
-        if healthy {
+        if stale {
+            println!(
+                "  {} Daemon is running but not responding (last heartbeat: {}, STALE)",
+                style("●").yellow(),
+                humanize_secs(heartbeat_age.unwrap_or_default())
+            );
+            println!(
+                "    Restart with: {}",
+                style("dusty stop && dusty start").cyan()
+            );
+        } else if healthy {
             println!("  {} Daemon is running", style("●").green());
         } else {
             println!(
@@ -83,6 +112,23 @@ pub fn cmd_status(json: bool) -> Result<()> {
         println!("    Start with: {}", style("dusty start").cyan());
     }
 
+    if running && let Some(ref backend) = monitor_backend {
+        let pid = monitor_pid
+            .map(|p| format!(" (pid {})", p))
+            .unwrap_or_default();
+        println!("  {} Monitoring via: {}{}", style("◦").dim(), backend, pid);
+    }
+    if running
+        && !stale
+        && let Some(age) = heartbeat_age
+    {
+        println!(
+            "  {} Last heartbeat: {}",
+            style("◦").dim(),
+            humanize_secs(age)
+        );
+    }
+
     // Permissions check
     if running && !healthy && !Daemon::check_permissions() {
         println!();
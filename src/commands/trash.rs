@@ -1,18 +1,60 @@
 use anyhow::Result;
-use chrono::{DateTime, Local};
 use console::style;
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::collections::HashMap;
-use std::process::Command;
 
+use crate::config::Config;
 use crate::defaults;
-use crate::storage::Database;
-use crate::ui::{Spinner, format_bytes};
-use crate::utils::local_datetime;
+use crate::storage::{self, Database, TrashRecord};
+use crate::ui::{OutputFormat, Spinner, emit, format_bytes};
+use crate::utils::{du_sizes, format_date, format_timestamp, json_timestamp};
 
-pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
+#[derive(Serialize, JsonSchema)]
+pub(super) struct TrashJson {
+    id: i64,
+    package_name: String,
+    source: String,
+    method: String,
+    original_path: String,
+    trash_path: Option<String>,
+    size_bytes: Option<u64>,
+    deleted_at: String,
+    restore_cmd: Option<String>,
+}
+
+pub fn cmd_trash(
+    drop: Option<String>,
+    empty: bool,
+    verify: bool,
+    gc: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let db = Database::open()?;
     let items = db.list_trash()?;
 
+    if verify {
+        return verify_trash(&db, &items);
+    }
+
+    if gc {
+        let limits = Config::load()?.clean;
+        println!();
+        if limits.trash_max_bytes == 0 && limits.trash_max_days == 0 {
+            println!(
+                "  {} No trash limits set; add {} or {} under {} in the config",
+                style("◦").dim(),
+                style("trash_max_bytes").cyan(),
+                style("trash_max_days").cyan(),
+                style("[clean]").cyan()
+            );
+        } else if gc_trash(&db, &[])? == 0 {
+            println!("  {} Trash is within its limits", style("●").green());
+        }
+        println!();
+        return Ok(());
+    }
+
     // Drop a specific package from trash
     if let Some(ref name) = drop {
         let matches = db.get_trash_by_name(name)?;
@@ -34,7 +76,7 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
                 let path = std::path::Path::new(tp);
                 if path.exists() {
                     println!("  Running: {}", style(format!("rm -rf {}", tp)).cyan());
-                    if std::fs::remove_dir_all(path).is_ok() {
+                    if remove_trashed(path).is_ok() {
                         removed += 1;
                     } else {
                         eprintln!("  {} Failed to remove {}", style("●").red(), tp);
@@ -63,10 +105,7 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
         }
 
         // Delete files for "moved" items
-        let trash_dir = dirs::data_local_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not find local data directory"))?
-            .join("dusty")
-            .join(defaults::TRASH_DIR);
+        let trash_dir = storage::data_dir()?.join(defaults::TRASH_DIR);
 
         println!();
         let mut removed = 0;
@@ -75,7 +114,7 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
                 let path = std::path::Path::new(tp);
                 if path.exists() {
                     println!("  Running: {}", style(format!("rm -rf {}", tp)).cyan());
-                    if std::fs::remove_dir_all(path).is_ok() {
+                    if remove_trashed(path).is_ok() {
                         removed += 1;
                     } else {
                         eprintln!("  {} Failed to remove {}", style("●").red(), tp);
@@ -103,8 +142,8 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
     }
 
     if items.is_empty() {
-        if json {
-            println!("[]");
+        if !format.is_table() {
+            emit(&Vec::<()>::new(), format)?;
         } else {
             println!();
             println!("  {} Trash is empty", style("●").green().bold());
@@ -119,42 +158,26 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
     let sizes = batch_trash_sizes(&items);
     spinner.finish();
 
-    if json {
-        #[derive(serde::Serialize)]
-        struct TrashJson {
-            id: i64,
-            package_name: String,
-            source: String,
-            method: String,
-            original_path: String,
-            trash_path: Option<String>,
-            size_bytes: Option<u64>,
-            deleted_at: String,
-            restore_cmd: Option<String>,
-        }
-
+    if !format.is_table() {
         let rows: Vec<TrashJson> = items
             .iter()
-            .map(|item| {
-                let dt: DateTime<Local> = local_datetime(item.deleted_at);
-                TrashJson {
-                    id: item.id,
-                    package_name: item.package_name.clone(),
-                    source: item.source.clone(),
-                    method: item.method.clone(),
-                    original_path: item.original_path.clone(),
-                    trash_path: item.trash_path.clone(),
-                    size_bytes: item
-                        .trash_path
-                        .as_ref()
-                        .and_then(|tp| sizes.get(tp.as_str()).copied()),
-                    deleted_at: dt.format("%Y-%m-%d %H:%M").to_string(),
-                    restore_cmd: item.restore_cmd.clone(),
-                }
+            .map(|item| TrashJson {
+                id: item.id,
+                package_name: item.package_name.clone(),
+                source: item.source.clone(),
+                method: item.method.clone(),
+                original_path: item.original_path.clone(),
+                trash_path: item.trash_path.clone(),
+                size_bytes: item
+                    .trash_path
+                    .as_ref()
+                    .and_then(|tp| sizes.get(tp.as_str()).copied()),
+                deleted_at: json_timestamp(item.deleted_at),
+                restore_cmd: item.restore_cmd.clone(),
             })
             .collect();
 
-        println!("{}", serde_json::to_string_pretty(&rows)?);
+        emit(&rows, format)?;
         return Ok(());
     }
 
@@ -172,8 +195,7 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
     let mut total_size: u64 = 0;
 
     for item in &items {
-        let dt: DateTime<Local> = local_datetime(item.deleted_at);
-        let date_str = dt.format("%Y-%m-%d %H:%M").to_string();
+        let date_str = format_timestamp(item.deleted_at);
 
         let method_str = match item.method.as_str() {
             "moved" => style("moved to trash".to_string()).yellow(),
@@ -240,33 +262,229 @@ pub fn cmd_trash(drop: Option<String>, empty: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
-/// Compute sizes for moved trash items using `du -sk` per path.
-/// Runs each path individually to handle spaces in paths correctly.
-fn batch_trash_sizes(items: &[crate::storage::TrashRecord]) -> HashMap<String, u64> {
-    let mut result = HashMap::new();
+/// Enforce `[clean] trash_max_bytes` / `trash_max_days`: permanently delete
+/// moved items past the age limit, then the oldest ones until the trash fits
+/// under the size cap. `keep` (what the current clean run trashed) is never
+/// evicted. Prints each eviction and returns how many items were evicted.
+pub(super) fn gc_trash(db: &Database, keep: &[String]) -> Result<usize> {
+    let config = Config::load()?;
+    let limits = &config.clean;
+    if limits.trash_max_bytes == 0 && limits.trash_max_days == 0 {
+        return Ok(0);
+    }
 
-    for item in items {
-        if item.method != "moved" {
-            continue;
+    let items = db.list_trash()?;
+    let moved: Vec<&TrashRecord> = items
+        .iter()
+        .filter(|i| i.method == "moved")
+        .filter(|i| {
+            i.trash_path
+                .as_deref()
+                .is_some_and(|tp| std::path::Path::new(tp).exists())
+        })
+        .collect();
+    let paths: Vec<&str> = moved
+        .iter()
+        .filter_map(|i| i.trash_path.as_deref())
+        .collect();
+    let sizes = du_sizes(&paths, &config.size);
+    let size_of = |item: &TrashRecord| {
+        item.trash_path
+            .as_deref()
+            .and_then(|tp| sizes.get(tp).copied())
+            .unwrap_or(0)
+    };
+
+    // Oldest first; list_trash returns newest first
+    let mut candidates: Vec<&TrashRecord> = moved
+        .iter()
+        .rev()
+        .filter(|i| {
+            !keep
+                .iter()
+                .any(|k| i.trash_path.as_deref() == Some(k.as_str()))
+        })
+        .copied()
+        .collect();
+    let mut total: u64 = moved.iter().map(|i| size_of(i)).sum();
+    let now = chrono::Utc::now().timestamp();
+    let max_age = limits.trash_max_days as i64 * 24 * 60 * 60;
+
+    let mut evicted = 0;
+    while let Some(&oldest) = candidates.first() {
+        let too_old = limits.trash_max_days > 0 && now - oldest.deleted_at > max_age;
+        let too_big = limits.trash_max_bytes > 0 && total > limits.trash_max_bytes;
+        if !too_old && !too_big {
+            break;
         }
-        let Some(ref tp) = item.trash_path else {
-            continue;
-        };
-        if !std::path::Path::new(tp).exists() {
+        candidates.remove(0);
+
+        let path = std::path::Path::new(oldest.trash_path.as_deref().unwrap_or_default());
+        if let Err(e) = remove_trashed(path) {
+            eprintln!(
+                "  {} Failed to evict {}: {}",
+                style("●").red(),
+                path.display(),
+                e
+            );
             continue;
         }
+        db.delete_trash(oldest.id)?;
+        let size = size_of(oldest);
+        total = total.saturating_sub(size);
+        evicted += 1;
+        println!(
+            "  {} Evicted {} from trash ({}, trashed {}; {})",
+            style("◦").dim(),
+            style(&oldest.package_name).bold(),
+            format_bytes(size),
+            format_date(oldest.deleted_at),
+            if too_old {
+                format!("older than {} days", limits.trash_max_days)
+            } else {
+                format!("over the {} cap", format_bytes(limits.trash_max_bytes))
+            }
+        );
+    }
+    Ok(evicted)
+}
+
+/// Check every receipt against the filesystem: moved items whose trash path is
+/// gone can't be restored, and uninstalled items need their package manager.
+fn verify_trash(db: &Database, items: &[TrashRecord]) -> Result<()> {
+    use dialoguer::{Confirm, theme::ColorfulTheme};
+
+    let mut dangling: Vec<&TrashRecord> = Vec::new();
+    let mut no_manager: Vec<(&TrashRecord, &str)> = Vec::new();
 
-        // Run du -sk on a single path — no whitespace parsing issues
-        let output = Command::new("du").args(["-sk", tp]).output();
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(size_str) = stdout.split_whitespace().next() {
-                if let Ok(kb) = size_str.parse::<u64>() {
-                    result.insert(tp.clone(), kb * 1024);
+    for item in items {
+        match item.method.as_str() {
+            "moved" => {
+                let exists = item
+                    .trash_path
+                    .as_deref()
+                    .is_some_and(|tp| std::path::Path::new(tp).exists());
+                if !exists {
+                    dangling.push(item);
+                }
+            }
+            "package_manager" => {
+                if let Some(program) = item
+                    .restore_cmd
+                    .as_deref()
+                    .and_then(|cmd| cmd.split_whitespace().next())
+                    && !on_path(program)
+                {
+                    no_manager.push((item, program));
                 }
             }
+            _ => {}
         }
     }
 
-    result
+    println!();
+    if dangling.is_empty() && no_manager.is_empty() {
+        println!(
+            "  {} All {} trash receipts check out",
+            style("●").green().bold(),
+            items.len()
+        );
+        println!();
+        return Ok(());
+    }
+
+    for item in &dangling {
+        println!(
+            "  {} {:<25} {}",
+            style("✗").red(),
+            style(&item.package_name).bold(),
+            style(format!(
+                "trashed files missing: {}",
+                item.trash_path.as_deref().unwrap_or("(no path recorded)")
+            ))
+            .dim()
+        );
+    }
+    for (item, program) in &no_manager {
+        println!(
+            "  {} {:<25} {}",
+            style("!").yellow(),
+            style(&item.package_name).bold(),
+            style(format!(
+                "{} is no longer on PATH, restore will fail",
+                program
+            ))
+            .dim()
+        );
+    }
+    println!();
+
+    if dangling.is_empty() {
+        return Ok(());
+    }
+    if !console::Term::stderr().is_term() {
+        println!(
+            "  {} Run {} in a terminal to purge dangling receipts",
+            style("◦").dim(),
+            style("dusty trash --verify").cyan()
+        );
+        println!();
+        return Ok(());
+    }
+
+    let confirm = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Purge {} receipt(s) whose trashed files are gone?",
+            dangling.len()
+        ))
+        .default(false)
+        .interact()?;
+    if !confirm {
+        println!("  {} Cancelled", style("◦").dim());
+        println!();
+        return Ok(());
+    }
+
+    for item in &dangling {
+        db.delete_trash(item.id)?;
+    }
+    println!(
+        "  {} Purged {} dangling receipt(s)",
+        style("●").green(),
+        dangling.len()
+    );
+    println!();
+    Ok(())
+}
+
+/// Whether `program` resolves to a file, either directly or via $PATH
+fn on_path(program: &str) -> bool {
+    if program.contains('/') {
+        return std::path::Path::new(program).exists();
+    }
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Compute sizes for moved trash items with a single (time-limited) `du` call.
+fn batch_trash_sizes(items: &[TrashRecord]) -> HashMap<String, u64> {
+    let size_config = Config::load().map(|c| c.size).unwrap_or_default();
+
+    let paths: Vec<&str> = items
+        .iter()
+        .filter(|item| item.method == "moved")
+        .filter_map(|item| item.trash_path.as_deref())
+        .filter(|tp| std::path::Path::new(tp).exists())
+        .collect();
+
+    du_sizes(&paths, &size_config)
+}
+
+/// Delete a trashed entry, which is a directory (install root) or a single file
+fn remove_trashed(path: &std::path::Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
 }
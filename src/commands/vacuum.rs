@@ -0,0 +1,38 @@
+use anyhow::Result;
+use console::style;
+use std::path::Path;
+
+use crate::storage::Database;
+use crate::ui::format_bytes;
+
+pub fn cmd_vacuum() -> Result<()> {
+    let db_path = Database::db_path()?;
+    let db = Database::open()?;
+
+    let before = on_disk_size(&db_path);
+    db.vacuum()?;
+    let after = on_disk_size(&db_path);
+
+    println!();
+    println!(
+        "  {} Vacuumed {}: {} → {} (reclaimed {})",
+        style("●").green(),
+        db_path.display(),
+        format_bytes(before),
+        format_bytes(after),
+        style(format_bytes(before.saturating_sub(after))).bold()
+    );
+    println!();
+    Ok(())
+}
+
+/// Size of the database file plus its `-wal` sidecar, if any
+fn on_disk_size(db_path: &Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path, Path::new(&wal)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
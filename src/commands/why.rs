@@ -1,17 +1,108 @@
 use anyhow::Result;
 use console::style;
+use schemars::JsonSchema;
 use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
 use crate::config;
-use crate::storage::Database;
-use crate::ui::shorten_path;
-use crate::utils::{detect_install_roots, local_datetime, sync_binaries};
+use crate::deps;
+use crate::package;
+use crate::storage::{BinaryRecord, Database};
+use crate::ui::{Spinner, Timer, print_json, shorten_path};
+use crate::utils::{
+    detect_install_roots, format_timestamp, json_timestamp, sync_binaries, usage_status,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct WhyJson {
+    name: String,
+    matches: Vec<WhyMatch>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct WhyMatch {
+    path: String,
+    source: Option<String>,
+    package_name: Option<String>,
+    count: i64,
+    last_used: Option<String>,
+    first_seen: Option<String>,
+    install_root: Option<String>,
+    /// Where the PATH entry really points (symlink or wrapper script target)
+    target: Option<String>,
+    /// App bundle or install directory owning the target
+    app: Option<String>,
+    siblings: Vec<String>,
+    sibling_count: usize,
+    uninstall_cmd: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct PkgJson {
+    package_name: String,
+    source: String,
+    binaries: usize,
+    used_binaries: usize,
+    total_uses: i64,
+    last_used: Option<String>,
+    install_root: Option<String>,
+    uninstall_cmd: Option<String>,
+    top_binaries: Vec<BinEntry>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct BinEntry {
+    name: String,
+    uses: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub(super) struct LibJson {
+    name: String,
+    libraries: Vec<LibMatch>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LibMatch {
+    lib_path: String,
+    manager: Option<String>,
+    package_name: Option<String>,
+    users: Vec<LibUser>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LibUser {
+    path: String,
+    status: &'static str,
+    source: Option<String>,
+    package_name: Option<String>,
+    count: i64,
+    last_used: Option<String>,
+    install_root: Option<String>,
+    uninstall_cmd: Option<String>,
+}
+
+/// The shapes `why --json` prints: a binary lookup, a package lookup, or `--lib`
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+pub(super) enum WhyOutput {
+    Binary(WhyJson),
+    Package(PkgJson),
+    Library(LibJson),
+}
 
-pub fn cmd_why(name: String, json: bool) -> Result<()> {
+pub fn cmd_why(name: String, lib: bool, json: bool) -> Result<()> {
     let db = Database::open()?;
     let config = config::Config::load()?;
     sync_binaries(&db)?;
 
+    if lib {
+        return show_lib_users(&db, &config, &name, json);
+    }
+
     let binaries = db.get_all_binaries()?;
 
     // Try matching by binary name first, then fall back to package name
@@ -35,6 +126,19 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
         false
     };
 
+    // Last resort: the app or bundle a PATH entry resolves into (e.g. "Visual Studio Code")
+    if matches.is_empty() {
+        let wanted = normalize_app_name(&name);
+        matches = binaries
+            .iter()
+            .filter(|b| {
+                resolve_target(&b.path)
+                    .and_then(|t| owning_app(&b.path, &t))
+                    .is_some_and(|app| normalize_app_name(&app) == wanted)
+            })
+            .collect();
+    }
+
     if matches.is_empty() {
         if json {
             #[derive(Serialize)]
@@ -42,13 +146,10 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
                 name: String,
                 matches: Vec<()>,
             }
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&Empty {
-                    name: name.clone(),
-                    matches: vec![],
-                })?
-            );
+            print_json(&Empty {
+                name: name.clone(),
+                matches: vec![],
+            })?;
         } else {
             println!();
             println!(
@@ -67,30 +168,20 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
     }
 
     // Binary-level matches: show each match with its package context
-    #[derive(Serialize)]
-    struct WhyJson {
-        name: String,
-        matches: Vec<WhyMatch>,
-    }
-
-    #[derive(Serialize)]
-    struct WhyMatch {
-        path: String,
-        source: Option<String>,
-        package_name: Option<String>,
-        count: i64,
-        last_used: Option<String>,
-        first_seen: Option<String>,
-        install_root: Option<String>,
-        siblings: Vec<String>,
-        sibling_count: usize,
-        uninstall_cmd: Option<String>,
-    }
 
     let mut why_matches: Vec<WhyMatch> = Vec::new();
 
     for m in &matches {
-        let install_root = detect_install_roots(&[m.path.as_str()]).into_iter().next();
+        let target = resolve_target(&m.path);
+        let app = target.as_deref().and_then(|t| owning_app(&m.path, t));
+        let install_root = detect_install_roots(&[m.path.as_str()])
+            .into_iter()
+            .next()
+            .or_else(|| {
+                target
+                    .as_deref()
+                    .and_then(|t| detect_install_roots(&[t]).into_iter().next())
+            });
 
         let siblings: Vec<String> = if let (Some(src), Some(pkg)) = (&m.source, &m.package_name) {
             binaries
@@ -121,15 +212,17 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
             .map(|cmd| {
                 let pkg = m.package_name.as_deref().unwrap_or(&name);
                 format!("{} {}", cmd, pkg)
-            });
-
-        let last_used = m
-            .last_seen
-            .map(|ts| local_datetime(ts).format("%Y-%m-%d %H:%M").to_string());
+            })
+            // `go install` has no uninstaller; the binary is the whole install
+            .or_else(|| package::is_go_install(&m.path).then(|| format!("rm {}", m.path)));
 
-        let first_seen = m
-            .first_seen
-            .map(|ts| local_datetime(ts).format("%Y-%m-%d %H:%M").to_string());
+        let stamp = if json {
+            json_timestamp
+        } else {
+            format_timestamp
+        };
+        let last_used = m.last_seen.map(stamp);
+        let first_seen = m.first_seen.map(stamp);
 
         why_matches.push(WhyMatch {
             path: m.path.clone(),
@@ -139,6 +232,8 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
             last_used,
             first_seen,
             install_root,
+            target,
+            app,
             siblings,
             sibling_count,
             uninstall_cmd,
@@ -146,13 +241,10 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
     }
 
     if json {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&WhyJson {
-                name: name.clone(),
-                matches: why_matches,
-            })?
-        );
+        print_json(&WhyJson {
+            name: name.clone(),
+            matches: why_matches,
+        })?;
         return Ok(());
     }
 
@@ -167,6 +259,12 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
         if let Some(ref pkg) = wm.package_name {
             println!("    {}  {}", style("Package:").dim(), pkg);
         }
+        if let Some(ref target) = wm.target {
+            println!("    {}  {}", style("Target:").dim(), shorten_path(target));
+        }
+        if let Some(ref app) = wm.app {
+            println!("    {}  {}", style("App:").dim(), app);
+        }
         if let Some(ref root) = wm.install_root {
             println!("    {}  {}", style("Root:").dim(), root);
         }
@@ -205,6 +303,67 @@ pub fn cmd_why(name: String, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Where a PATH entry really lives: its symlink target, or the program a
+/// wrapper script execs. None when the entry is the binary itself.
+fn resolve_target(path: &str) -> Option<String> {
+    let canonical = std::fs::canonicalize(path).ok()?;
+    let resolved = wrapper_target(&canonical).unwrap_or(canonical);
+    let resolved = resolved.to_str()?.to_string();
+    (resolved != path).then_some(resolved)
+}
+
+/// Absolute path a small shell wrapper `exec`s, if it points at an existing file
+fn wrapper_target(script: &Path) -> Option<PathBuf> {
+    use std::io::Read;
+
+    let mut head = String::new();
+    std::fs::File::open(script)
+        .ok()?
+        .take(8192)
+        .read_to_string(&mut head)
+        .ok()?;
+    if !head.starts_with("#!") {
+        return None;
+    }
+    head.lines()
+        .filter_map(|line| line.trim_start().strip_prefix("exec "))
+        .find_map(|rest| {
+            let rest = rest.trim_start();
+            let path = match rest.strip_prefix('"') {
+                Some(quoted) => quoted.split('"').next()?,
+                None => rest.split_whitespace().next()?,
+            };
+            let path = Path::new(path);
+            (path.is_absolute() && path.is_file()).then(|| path.to_path_buf())
+        })
+}
+
+/// The app bundle ("Visual Studio Code" for `.../Visual Studio Code.app/...`)
+/// or, outside bundles, the install directory the target lives in when that
+/// differs from the PATH entry's own
+fn owning_app(path: &str, target: &str) -> Option<String> {
+    let bundle = Path::new(target)
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .find_map(|c| c.strip_suffix(".app"));
+    if let Some(app) = bundle {
+        return Some(app.to_string());
+    }
+    let root = detect_install_roots(&[target]).into_iter().next()?;
+    if detect_install_roots(&[path]).first() == Some(&root) {
+        return None;
+    }
+    Some(Path::new(&root).file_name()?.to_str()?.to_string())
+}
+
+/// Compare app names loosely: "Visual Studio Code" == "visual-studio-code"
+fn normalize_app_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Show a package-level summary when the user looked up a package name
 fn show_package_summary(
     name: &str,
@@ -228,31 +387,22 @@ fn show_package_summary(
 
     let uninstall_cmd = config
         .get_uninstall_cmd(source)
-        .map(|cmd| format!("{} {}", cmd, name));
+        .map(|cmd| format!("{} {}", cmd, name))
+        .or_else(|| {
+            matches
+                .iter()
+                .all(|b| package::is_go_install(&b.path))
+                .then(|| {
+                    let paths: Vec<&str> = matches.iter().map(|b| b.path.as_str()).collect();
+                    format!("rm {}", paths.join(" "))
+                })
+        });
 
     // Top used binaries
     let mut by_use: Vec<_> = matches.iter().collect();
-    by_use.sort_by(|a, b| b.count.cmp(&a.count));
+    by_use.sort_by_key(|b| std::cmp::Reverse(b.count));
 
     if json {
-        #[derive(Serialize)]
-        struct PkgJson {
-            package_name: String,
-            source: String,
-            binaries: usize,
-            used_binaries: usize,
-            total_uses: i64,
-            last_used: Option<String>,
-            install_root: Option<String>,
-            uninstall_cmd: Option<String>,
-            top_binaries: Vec<BinEntry>,
-        }
-        #[derive(Serialize)]
-        struct BinEntry {
-            name: String,
-            uses: i64,
-        }
-
         let top: Vec<BinEntry> = by_use
             .iter()
             .take(10)
@@ -266,21 +416,17 @@ fn show_package_summary(
             })
             .collect();
 
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&PkgJson {
-                package_name: name.to_string(),
-                source: source.to_string(),
-                binaries: total_bins,
-                used_binaries: used_bins,
-                total_uses,
-                last_used: last_seen
-                    .map(|ts| local_datetime(ts).format("%Y-%m-%d %H:%M").to_string()),
-                install_root,
-                uninstall_cmd,
-                top_binaries: top,
-            })?
-        );
+        print_json(&PkgJson {
+            package_name: name.to_string(),
+            source: source.to_string(),
+            binaries: total_bins,
+            used_binaries: used_bins,
+            total_uses,
+            last_used: last_seen.map(json_timestamp),
+            install_root,
+            uninstall_cmd,
+            top_binaries: top,
+        })?;
         return Ok(());
     }
 
@@ -311,7 +457,7 @@ fn show_package_summary(
         println!(
             "    {}  {}",
             style("Last used:").dim(),
-            local_datetime(ts).format("%Y-%m-%d %H:%M")
+            format_timestamp(ts)
         );
     }
 
@@ -347,3 +493,228 @@ fn show_package_summary(
 
     Ok(())
 }
+
+/// Whether `name` picks out this library: its full path, its file name, the
+/// file name without a version suffix (`libssl.so`, `libssl`, or `ssl` for
+/// `libssl.so.3`), or a framework's name
+fn lib_matches(lib_path: &str, name: &str) -> bool {
+    if lib_path == name {
+        return true;
+    }
+    let Some(file_name) = Path::new(lib_path).file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let stem_of = |prefix: &str| {
+        file_name
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    };
+    stem_of(name) || stem_of(&format!("lib{}", name))
+}
+
+/// `dusty why --lib`: every tracked binary linking the matching libraries,
+/// grouped by usage, so you know what removing them would break
+fn show_lib_users(db: &Database, config: &config::Config, name: &str, json: bool) -> Result<()> {
+    let binaries = db.get_all_binaries()?;
+
+    // Reuses the `dusty deps` cache: only new or changed binaries are analyzed
+    let spinner = RefCell::new(Spinner::new());
+    let timer = Timer::start("dep analysis");
+    deps::update_dep_cache(
+        db,
+        &binaries,
+        false,
+        Some(&|current, total| {
+            spinner
+                .borrow_mut()
+                .update("Analyzing binaries", current + 1, total)
+        }),
+    )?;
+    spinner.into_inner().finish();
+    drop(timer);
+
+    let lib_packages: HashMap<String, (String, String)> = db
+        .get_all_lib_packages()?
+        .into_iter()
+        .map(|(lib, manager, package)| (lib, (manager, package)))
+        .collect();
+    let known_libs: BTreeSet<String> = db
+        .get_all_dylib_deps()?
+        .into_values()
+        .flatten()
+        .chain(lib_packages.keys().cloned())
+        .collect();
+    let by_path: HashMap<&str, &BinaryRecord> =
+        binaries.iter().map(|b| (b.path.as_str(), b)).collect();
+
+    let stamp = if json {
+        json_timestamp
+    } else {
+        format_timestamp
+    };
+    let now = chrono::Utc::now().timestamp();
+    let mut libraries = Vec::new();
+    for lib_path in known_libs {
+        let owner = lib_packages.get(&lib_path);
+        let owned_by_name = owner.is_some_and(|(_, package)| package == name);
+        if !owned_by_name && !lib_matches(&lib_path, name) {
+            continue;
+        }
+
+        // Binaries dropped from tracking can linger in the deps cache
+        let mut users: Vec<LibUser> = db
+            .get_binaries_using_lib(&lib_path)?
+            .into_iter()
+            .filter_map(|path| by_path.get(path.as_str()).copied())
+            .map(|b| LibUser {
+                path: b.path.clone(),
+                status: usage_status(b.count, b.first_seen, now, config.report.grace_days),
+                source: b.source.clone(),
+                package_name: b.package_name.clone(),
+                count: b.count,
+                last_used: b.last_seen.map(stamp),
+                install_root: detect_install_roots(&[b.path.as_str()]).into_iter().next(),
+                uninstall_cmd: b
+                    .source
+                    .as_ref()
+                    .and_then(|s| config.get_uninstall_cmd(s))
+                    .zip(b.package_name.as_ref())
+                    .map(|(cmd, pkg)| format!("{} {}", cmd, pkg)),
+            })
+            .collect();
+        users.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+
+        libraries.push(LibMatch {
+            lib_path,
+            manager: owner.map(|(manager, _)| manager.clone()),
+            package_name: owner.map(|(_, package)| package.clone()),
+            users,
+        });
+    }
+
+    if json {
+        return print_json(&LibJson {
+            name: name.to_string(),
+            libraries,
+        });
+    }
+
+    println!();
+    if libraries.is_empty() {
+        println!(
+            "  {} No tracked binary links a library matching '{}'",
+            style("◦").dim(),
+            style(name).bold()
+        );
+        println!();
+        return Ok(());
+    }
+
+    for lib in &libraries {
+        println!("  {}", style(shorten_path(&lib.lib_path)).bold());
+        println!();
+        if let (Some(manager), Some(package)) = (&lib.manager, &lib.package_name) {
+            println!("    {}  {} ({})", style("Package:").dim(), package, manager);
+        }
+        if lib.users.is_empty() {
+            println!(
+                "    {}  no tracked binaries; safe to remove as far as dusty knows",
+                style("Used by:").dim()
+            );
+            println!();
+            continue;
+        }
+        let count_of = |status: &str| lib.users.iter().filter(|u| u.status == status).count();
+        println!(
+            "    {}  {} {} ({} active, {} low, {} new, {} dusty)",
+            style("Used by:").dim(),
+            lib.users.len(),
+            if lib.users.len() == 1 {
+                "binary"
+            } else {
+                "binaries"
+            },
+            count_of("active"),
+            count_of("low"),
+            count_of("new"),
+            count_of("dusty")
+        );
+
+        for status in ["active", "low", "new", "dusty"] {
+            let group: Vec<&LibUser> = lib.users.iter().filter(|u| u.status == status).collect();
+            if group.is_empty() {
+                continue;
+            }
+            let heading = format!("{} ({})", status, group.len());
+            let heading = match status {
+                "active" => style(heading).green(),
+                "low" => style(heading).yellow(),
+                "new" => style(heading).cyan(),
+                _ => style(heading).red(),
+            };
+            println!();
+            println!("    {}", heading);
+            for user in group {
+                let uses = match &user.last_used {
+                    Some(last) => format!("{} uses, last {}", user.count, last),
+                    None => format!("{} uses", user.count),
+                };
+                println!("      {}  {}", shorten_path(&user.path), style(uses).dim());
+                if let (Some(source), Some(package)) = (&user.source, &user.package_name) {
+                    println!(
+                        "        {}  {} ({})",
+                        style("Package:").dim(),
+                        package,
+                        source
+                    );
+                }
+                if let Some(ref root) = user.install_root {
+                    println!("        {}  {}", style("Root:").dim(), root);
+                }
+                if let Some(ref cmd) = user.uninstall_cmd {
+                    println!(
+                        "        {}  {}",
+                        style("Uninstall:").dim(),
+                        style(cmd).cyan()
+                    );
+                }
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lib_matches() {
+        let ssl = "/usr/lib/x86_64-linux-gnu/libssl.so.3";
+        for (name, expected) in [
+            (ssl, true),
+            ("libssl.so.3", true),
+            ("libssl.so", true),
+            ("libssl", true),
+            ("ssl", true),
+            // Near misses: another library, or a partial name
+            ("libssl3", false),
+            ("libss", false),
+            ("ss", false),
+            ("crypto", false),
+            ("/usr/lib/libssl.so.3", false),
+            ("", false),
+        ] {
+            assert_eq!(lib_matches(ssl, name), expected, "{name}");
+        }
+
+        assert!(!lib_matches("/usr/lib/libssl3.so", "libssl"));
+        assert!(lib_matches("/usr/lib/libssl3.so", "ssl3"));
+
+        let framework = "/System/Library/Frameworks/Foundation.framework/Versions/C/Foundation";
+        assert!(lib_matches(framework, "Foundation"));
+        assert!(!lib_matches(framework, "Found"));
+    }
+}
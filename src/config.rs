@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceDef {
@@ -15,6 +15,34 @@ pub struct SourceDef {
     /// Command that lists installed packages (one per line to stdout)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub list_cmd: Option<String>,
+    /// Show in reports but never offer for cleanup (e.g. nix, system apt)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Whether packages are per-user or part of the OS (inferred from the name if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<SourceScope>,
+    /// Binary name patterns to hide, only for binaries under this source
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_binaries: Vec<String>,
+}
+
+/// Who a source's packages belong to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceScope {
+    /// Per-user installs (cargo, npm, pip, ...)
+    User,
+    /// OS packages (apt, dnf, ...); cleaning them needs extra confirmation
+    System,
+}
+
+impl SourceScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SourceScope::User => "user",
+            SourceScope::System => "system",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,14 +66,241 @@ pub struct ScanConfig {
     /// Binaries to ignore in reports (patterns, e.g. "python*-config")
     #[serde(default)]
     pub ignore_binaries: Vec<String>,
+
+    /// File extensions tracked even without the execute bit (e.g. ".py", ".sh")
+    #[serde(default)]
+    pub executable_extensions: Vec<String>,
+}
+
+impl ScanConfig {
+    /// Whether `path` ends in one of `executable_extensions` (compared
+    /// case-insensitively; the leading dot is optional)
+    pub fn has_executable_extension(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        self.executable_extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeConfig {
+    /// Seconds to wait for `du` before reporting a size as unknown (0 = no limit)
+    #[serde(default = "default_du_timeout_secs")]
+    pub du_timeout_secs: u64,
+
+    /// Report apparent (logical) size instead of disk usage
+    #[serde(default)]
+    pub apparent_size: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Days after which an exec counts half as much in the recency score
+    #[serde(default = "default_half_life_days")]
+    pub half_life_days: f64,
+
+    /// Days of per-day history to keep; older days are rolled up (0 = keep all)
+    #[serde(default = "default_retain_days")]
+    pub retain_days: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanConfig {
+    /// Evict the oldest trashed items once the trash grows past this many bytes (0 = no cap)
+    #[serde(default)]
+    pub trash_max_bytes: u64,
+
+    /// Evict trashed items older than this many days (0 = keep forever)
+    #[serde(default)]
+    pub trash_max_days: u32,
+
+    /// Shell command to run after a clean that removed something
+    /// (`$DUSTY_CLEANED_SOURCES` holds the cleaned sources, comma-separated)
+    #[serde(default)]
+    pub on_success: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    /// Buffer exec events and adapt the flush interval to power state and event rate
+    #[serde(default)]
+    pub power_aware: bool,
+
+    /// Where exec events come from
+    #[serde(default)]
+    pub backend: MonitorBackend,
+
+    /// File the daemon logs to, rotated by size (unset = stdout/stderr)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+
+    /// Roll the log file over once it would grow past this many bytes
+    #[serde(default = "default_log_max_bytes")]
+    pub log_max_bytes: u64,
+
+    /// Rolled-over logs to keep (`dusty.log.1` is the newest)
+    #[serde(default = "default_log_keep")]
+    pub log_keep: u32,
+
+    /// Least severe messages the daemon writes
+    #[serde(default)]
+    pub log_level: LogLevel,
+}
+
+/// `[daemon] log_level`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// Startup, shutdown and the hourly heartbeat
+    #[default]
+    Info,
+    /// Every recorded or skipped exec
+    Debug,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        }
+    }
+}
+
+/// `[daemon] backend`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorBackend {
+    /// fatrace on Linux, eslogger on macOS
+    #[default]
+    Native,
+    /// Newline-delimited paths on the daemon's stdin, from another event producer
+    Stdin,
+}
+
+/// Column names accepted by `[report] columns`
+pub const REPORT_COLUMNS: &[&str] = &[
+    "name",
+    "source",
+    "bins",
+    "uses",
+    "last-used",
+    "size",
+    "score",
+    "rate",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// Columns of the `dusty report` table, in order (empty = built-in layout)
+    #[serde(default)]
+    pub columns: Vec<String>,
+
+    /// Days a never-run binary counts as "new" rather than dusty after dusty first sees it
+    #[serde(default = "default_grace_days")]
+    pub grace_days: u32,
+
+    /// Regex for the version suffix `--merge-versions` strips (unset = `@3.12`, `-20`, `_13` style suffixes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_suffix: Option<String>,
+}
+
+/// One display prefix rewrite, e.g. `/opt/acme/bin/` -> `acme:`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathShorthand {
+    /// Path prefix to match (`~` is expanded to $HOME)
+    pub prefix: String,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    /// Extra path shorthands, tried in order before the built-in ones
+    #[serde(default)]
+    pub path_shorthands: Vec<PathShorthand>,
+
+    /// Timezone for shown timestamps: "local" (default), "utc", or a name like "Europe/Berlin"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// Timezone timestamps are displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayTimezone {
+    Local,
+    Named(chrono_tz::Tz),
+}
+
+impl DisplayTimezone {
+    pub const UTC: Self = Self::Named(chrono_tz::UTC);
+
+    /// "local", "utc" (any case) or an IANA name
+    pub fn parse(name: &str) -> Result<Self> {
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        if name.eq_ignore_ascii_case("utc") {
+            return Ok(Self::UTC);
+        }
+        name.parse::<chrono_tz::Tz>().map(Self::Named).map_err(|_| {
+            anyhow::anyhow!(
+                "unknown timezone '{}' (use local, utc, or an IANA name like Europe/Berlin)",
+                name
+            )
+        })
+    }
+}
+
+impl DisplayConfig {
+    /// The configured `timezone`, local time when unset
+    pub fn timezone(&self) -> Result<DisplayTimezone> {
+        self.timezone
+            .as_deref()
+            .map_or(Ok(DisplayTimezone::Local), DisplayTimezone::parse)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Shared config files whose sources and scan dirs are merged in at load time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
     /// Scanning configuration
     #[serde(default)]
     pub scan: ScanConfig,
 
+    /// Disk size calculation
+    #[serde(default)]
+    pub size: SizeConfig,
+
+    /// Per-day exec history
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// How paths are shown in tables
+    #[serde(default)]
+    pub display: DisplayConfig,
+
+    /// `dusty report` table layout
+    #[serde(default)]
+    pub report: ReportConfig,
+
+    /// `dusty clean` and trash limits
+    #[serde(default)]
+    pub clean: CleanConfig,
+
+    /// Background daemon behavior
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+
     /// Source definitions for categorizing binaries
     #[serde(default = "default_sources")]
     pub sources: Vec<SourceDef>,
@@ -55,6 +310,30 @@ fn default_true() -> bool {
     true
 }
 
+fn default_du_timeout_secs() -> u64 {
+    30
+}
+
+fn default_half_life_days() -> f64 {
+    30.0
+}
+
+fn default_grace_days() -> u32 {
+    7
+}
+
+fn default_retain_days() -> u32 {
+    365
+}
+
+fn default_log_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_keep() -> u32 {
+    3
+}
+
 #[cfg(target_os = "macos")]
 fn default_skip_dirs() -> Vec<String> {
     vec![
@@ -112,14 +391,89 @@ impl Default for ScanConfig {
             skip_dirs: default_skip_dirs(),
             skip_prefixes: default_skip_prefixes(),
             ignore_binaries: vec![],
+            executable_extensions: vec![],
+        }
+    }
+}
+
+impl Default for SizeConfig {
+    fn default() -> Self {
+        Self {
+            du_timeout_secs: default_du_timeout_secs(),
+            apparent_size: false,
+        }
+    }
+}
+
+impl DaemonConfig {
+    /// `log_file` with a leading `~/` expanded
+    pub fn log_path(&self) -> Option<PathBuf> {
+        let file = self.log_file.as_deref()?;
+        Some(match file.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(file),
+        })
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            power_aware: false,
+            backend: MonitorBackend::default(),
+            log_file: None,
+            log_max_bytes: default_log_max_bytes(),
+            log_keep: default_log_keep(),
+            log_level: LogLevel::default(),
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            half_life_days: default_half_life_days(),
+            retain_days: default_retain_days(),
+        }
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            grace_days: default_grace_days(),
+            version_suffix: None,
         }
     }
 }
 
+/// The parts of an included file that get merged into the local config
+#[derive(Debug, Default, Deserialize)]
+struct SharedConfig {
+    #[serde(default)]
+    sources: Vec<SourceDef>,
+    #[serde(default)]
+    scan: SharedScanConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SharedScanConfig {
+    #[serde(default)]
+    extra_dirs: Vec<String>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
+            include: vec![],
             scan: ScanConfig::default(),
+            size: SizeConfig::default(),
+            history: HistoryConfig::default(),
+            display: DisplayConfig::default(),
+            report: ReportConfig::default(),
+            clean: CleanConfig::default(),
+            daemon: DaemonConfig::default(),
             sources: Self::default_sources_list(),
         }
     }
@@ -145,6 +499,9 @@ impl Config {
                         path: pattern,
                         uninstall_cmd: candidate.uninstall_cmd.map(|s| s.to_string()),
                         list_cmd: None,
+                        read_only: false,
+                        scope: None,
+                        ignore_binaries: vec![],
                     });
                     break;
                 }
@@ -158,6 +515,9 @@ impl Config {
                     path: pattern.to_string(),
                     uninstall_cmd: None,
                     list_cmd: None,
+                    read_only: false,
+                    scope: None,
+                    ignore_binaries: vec![],
                 });
             }
         }
@@ -181,6 +541,32 @@ impl Config {
             .and_then(|s| s.list_cmd.clone())
     }
 
+    /// Whether a source is marked `read_only` (reported, but never cleaned).
+    pub fn is_read_only(&self, source_name: &str) -> bool {
+        self.sources
+            .iter()
+            .any(|s| s.name == source_name && s.read_only)
+    }
+
+    /// Scope of a source: the configured `scope`, else inferred from well-known names.
+    pub fn source_scope(&self, source_name: &str) -> Option<SourceScope> {
+        use crate::defaults::{SYSTEM_SOURCES, USER_SOURCES};
+
+        self.sources
+            .iter()
+            .find(|s| s.name == source_name)
+            .and_then(|s| s.scope)
+            .or_else(|| {
+                if SYSTEM_SOURCES.contains(&source_name) {
+                    Some(SourceScope::System)
+                } else if USER_SOURCES.contains(&source_name) {
+                    Some(SourceScope::User)
+                } else {
+                    None
+                }
+            })
+    }
+
     /// Get all sources that have a list_cmd configured.
     pub fn get_sources_with_list_cmd(&self) -> Vec<&SourceDef> {
         self.sources
@@ -205,7 +591,9 @@ impl Config {
 
         if config_path.exists() {
             let content = fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&content)?;
+            let mut config: Config = toml::from_str(&content)?;
+            config.load_includes(config_path.parent().unwrap_or(Path::new(".")))?;
+            config.validate()?;
             Ok(config)
         } else {
             let config = Config::default();
@@ -214,6 +602,72 @@ impl Config {
         }
     }
 
+    /// Merge every `include` file; relative paths resolve against `base_dir`
+    fn load_includes(&mut self, base_dir: &Path) -> Result<()> {
+        for include in self.include.clone() {
+            let path = match include.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                None => base_dir.join(&include),
+            };
+            let content = fs::read_to_string(&path).map_err(|e| {
+                anyhow::anyhow!("failed to read included config {}: {}", path.display(), e)
+            })?;
+            let shared: SharedConfig = toml::from_str(&content).map_err(|e| {
+                anyhow::anyhow!("invalid included config {}: {}", path.display(), e)
+            })?;
+            self.merge_shared(shared);
+        }
+        Ok(())
+    }
+
+    /// Append shared sources and scan dirs; local entries win on name conflicts
+    fn merge_shared(&mut self, shared: SharedConfig) {
+        for source in shared.sources {
+            if !self.sources.iter().any(|s| s.name == source.name) {
+                self.sources.push(source);
+            }
+        }
+        for dir in shared.scan.extra_dirs {
+            if !self.scan.extra_dirs.contains(&dir) {
+                self.scan.extra_dirs.push(dir);
+            }
+        }
+    }
+
+    /// Reject values serde accepts but dusty can't use
+    fn validate(&self) -> Result<()> {
+        if let Some(bad) = self
+            .report
+            .columns
+            .iter()
+            .find(|c| !REPORT_COLUMNS.contains(&c.as_str()))
+        {
+            anyhow::bail!(
+                "unknown column '{}' in [report] columns of {} (expected one of: {})",
+                bad,
+                Self::config_path()?.display(),
+                REPORT_COLUMNS.join(", ")
+            );
+        }
+        self.version_suffix_regex()?;
+        self.display
+            .timezone()
+            .map_err(|e| anyhow::anyhow!("invalid [display] timezone: {}", e))?;
+        Ok(())
+    }
+
+    /// The compiled `[report] version_suffix`, if one is set
+    pub fn version_suffix_regex(&self) -> Result<Option<regex::Regex>> {
+        self.report
+            .version_suffix
+            .as_deref()
+            .map(|p| {
+                regex::Regex::new(p)
+                    .map_err(|e| anyhow::anyhow!("invalid [report] version_suffix: {}", e))
+            })
+            .transpose()
+    }
+
     /// Save config to file
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
@@ -227,6 +681,83 @@ impl Config {
         Ok(())
     }
 
+    /// Add the sections, fields and sources that `content` lacks compared to
+    /// `defaults`, editing the parsed document so the user's values, comments
+    /// and layout stay as they are
+    pub fn migrate(content: &str, defaults: &Config) -> Result<Migration> {
+        use toml_edit::{ArrayOfTables, DocumentMut, Item, Value};
+
+        let mut doc: DocumentMut = content.parse()?;
+        let mut defaults: DocumentMut = toml::to_string(defaults)?.parse()?;
+        unplace(defaults.as_item_mut());
+        let mut added = Vec::new();
+
+        for (key, default) in defaults.iter() {
+            if key == "sources" {
+                continue;
+            }
+            let Some(have) = doc.get_mut(key) else {
+                doc.insert(key, default.clone());
+                added.push(match default {
+                    Item::Table(_) => format!("[{}]", key),
+                    _ => key.to_string(),
+                });
+                continue;
+            };
+            let Item::Table(fields) = default else {
+                continue;
+            };
+            for (field, value) in fields.iter() {
+                if let Some(table) = have.as_table_like_mut()
+                    && !table.contains_key(field)
+                {
+                    table.insert(field, value.clone());
+                    added.push(format!("[{}] {}", key, field));
+                }
+            }
+            // An inline table has no comments to keep; respace it around the new fields
+            if let Some(inline) = have.as_inline_table_mut() {
+                inline.fmt();
+            }
+        }
+
+        let have_sources: Vec<String> = toml::from_str::<toml::Table>(content)?
+            .get("sources")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|s| Some(s.get("name")?.as_str()?.to_string()))
+            .collect();
+        let missing: Vec<_> = defaults
+            .get("sources")
+            .and_then(Item::as_array_of_tables)
+            .into_iter()
+            .flatten()
+            .filter_map(|s| Some((s.get("name")?.as_str()?, s)))
+            .filter(|(name, _)| !have_sources.iter().any(|have| have == name))
+            .collect();
+        if !missing.is_empty() {
+            let sources = doc
+                .entry("sources")
+                .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+            for (name, source) in missing {
+                match sources {
+                    Item::ArrayOfTables(list) => list.push(source.clone()),
+                    Item::Value(Value::Array(list)) => {
+                        list.push(source.clone().into_inline_table())
+                    }
+                    _ => anyhow::bail!("`sources` is not a list of tables"),
+                }
+                added.push(format!("[[sources]] {}", name));
+            }
+        }
+
+        let content = doc.to_string();
+        toml::from_str::<Config>(&content)
+            .map_err(|e| anyhow::anyhow!("migrated config does not parse: {}", e))?;
+        Ok(Migration { content, added })
+    }
+
     /// Get config file path
     pub fn config_path() -> Result<PathBuf> {
         let config_dir =
@@ -263,36 +794,105 @@ impl Config {
     }
 
     /// Check if a binary should be ignored in reports
-    pub fn should_ignore_binary(&self, binary_name: &str) -> bool {
-        for pattern in &self.scan.ignore_binaries {
-            if pattern.contains('*') {
-                let parts: Vec<&str> = pattern.split('*').collect();
-                if parts.len() == 2 {
-                    let (prefix, suffix) = (parts[0], parts[1]);
-                    if binary_name.starts_with(prefix) && binary_name.ends_with(suffix) {
-                        return true;
-                    }
-                }
-            } else if binary_name == pattern {
-                return true;
-            }
+    pub fn should_ignore_binary(&self, binary_name: &str, source: Option<&str>) -> bool {
+        self.ignored_by(binary_name, source).is_some()
+    }
+
+    /// Return the `ignore_binaries` pattern that hides this binary, if any:
+    /// the global list first, then the list on the binary's source
+    pub fn ignored_by(&self, binary_name: &str, source: Option<&str>) -> Option<&str> {
+        let source_patterns = self
+            .sources
+            .iter()
+            .filter(|s| source == Some(s.name.as_str()))
+            .flat_map(|s| &s.ignore_binaries);
+        self.scan
+            .ignore_binaries
+            .iter()
+            .chain(source_patterns)
+            .find(|pattern| glob_match(pattern, binary_name))
+            .map(String::as_str)
+    }
+}
+
+/// The result of [`Config::migrate`]
+#[derive(Debug)]
+pub struct Migration {
+    /// The upgraded file
+    pub content: String,
+    /// What was added, e.g. `[daemon]`, `[report] grace_days` or `[[sources]] go`
+    pub added: Vec<String>,
+}
+
+/// Clear the layout `item` was parsed with, so tables copied from it into
+/// another document are placed after that document's own tables
+fn unplace(item: &mut toml_edit::Item) {
+    let tables: Vec<&mut toml_edit::Table> = match item {
+        toml_edit::Item::Table(table) => vec![table],
+        toml_edit::Item::ArrayOfTables(list) => list.iter_mut().collect(),
+        _ => return,
+    };
+    for table in tables {
+        table.set_position(None);
+        table.decor_mut().clear();
+        for (_, value) in table.iter_mut() {
+            unplace(value);
+        }
+    }
+}
+
+/// Match `text` against a glob where `*` is any run of characters (including
+/// `/`) and `?` is exactly one; everything else is literal.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` and the text index it is currently absorbing up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, absorbed)) = backtrack {
+            pi = star + 1;
+            ti = absorbed + 1;
+            backtrack = Some((star, ti));
+        } else {
+            return false;
         }
-        false
     }
+    p[pi..].iter().all(|&c| c == '*')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_display_timezone_parse() {
+        assert_eq!(DisplayTimezone::parse("UTC").unwrap(), DisplayTimezone::UTC);
+        assert_eq!(
+            DisplayTimezone::parse("local").unwrap(),
+            DisplayTimezone::Local
+        );
+        assert_eq!(
+            DisplayTimezone::parse("Asia/Tokyo").unwrap(),
+            DisplayTimezone::Named(chrono_tz::Asia::Tokyo)
+        );
+        assert!(DisplayTimezone::parse("Mars/Olympus").is_err());
+    }
+
     #[test]
     fn test_should_ignore_binary_exact() {
         let mut config = Config::default();
         config.scan.ignore_binaries = vec!["python3-config".to_string()];
 
-        assert!(config.should_ignore_binary("python3-config"));
-        assert!(!config.should_ignore_binary("python3"));
-        assert!(!config.should_ignore_binary("python3-config-extra"));
+        assert!(config.should_ignore_binary("python3-config", None));
+        assert!(!config.should_ignore_binary("python3", None));
+        assert!(!config.should_ignore_binary("python3-config-extra", None));
     }
 
     #[test]
@@ -300,29 +900,205 @@ mod tests {
         let mut config = Config::default();
         config.scan.ignore_binaries = vec!["python*-config".to_string()];
 
-        assert!(config.should_ignore_binary("python3-config"));
-        assert!(config.should_ignore_binary("python-config"));
-        assert!(config.should_ignore_binary("python3.11-config"));
-        assert!(!config.should_ignore_binary("python3"));
+        assert!(config.should_ignore_binary("python3-config", None));
+        assert!(config.should_ignore_binary("python-config", None));
+        assert!(config.should_ignore_binary("python3.11-config", None));
+        assert!(!config.should_ignore_binary("python3", None));
     }
 
     #[test]
-    fn test_categorize_path() {
+    fn test_should_ignore_binary_per_source() {
         let mut config = Config::default();
-        config.sources = vec![
-            SourceDef {
-                name: "homebrew".to_string(),
-                path: "/opt/homebrew".to_string(),
-                uninstall_cmd: None,
-                list_cmd: None,
-            },
-            SourceDef {
-                name: "cargo".to_string(),
-                path: ".cargo/bin".to_string(),
-                uninstall_cmd: None,
-                list_cmd: None,
-            },
-        ];
+        config.scan.ignore_binaries = vec!["*-config".to_string()];
+        config.sources = vec![SourceDef {
+            name: "pip".to_string(),
+            path: ".local/bin".to_string(),
+            uninstall_cmd: None,
+            list_cmd: None,
+            read_only: false,
+            scope: None,
+            ignore_binaries: vec!["f2py*".to_string()],
+        }];
+
+        assert!(config.should_ignore_binary("f2py3", Some("pip")));
+        assert!(!config.should_ignore_binary("f2py3", Some("cargo")));
+        assert!(!config.should_ignore_binary("f2py3", None));
+        assert!(config.should_ignore_binary("python3-config", Some("cargo")));
+        assert_eq!(config.ignored_by("f2py", Some("pip")), Some("f2py*"));
+    }
+
+    #[test]
+    fn test_merge_shared_keeps_local_sources() {
+        let mut config: Config = toml::from_str(
+            r#"
+            include = ["team.toml"]
+            [scan]
+            extra_dirs = ["/opt/local/bin"]
+            [[sources]]
+            name = "acme"
+            path = "/opt/acme-local"
+            "#,
+        )
+        .unwrap();
+        let shared: SharedConfig = toml::from_str(
+            r#"
+            [scan]
+            extra_dirs = ["/opt/local/bin", "/opt/team/bin"]
+            [[sources]]
+            name = "acme"
+            path = "/opt/acme"
+            [[sources]]
+            name = "teamtool"
+            path = "/opt/team"
+            "#,
+        )
+        .unwrap();
+        config.merge_shared(shared);
+
+        let paths: Vec<_> = config
+            .sources
+            .iter()
+            .map(|s| (s.name.as_str(), s.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![("acme", "/opt/acme-local"), ("teamtool", "/opt/team")]
+        );
+        assert_eq!(
+            config.scan.extra_dirs,
+            vec!["/opt/local/bin", "/opt/team/bin"]
+        );
+    }
+
+    #[test]
+    fn test_migrate_adds_missing_fields_and_sources() {
+        let defaults: Config = toml::from_str(
+            r#"
+            [[sources]]
+            name = "cargo"
+            path = "~/.cargo/bin"
+            [[sources]]
+            name = "go"
+            path = "~/go/bin"
+            uninstall_cmd = "rm"
+            "#,
+        )
+        .unwrap();
+        let old = "# my settings\n[report]\ngrace_days = 3 # short\n\n# sources\n[[sources]]\nname = \"cargo\"\npath = \"/custom/cargo\"\n";
+
+        let migration = Config::migrate(old, &defaults).unwrap();
+        let config: Config = toml::from_str(&migration.content).unwrap();
+        assert!(migration.content.starts_with(
+            "# my settings\n[report]\ngrace_days = 3 # short\ncolumns = []\n\n# sources\n"
+        ));
+        assert_eq!(config.report.grace_days, 3);
+        let sources: Vec<_> = config
+            .sources
+            .iter()
+            .map(|s| (s.name.as_str(), s.path.as_str()))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![("cargo", "/custom/cargo"), ("go", "~/go/bin")]
+        );
+        assert!(migration.added.contains(&"[report] columns".to_string()));
+        assert!(migration.added.contains(&"[daemon]".to_string()));
+        assert!(migration.added.contains(&"[[sources]] go".to_string()));
+
+        let again = Config::migrate(&migration.content, &defaults).unwrap();
+        assert!(again.added.is_empty());
+        assert_eq!(again.content, migration.content);
+    }
+
+    #[test]
+    fn test_migrate_keeps_arrays_and_inline_tables() {
+        let defaults: Config = toml::from_str("").unwrap();
+        let old = "report = { grace_days = 3 }\n\n[scan]\nskip_dirs = [\n  \"/usr/bin\", # system\n]\nmatrix = [\n  [\"a\", \"b\"]\n]\n";
+
+        let migration = Config::migrate(old, &defaults).unwrap();
+        let config: Config = toml::from_str(&migration.content).unwrap();
+        assert!(migration.content.starts_with(
+            "report = { grace_days = 3, columns = [] }\n\n[scan]\nskip_dirs = [\n  \"/usr/bin\", # system\n]\nmatrix = [\n  [\"a\", \"b\"]\n]\npath = true\n"
+        ));
+        assert_eq!(config.report.grace_days, 3);
+        assert_eq!(config.scan.skip_dirs, vec!["/usr/bin"]);
+        assert!(migration.added.contains(&"[report] columns".to_string()));
+        assert!(migration.added.contains(&"[scan] path".to_string()));
+        assert!(migration.added.contains(&"[daemon]".to_string()));
+    }
+
+    #[test]
+    fn test_daemon_log_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            [daemon]
+            log_file = "~/logs/dusty.log"
+            log_level = "warn"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.daemon.log_level, LogLevel::Warn);
+        assert_eq!(config.daemon.log_max_bytes, 10 * 1024 * 1024);
+        assert_eq!(config.daemon.log_keep, 3);
+        assert_eq!(
+            config.daemon.log_path(),
+            dirs::home_dir().map(|h| h.join("logs/dusty.log"))
+        );
+        assert!(LogLevel::Error < LogLevel::Warn && LogLevel::Info < LogLevel::Debug);
+        assert!(DaemonConfig::default().log_path().is_none());
+    }
+
+    #[test]
+    fn test_has_executable_extension() {
+        let mut scan = ScanConfig::default();
+        assert!(!scan.has_executable_extension(Path::new("/bin/tool.py")));
+
+        scan.executable_extensions = vec![".py".to_string(), "PS1".to_string()];
+        assert!(scan.has_executable_extension(Path::new("/bin/tool.py")));
+        assert!(scan.has_executable_extension(Path::new("/bin/Tool.PY")));
+        assert!(scan.has_executable_extension(Path::new("/bin/setup.ps1")));
+        assert!(!scan.has_executable_extension(Path::new("/bin/tool.pyc")));
+        assert!(!scan.has_executable_extension(Path::new("/bin/py")));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("2to3*", "2to3-3.11"));
+        assert!(glob_match("*-config", "python3-config"));
+        assert!(glob_match("x*y*z", "x-a-y-b-z"));
+        assert!(glob_match("lua?.?", "lua5.4"));
+        assert!(glob_match("/opt/company/*", "/opt/company/tools/bin/x"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("x*y*z", "x-a-z-b-y"));
+        assert!(!glob_match("lua?.?", "lua5.40"));
+        assert!(!glob_match("git", "gitk"));
+    }
+
+    #[test]
+    fn test_categorize_path() {
+        let config = Config {
+            sources: vec![
+                SourceDef {
+                    name: "homebrew".to_string(),
+                    path: "/opt/homebrew".to_string(),
+                    uninstall_cmd: None,
+                    list_cmd: None,
+                    read_only: false,
+                    scope: None,
+                    ignore_binaries: vec![],
+                },
+                SourceDef {
+                    name: "cargo".to_string(),
+                    path: ".cargo/bin".to_string(),
+                    uninstall_cmd: None,
+                    list_cmd: None,
+                    read_only: false,
+                    scope: None,
+                    ignore_binaries: vec![],
+                },
+            ],
+            ..Config::default()
+        };
 
         assert_eq!(config.categorize_path("/opt/homebrew/bin/git"), "homebrew");
         assert_eq!(
@@ -340,4 +1116,28 @@ mod tests {
         assert!(config.should_skip_dir("/bin"));
         assert!(!config.should_skip_dir("/opt/homebrew/bin"));
     }
+
+    #[test]
+    fn test_source_scope() {
+        let config: Config = toml::from_str(
+            r#"
+            [[sources]]
+            name = "apt"
+            path = "/usr/bin"
+            scope = "user"
+
+            [[sources]]
+            name = "company"
+            path = "/opt/company"
+            scope = "system"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.source_scope("apt"), Some(SourceScope::User));
+        assert_eq!(config.source_scope("company"), Some(SourceScope::System));
+        assert_eq!(config.source_scope("dnf"), Some(SourceScope::System));
+        assert_eq!(config.source_scope("cargo"), Some(SourceScope::User));
+        assert_eq!(config.source_scope("opt"), None);
+    }
 }
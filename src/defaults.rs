@@ -13,6 +13,13 @@ pub struct SourceCandidate {
     pub uninstall_cmd: Option<&'static str>,
 }
 
+/// `asdf uninstall` takes the plugin and version as separate arguments, so
+/// each "tool@version" package name is split in a small shell loop
+pub const ASDF_UNINSTALL_CMD: &str =
+    r#"sh -c 'for p; do asdf uninstall "${p%@*}" "${p#*@}"; done' asdf-uninstall"#;
+pub const ASDF_INSTALL_CMD: &str =
+    r#"sh -c 'for p; do asdf install "${p%@*}" "${p#*@}"; done' asdf-install"#;
+
 /// All known source candidates, checked in order during config generation.
 /// Only sources whose detection paths exist on the system are included in config.
 pub const SOURCE_CANDIDATES: &[SourceCandidate] = &[
@@ -75,11 +82,27 @@ pub const SOURCE_CANDIDATES: &[SourceCandidate] = &[
         detect_paths: &["~/go/bin"],
         uninstall_cmd: None,
     },
+    SourceCandidate {
+        name: "pipx",
+        detect_paths: &["~/.local/share/pipx/venvs", "~/.local/pipx/venvs"],
+        uninstall_cmd: Some("pipx uninstall"),
+    },
     SourceCandidate {
         name: "pip",
         detect_paths: &["~/.local/bin"],
         uninstall_cmd: Some("pip uninstall -y"),
     },
+    // Polyglot version managers: packages are "tool@version"
+    SourceCandidate {
+        name: "asdf",
+        detect_paths: &["~/.asdf"],
+        uninstall_cmd: Some(ASDF_UNINSTALL_CMD),
+    },
+    SourceCandidate {
+        name: "mise",
+        detect_paths: &["~/.local/share/mise"],
+        uninstall_cmd: Some("mise uninstall"),
+    },
     SourceCandidate {
         name: "pyenv",
         detect_paths: &["~/.pyenv"],
@@ -127,6 +150,15 @@ pub const EXTRA_PATH_PATTERNS: &[(&str, &str, &str)] = &[("homebrew", "Cellar",
 #[cfg(target_os = "linux")]
 pub const EXTRA_PATH_PATTERNS: &[(&str, &str, &str)] = &[];
 
+/// Sources assumed `scope = "system"` when the config doesn't say: removing
+/// their packages can affect the OS itself
+pub const SYSTEM_SOURCES: &[&str] = &["apt", "dnf", "pacman", "zypper", "apk", "snap", "flatpak"];
+
+/// Sources assumed `scope = "user"` when the config doesn't say
+pub const USER_SOURCES: &[&str] = &[
+    "cargo", "npm", "pip", "pipx", "go", "bun", "deno", "asdf", "mise",
+];
+
 /// Path prefix replacements for display shortening, applied in order.
 /// Format: (prefix_to_match, replacement)
 /// `~` in the prefix is expanded to $HOME at runtime.
@@ -148,10 +180,34 @@ pub const SUDO: &str = "sudo";
 pub const RM: &str = "rm";
 pub const RM_RECURSIVE_FLAGS: &[&str] = &["-rf"];
 
+/// `du` flag for apparent (logical) size instead of disk usage
+#[cfg(target_os = "macos")]
+pub const DU_APPARENT_FLAG: &str = "-A";
+
+#[cfg(target_os = "linux")]
+pub const DU_APPARENT_FLAG: &str = "--apparent-size";
+
+/// Version-manager directories holding one subdirectory per installed version
+/// (~ expanded to $HOME at runtime). `clean --all-versions` only removes
+/// version directories under these; other sources go through their uninstall command.
+pub const VERSION_MANAGER_DIRS: &[&str] = &[
+    "~/.pyenv/versions/",
+    "~/.rbenv/versions/",
+    "~/.nvm/versions/node/",
+    "~/.asdf/installs/",
+    "~/.local/share/mise/installs/",
+];
+
 /// Install root detection anchors (~ expanded to $HOME at runtime)
 pub const INSTALL_ROOT_ANCHORS: &[&str] = &["/opt/", "/usr/local/", "~/"];
 
-/// Trash directory name (under data_local_dir/dusty/)
+/// Most execs `dusty import --from-history` credits to one binary (the newest are kept)
+pub const HISTORY_IMPORT_CAP: usize = 1000;
+
+/// How long a measured package size stays valid in the `package_sizes` cache
+pub const PACKAGE_SIZE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Trash directory name (under `storage::data_dir()`)
 pub const TRASH_DIR: &str = "trash";
 
 /// Derive an install command from an uninstall command.
@@ -168,9 +224,12 @@ pub fn install_cmd_from_uninstall(uninstall_cmd: &str) -> Option<String> {
         ("flatpak uninstall", "flatpak install"),
         ("cargo uninstall", "cargo install"),
         ("npm uninstall -g", "npm install -g"),
+        ("pipx uninstall", "pipx install"),
         ("pip uninstall -y", "pip install"),
         ("nix-env --uninstall", "nix-env --install"),
         ("bun remove -g", "bun add -g"),
+        ("mise uninstall", "mise install"),
+        (ASDF_UNINSTALL_CMD, ASDF_INSTALL_CMD),
     ];
 
     for (uninstall, install) in mappings {
@@ -181,6 +240,12 @@ pub fn install_cmd_from_uninstall(uninstall_cmd: &str) -> Option<String> {
     None
 }
 
+/// Whether an uninstall command only accepts "tool@version" package names
+/// (a bare tool name would remove every version, or split into garbage)
+pub fn uninstall_needs_version(uninstall_cmd: &str) -> bool {
+    uninstall_cmd == ASDF_UNINSTALL_CMD || uninstall_cmd == "mise uninstall"
+}
+
 /// Autoremove commands to clean up orphaned dependencies, keyed by source name.
 pub fn autoremove_hint(source: &str) -> Option<&'static str> {
     match source {
@@ -196,7 +261,6 @@ pub fn autoremove_hint(source: &str) -> Option<&'static str> {
 
 /// Editor and pager defaults
 pub const DEFAULT_EDITOR: &str = "vim";
-pub const DEFAULT_PAGER: &str = "less";
 pub const PAGER_COLOR_FLAG: &str = "-R";
 
 #[cfg(test)]
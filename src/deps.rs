@@ -1,22 +1,31 @@
 //! Dynamic library dependency analysis and orphan detection
 
-use crate::platform::{Analyzer, DylibAnalyzer};
-use crate::storage::Database;
+use crate::defaults;
+use crate::platform::{Analyzer, DylibAnalyzer, Linkage, is_offline};
+use crate::storage::{BinaryRecord, Database};
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
 /// Full dependency analysis result
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DepsReport {
     pub orphan_packages: Vec<OrphanPackage>,
     pub total_freeable_bytes: u64,
     pub binaries_analyzed: usize,
     pub total_lib_packages: usize,
+    /// Binaries with no shared library dependencies (static or system-only)
+    pub static_binaries: usize,
+    /// Binaries the platform analyzer couldn't inspect
+    pub failed_binaries: usize,
+    /// Orphans smaller than `dusty deps --min-size`, left out of
+    /// `orphan_packages` but still counted in `total_freeable_bytes`
+    pub hidden_orphans: usize,
 }
 
 /// A library package only used by dusty binaries
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct OrphanPackage {
     pub manager: String,
     pub package_name: String,
@@ -24,34 +33,251 @@ pub struct OrphanPackage {
     pub used_by_dusty: Vec<String>,
 }
 
+/// An active binary and the library packages no other active binary needs
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UnusedDeps {
+    pub binary_path: String,
+    pub package_name: Option<String>,
+    pub source: Option<String>,
+    pub uses: i64,
+    pub libs: Vec<LonelyLib>,
+    pub total_bytes: u64,
+}
+
+/// A library package whose only active user is a single binary
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LonelyLib {
+    pub manager: String,
+    pub package_name: String,
+    pub size_bytes: Option<u64>,
+    pub used_by_dusty: Vec<String>,
+}
+
+/// A library package linked only by one package's binaries
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExclusiveLib {
+    pub manager: String,
+    pub package_name: String,
+    pub size_bytes: Option<u64>,
+}
+
+/// An active binary that links a library from inside an install root
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RootDependent {
+    pub binary_path: String,
+    pub lib_path: String,
+    pub uses: i64,
+}
+
 /// Result of analyzing a single binary's deps (for --binary mode)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SingleBinaryDeps {
     pub binary_path: String,
+    /// "dynamic", "static", or "script"
+    pub linkage: String,
     pub libs: Vec<ResolvedLib>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ResolvedLib {
     pub lib_path: String,
     pub package_name: Option<String>,
     pub manager: Option<String>,
 }
 
-/// Run the full dependency analysis pipeline
+/// Binary → library → package graph built from the cached analysis
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DepGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphNode {
+    /// Unique id: "bin:<path>", "lib:<path>" or "pkg:<manager>/<name>"
+    pub id: String,
+    /// "binary", "library" or "package"
+    pub kind: &'static str,
+    pub label: String,
+    /// Binaries: "active" or "dusty". Packages: "active" if any active binary
+    /// needs them, else "orphan". Libraries: none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Build the dependency graph from what's already in the DB. Nothing is
+/// re-analyzed; run `dusty deps` first to fill the cache.
+pub fn build_dep_graph(db: &Database, manager_filter: Option<&str>) -> Result<DepGraph> {
+    let counts: HashMap<String, i64> = db
+        .get_all_binaries()?
+        .into_iter()
+        .map(|b| (b.path, b.count))
+        .collect();
+    let owners: HashMap<String, (String, String)> = db
+        .get_all_lib_packages()?
+        .into_iter()
+        .filter(|(_, manager, _)| manager_filter.is_none_or(|m| m == manager))
+        .map(|(lib, manager, pkg)| (lib, (manager, pkg)))
+        .collect();
+
+    let mut deps: Vec<(String, Vec<String>)> = db
+        .get_all_dylib_deps()?
+        .into_iter()
+        .filter(|(bin, _)| counts.contains_key(bin))
+        .collect();
+    deps.sort();
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut seen_libs = HashSet::new();
+    // (manager, package) -> whether any active binary reaches it
+    let mut packages: std::collections::BTreeMap<(String, String), bool> = Default::default();
+
+    for (bin, libs) in &deps {
+        let count = counts[bin];
+        let bin_id = format!("bin:{}", bin);
+        nodes.push(GraphNode {
+            id: bin_id.clone(),
+            kind: "binary",
+            label: file_label(bin),
+            status: Some(if count > 0 { "active" } else { "dusty" }),
+            count: Some(count),
+            size_bytes: None,
+        });
+        for lib in libs {
+            let owner = owners.get(lib);
+            if manager_filter.is_some() && owner.is_none() {
+                continue;
+            }
+            let lib_id = format!("lib:{}", lib);
+            edges.push(GraphEdge {
+                from: bin_id.clone(),
+                to: lib_id.clone(),
+            });
+            if let Some(key) = owner {
+                *packages.entry(key.clone()).or_default() |= count > 0;
+            }
+            if !seen_libs.insert(lib.clone()) {
+                continue;
+            }
+            nodes.push(GraphNode {
+                id: lib_id.clone(),
+                kind: "library",
+                label: file_label(lib),
+                status: None,
+                count: None,
+                size_bytes: None,
+            });
+            if let Some((manager, pkg)) = owner {
+                edges.push(GraphEdge {
+                    from: lib_id,
+                    to: format!("pkg:{}/{}", manager, pkg),
+                });
+            }
+        }
+    }
+
+    for ((manager, pkg), active) in packages {
+        nodes.push(GraphNode {
+            id: format!("pkg:{}/{}", manager, pkg),
+            kind: "package",
+            label: format!("{} ({})", pkg, manager),
+            status: Some(if active { "active" } else { "orphan" }),
+            count: None,
+            size_bytes: package_size(db, &manager, &pkg),
+        });
+    }
+
+    Ok(DepGraph { nodes, edges })
+}
+
+/// On-disk size of an installed package, served from the `package_sizes` cache
+/// while fresh so `dusty deps` and `dusty size` don't re-run `du` for it
+pub fn package_size(db: &Database, manager: &str, package_name: &str) -> Option<u64> {
+    if let Ok(Some(size)) =
+        db.get_package_size(manager, package_name, defaults::PACKAGE_SIZE_TTL_SECS)
+    {
+        return Some(size);
+    }
+    let size = Analyzer::get_package_size(manager, package_name).unwrap_or(None)?;
+    let _ = db.store_package_size(manager, package_name, size);
+    Some(size)
+}
+
+fn file_label(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Run the full dependency analysis pipeline.
+/// `manager` restricts the orphan report (and its size lookups) to one package manager.
 pub fn analyze_deps(
     db: &Database,
     refresh: bool,
+    manager: Option<&str>,
     progress_callback: Option<&dyn Fn(usize, usize)>,
 ) -> Result<DepsReport> {
+    let binaries = db.get_all_binaries()?;
+    update_dep_cache(db, &binaries, refresh, progress_callback)?;
+
+    // Phase 3: Build orphan report
+    let binary_counts: Vec<(String, i64)> =
+        binaries.iter().map(|b| (b.path.clone(), b.count)).collect();
+    let mut report = build_orphan_report(db, &binary_counts, manager)?;
+
+    let linkage = db.get_all_linkage()?;
+    let count_of = |kind: &str| {
+        binaries
+            .iter()
+            .filter(|b| linkage.get(&b.path).is_some_and(|l| l == kind))
+            .count()
+    };
+    report.static_binaries = count_of(Linkage::Static.as_str());
+    report.failed_binaries = count_of("failed");
+    Ok(report)
+}
+
+/// Analysis results are flushed to the DB in chunks of this many binaries,
+/// so an interrupted run resumes where it left off instead of starting over
+const FLUSH_EVERY: usize = 200;
+
+/// Phases 1-2: analyze binaries whose cached deps are missing or stale, then
+/// resolve any new library paths to packages. `refresh` discards the cache first.
+pub fn update_dep_cache(
+    db: &Database,
+    binaries: &[BinaryRecord],
+    refresh: bool,
+    progress_callback: Option<&dyn Fn(usize, usize)>,
+) -> Result<()> {
     if refresh {
+        if is_offline() {
+            anyhow::bail!("--refresh re-runs dependency analysis, which --offline forbids");
+        }
         db.clear_all_deps()?;
     }
+    // Offline: report from whatever the cache already holds
+    if is_offline() {
+        return Ok(());
+    }
 
-    let binaries = db.get_all_binaries()?;
     let total = binaries.len();
 
     // Phase 1: Analyze each binary's dylib dependencies
+    let mut analyzed_deps: Vec<(String, Vec<String>)> = Vec::new();
+    let mut analyzed_mtimes: Vec<(String, Option<i64>, &str)> = Vec::new();
     for (i, binary) in binaries.iter().enumerate() {
         if let Some(cb) = &progress_callback {
             cb(i, total);
@@ -64,16 +290,30 @@ pub fn analyze_deps(
         match Analyzer::analyze_binary(&binary.path) {
             Ok(analysis) => {
                 let lib_paths: Vec<String> = analysis.libs.iter().map(|l| l.path.clone()).collect();
-                db.store_dylib_deps(&binary.path, &lib_paths)?;
-                let mtime = get_file_mtime(&binary.path);
-                db.mark_deps_analyzed(&binary.path, mtime)?;
+                analyzed_deps.push((binary.path.clone(), lib_paths));
+                analyzed_mtimes.push((
+                    binary.path.clone(),
+                    get_file_mtime(&binary.path),
+                    analysis.linkage.as_str(),
+                ));
             }
             Err(_) => {
-                db.store_dylib_deps(&binary.path, &[])?;
-                db.mark_deps_analyzed(&binary.path, None)?;
+                // No mtime recorded, so a failed binary is retried on the next run
+                analyzed_deps.push((binary.path.clone(), Vec::new()));
+                analyzed_mtimes.push((binary.path.clone(), None, "failed"));
             }
         }
+
+        // Write results in batched transactions instead of one per binary
+        if analyzed_deps.len() >= FLUSH_EVERY {
+            db.store_dylib_deps_many(&analyzed_deps)?;
+            db.mark_deps_analyzed_many(&analyzed_mtimes)?;
+            analyzed_deps.clear();
+            analyzed_mtimes.clear();
+        }
     }
+    db.store_dylib_deps_many(&analyzed_deps)?;
+    db.mark_deps_analyzed_many(&analyzed_mtimes)?;
 
     // Phase 2: Resolve unresolved library paths to packages
     let unresolved = db.get_unresolved_libs()?;
@@ -84,29 +324,49 @@ pub fn analyze_deps(
         }
     }
 
-    // Phase 3: Build orphan report
-    let binary_counts: Vec<(String, i64)> =
-        binaries.iter().map(|b| (b.path.clone(), b.count)).collect();
-    build_orphan_report(db, &binary_counts)
+    Ok(())
 }
 
 /// Analyze a single binary and resolve its deps
+/// (offline: read back the cached analysis instead)
 pub fn analyze_single_binary(db: &Database, binary_path: &str) -> Result<SingleBinaryDeps> {
-    let analysis = Analyzer::analyze_binary(binary_path)?;
-    let lib_paths: Vec<String> = analysis.libs.iter().map(|l| l.path.clone()).collect();
-
-    // Store in DB for caching
-    db.store_dylib_deps(binary_path, &lib_paths)?;
-    db.mark_deps_analyzed(binary_path, get_file_mtime(binary_path))?;
-
-    // Resolve any new libs
-    let unresolved = db.get_unresolved_libs()?;
-    if !unresolved.is_empty() {
-        let resolved = Analyzer::resolve_lib_packages(&unresolved)?;
-        for info in &resolved {
-            db.store_lib_package(&info.lib_path, &info.manager, &info.package_name)?;
+    let (linkage, lib_paths) = if is_offline() {
+        let linkage = db
+            .get_all_linkage()?
+            .remove(binary_path)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} has no cached dependency data (unknown offline); run dusty deps without --offline first",
+                    binary_path
+                )
+            })?;
+        let libs = db
+            .get_all_dylib_deps()?
+            .remove(binary_path)
+            .unwrap_or_default();
+        (linkage, libs)
+    } else {
+        let analysis = Analyzer::analyze_binary(binary_path)?;
+        let lib_paths: Vec<String> = analysis.libs.iter().map(|l| l.path.clone()).collect();
+
+        // Store in DB for caching
+        db.store_dylib_deps(binary_path, &lib_paths)?;
+        db.mark_deps_analyzed(
+            binary_path,
+            get_file_mtime(binary_path),
+            analysis.linkage.as_str(),
+        )?;
+
+        // Resolve any new libs
+        let unresolved = db.get_unresolved_libs()?;
+        if !unresolved.is_empty() {
+            let resolved = Analyzer::resolve_lib_packages(&unresolved)?;
+            for info in &resolved {
+                db.store_lib_package(&info.lib_path, &info.manager, &info.package_name)?;
+            }
         }
-    }
+        (analysis.linkage.as_str().to_string(), lib_paths)
+    };
 
     // Build result with resolved package info
     let all_lib_pkgs = db.get_all_lib_packages()?;
@@ -129,6 +389,7 @@ pub fn analyze_single_binary(db: &Database, binary_path: &str) -> Result<SingleB
 
     Ok(SingleBinaryDeps {
         binary_path: binary_path.to_string(),
+        linkage,
         libs,
     })
 }
@@ -150,7 +411,156 @@ fn get_file_mtime(path: &str) -> Option<i64> {
         .map(|d| d.as_secs() as i64)
 }
 
-fn build_orphan_report(db: &Database, binaries: &[(String, i64)]) -> Result<DepsReport> {
+/// (manager, package_name) -> set of binary paths that link against it
+fn lib_package_users(
+    db: &Database,
+    manager_filter: Option<&str>,
+) -> Result<HashMap<(String, String), HashSet<String>>> {
+    let all_lib_packages = db.get_all_lib_packages()?;
+    let mut pkg_to_users: HashMap<(String, String), HashSet<String>> = HashMap::new();
+
+    for (lib_path, manager, pkg_name) in &all_lib_packages {
+        if manager_filter.is_some_and(|m| m != manager) {
+            continue;
+        }
+        let users = db.get_binaries_using_lib(lib_path)?;
+        pkg_to_users
+            .entry((manager.clone(), pkg_name.clone()))
+            .or_default()
+            .extend(users);
+    }
+    Ok(pkg_to_users)
+}
+
+/// Library packages that only one package's binaries link against, keyed by
+/// that package's (source, name). `package_of` maps tracked binary paths to
+/// their package; any other user (e.g. a pruned binary) makes a library shared.
+/// A package's own libraries are left out. Reads the cached deps tables only.
+pub fn exclusive_lib_packages(
+    db: &Database,
+    package_of: &HashMap<String, (String, String)>,
+) -> Result<HashMap<(String, String), Vec<ExclusiveLib>>> {
+    let mut by_package: HashMap<(String, String), Vec<ExclusiveLib>> = HashMap::new();
+    for ((manager, pkg_name), users) in lib_package_users(db, None)? {
+        let mut owners = users.iter().map(|u| package_of.get(u));
+        let Some(Some(owner)) = owners.next() else {
+            continue;
+        };
+        if owners.any(|o| o != Some(owner)) || owner.1 == pkg_name {
+            continue;
+        }
+        let size_bytes = package_size(db, &manager, &pkg_name);
+        by_package
+            .entry(owner.clone())
+            .or_default()
+            .push(ExclusiveLib {
+                manager,
+                package_name: pkg_name,
+                size_bytes,
+            });
+    }
+    Ok(by_package)
+}
+
+/// Active binaries outside `root` that link a library under it, i.e. what
+/// removing `root` would break. Reads the cached deps tables only. Paths are
+/// compared after resolving symlinks, so `/usr/local/opt/foo` and the Cellar
+/// directory it points into count as the same root.
+pub fn active_dependents_under(
+    db: &Database,
+    binaries: &[BinaryRecord],
+    root: &str,
+) -> Result<Vec<RootDependent>> {
+    let canonical =
+        |path: &str| std::fs::canonicalize(path).unwrap_or_else(|_| std::path::PathBuf::from(path));
+    let root = canonical(root);
+    let deps = db.get_all_dylib_deps()?;
+    let mut dependents: Vec<RootDependent> = binaries
+        .iter()
+        .filter(|b| b.count > 0 && !canonical(&b.path).starts_with(&root))
+        .filter_map(|b| {
+            let lib = deps
+                .get(&b.path)?
+                .iter()
+                .find(|lib| canonical(lib).starts_with(&root))?;
+            Some(RootDependent {
+                binary_path: b.path.clone(),
+                lib_path: lib.clone(),
+                uses: b.count,
+            })
+        })
+        .collect();
+    dependents.sort_by(|a, b| {
+        b.uses
+            .cmp(&a.uses)
+            .then_with(|| a.binary_path.cmp(&b.binary_path))
+    });
+    Ok(dependents)
+}
+
+/// Library packages kept alive by exactly one active binary, grouped by that
+/// binary and largest first. Other users, if any, are all dusty. Reads the
+/// cached deps tables only; run `update_dep_cache` first.
+pub fn find_unused_deps(db: &Database, binaries: &[BinaryRecord]) -> Result<Vec<UnusedDeps>> {
+    let by_path: HashMap<&str, &BinaryRecord> =
+        binaries.iter().map(|b| (b.path.as_str(), b)).collect();
+
+    let mut by_binary: HashMap<&str, Vec<LonelyLib>> = HashMap::new();
+    for ((manager, pkg_name), users) in lib_package_users(db, None)? {
+        let mut active = users
+            .iter()
+            .filter(|u| by_path.get(u.as_str()).is_some_and(|b| b.count > 0));
+        let (Some(owner), None) = (active.next(), active.next()) else {
+            continue;
+        };
+        let Some((&owner, _)) = by_path.get_key_value(owner.as_str()) else {
+            continue;
+        };
+
+        let mut used_by_dusty: Vec<String> = users
+            .iter()
+            .filter(|u| by_path.get(u.as_str()).is_some_and(|b| b.count == 0))
+            .cloned()
+            .collect();
+        used_by_dusty.sort();
+
+        let size_bytes = package_size(db, &manager, &pkg_name);
+        by_binary.entry(owner).or_default().push(LonelyLib {
+            manager,
+            package_name: pkg_name,
+            size_bytes,
+            used_by_dusty,
+        });
+    }
+
+    let mut entries: Vec<UnusedDeps> = by_binary
+        .into_iter()
+        .map(|(path, mut libs)| {
+            libs.sort_by_key(|l| std::cmp::Reverse(l.size_bytes.unwrap_or(0)));
+            let b = by_path[path];
+            UnusedDeps {
+                binary_path: path.to_string(),
+                package_name: b.package_name.clone(),
+                source: b.source.clone(),
+                uses: b.count,
+                total_bytes: libs.iter().filter_map(|l| l.size_bytes).sum(),
+                libs,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.total_bytes
+            .cmp(&a.total_bytes)
+            .then_with(|| a.binary_path.cmp(&b.binary_path))
+    });
+    Ok(entries)
+}
+
+fn build_orphan_report(
+    db: &Database,
+    binaries: &[(String, i64)],
+    manager_filter: Option<&str>,
+) -> Result<DepsReport> {
     let dusty_paths: HashSet<&str> = binaries
         .iter()
         .filter(|(_, count)| *count == 0)
@@ -163,18 +573,7 @@ fn build_orphan_report(db: &Database, binaries: &[(String, i64)]) -> Result<Deps
         .map(|(path, _)| path.as_str())
         .collect();
 
-    // Build: (manager, package_name) -> set of binary_paths that use it
-    let all_lib_packages = db.get_all_lib_packages()?;
-    let mut pkg_to_users: HashMap<(String, String), HashSet<String>> = HashMap::new();
-
-    for (lib_path, manager, pkg_name) in &all_lib_packages {
-        let users = db.get_binaries_using_lib(lib_path)?;
-        pkg_to_users
-            .entry((manager.clone(), pkg_name.clone()))
-            .or_default()
-            .extend(users);
-    }
-
+    let pkg_to_users = lib_package_users(db, manager_filter)?;
     let total_lib_packages = pkg_to_users.len();
 
     // Find orphans: packages where ALL users are dusty
@@ -197,7 +596,7 @@ fn build_orphan_report(db: &Database, binaries: &[(String, i64)]) -> Result<Deps
             continue;
         }
 
-        let size = Analyzer::get_package_size(manager, pkg_name).unwrap_or(None);
+        let size = package_size(db, manager, pkg_name);
         if let Some(s) = size {
             total_freeable += s;
         }
@@ -211,12 +610,71 @@ fn build_orphan_report(db: &Database, binaries: &[(String, i64)]) -> Result<Deps
     }
 
     // Sort by size descending
-    orphans.sort_by(|a, b| b.size_bytes.unwrap_or(0).cmp(&a.size_bytes.unwrap_or(0)));
+    orphans.sort_by_key(|o| std::cmp::Reverse(o.size_bytes.unwrap_or(0)));
 
     Ok(DepsReport {
         orphan_packages: orphans,
         total_freeable_bytes: total_freeable,
         binaries_analyzed: binaries.len(),
         total_lib_packages,
+        static_binaries: 0,
+        failed_binaries: 0,
+        hidden_orphans: 0,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(path: &std::path::Path, count: i64) -> BinaryRecord {
+        BinaryRecord {
+            path: path.to_string_lossy().to_string(),
+            count,
+            first_seen: None,
+            last_seen: None,
+            source: Some("other".to_string()),
+            package_name: None,
+            observed: count > 0,
+        }
+    }
+
+    #[test]
+    fn test_active_dependents_under_resolves_symlinks() {
+        let tmp = std::env::temp_dir().join(format!("dusty-deps-{}", std::process::id()));
+        let root = tmp.join("Cellar/foo/1.0");
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::create_dir_all(tmp.join("bin")).unwrap();
+        std::fs::write(root.join("lib/libfoo.so"), "").unwrap();
+        std::os::unix::fs::symlink(&root, tmp.join("opt-foo")).unwrap();
+
+        let db = Database::open_in_memory().unwrap();
+        let user = tmp.join("bin/user");
+        let idle = tmp.join("bin/idle");
+        let own = root.join("bin/foo");
+        // Recorded through the symlinked path, asked about by the real one
+        let lib = tmp
+            .join("opt-foo/lib/libfoo.so")
+            .to_string_lossy()
+            .to_string();
+        for b in [&user, &idle, &own] {
+            std::fs::write(b, "").unwrap();
+            db.store_dylib_deps(&b.to_string_lossy(), std::slice::from_ref(&lib))
+                .unwrap();
+        }
+        let binaries = vec![binary(&user, 5), binary(&idle, 0), binary(&own, 9)];
+
+        let found = active_dependents_under(&db, &binaries, &root.to_string_lossy()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].binary_path, user.to_string_lossy());
+        assert_eq!(found[0].lib_path, lib);
+
+        // And the other way round: root given through the symlink
+        let via_link = tmp.join("opt-foo").to_string_lossy().to_string();
+        let found = active_dependents_under(&db, &binaries, &via_link).unwrap();
+        assert_eq!(found.len(), 1);
+
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
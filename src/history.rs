@@ -0,0 +1,231 @@
+//! Shell history parsers used to seed usage counts (`dusty import --from-history`).
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// One command from a shell history: the program it ran and, where the
+/// history records it, when
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub program: String,
+    pub timestamp: Option<i64>,
+}
+
+/// Where to read history from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistorySource {
+    Zsh(PathBuf),
+    Bash(PathBuf),
+    Atuin(PathBuf),
+}
+
+impl HistorySource {
+    /// Pick a source from `--from-history`'s value: "atuin", a history file, or
+    /// "auto" ($HISTFILE, else the history file of $SHELL)
+    pub fn detect(arg: &str) -> Result<Self> {
+        let home = dirs::home_dir().unwrap_or_default();
+        if arg == "atuin" {
+            let path = std::env::var_os("ATUIN_DB_PATH")
+                .map(PathBuf::from)
+                .or_else(|| {
+                    std::env::var_os("XDG_DATA_HOME")
+                        .map(|d| PathBuf::from(d).join("atuin/history.db"))
+                })
+                .unwrap_or_else(|| home.join(".local/share/atuin/history.db"));
+            return Ok(Self::Atuin(path));
+        }
+
+        let path = if arg == "auto" {
+            match std::env::var_os("HISTFILE") {
+                Some(file) => PathBuf::from(file),
+                None => {
+                    let shell = std::env::var("SHELL").unwrap_or_default();
+                    match Path::new(&shell).file_name().and_then(|n| n.to_str()) {
+                        Some("zsh") => home.join(".zsh_history"),
+                        Some("bash") => home.join(".bash_history"),
+                        _ => anyhow::bail!(
+                            "can't tell which history to read from $SHELL; pass a file or 'atuin'"
+                        ),
+                    }
+                }
+            }
+        } else if let Some(rest) = arg.strip_prefix("~/") {
+            home.join(rest)
+        } else {
+            PathBuf::from(arg)
+        };
+
+        let is_zsh = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.contains("zsh") || n.contains("zhistory"));
+        Ok(if is_zsh {
+            Self::Zsh(path)
+        } else {
+            Self::Bash(path)
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Zsh(p) | Self::Bash(p) | Self::Atuin(p) => p,
+        }
+    }
+
+    /// Read and parse every entry
+    pub fn read(&self) -> Result<Vec<HistoryEntry>> {
+        match self {
+            Self::Atuin(path) => read_atuin(path),
+            Self::Zsh(path) | Self::Bash(path) => {
+                // zsh "metafies" non-ASCII bytes, so don't insist on UTF-8
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let content = String::from_utf8_lossy(&bytes);
+                Ok(match self {
+                    Self::Zsh(_) => parse_zsh(&content),
+                    _ => parse_bash(&content),
+                })
+            }
+        }
+    }
+}
+
+/// zsh history, plain or with EXTENDED_HISTORY (`: 1700000000:0;git status`).
+/// Continuation lines of multi-line commands are skipped.
+pub fn parse_zsh(content: &str) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        let was_continued = continued;
+        continued = line.ends_with('\\');
+        if was_continued {
+            continue;
+        }
+
+        let (timestamp, command) = match line
+            .strip_prefix(": ")
+            .and_then(|rest| rest.split_once(';'))
+        {
+            Some((meta, command)) => (
+                meta.split(':').next().and_then(|ts| ts.trim().parse().ok()),
+                command,
+            ),
+            None => (None, line),
+        };
+        if let Some(program) = program_of(command) {
+            entries.push(HistoryEntry { program, timestamp });
+        }
+    }
+    entries
+}
+
+/// bash history; `#1700000000` lines (HISTTIMEFORMAT) date the command after them
+pub fn parse_bash(content: &str) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new();
+    let mut timestamp = None;
+    for line in content.lines() {
+        if let Some(ts) = line.strip_prefix('#').and_then(|ts| ts.trim().parse().ok()) {
+            timestamp = Some(ts);
+            continue;
+        }
+        if let Some(program) = program_of(line) {
+            entries.push(HistoryEntry { program, timestamp });
+        }
+        timestamp = None;
+    }
+    entries
+}
+
+/// atuin's SQLite history (timestamps are in nanoseconds)
+fn read_atuin(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let conn =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open atuin database {}", path.display()))?;
+    let mut stmt = conn.prepare(
+        "SELECT command, timestamp FROM history WHERE deleted_at IS NULL ORDER BY timestamp",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let (command, ts_ns) = row?;
+        if let Some(program) = program_of(&command) {
+            entries.push(HistoryEntry {
+                program,
+                timestamp: Some(ts_ns / 1_000_000_000),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Wrappers whose first argument is the program that actually ran
+const COMMAND_PREFIXES: &[&str] = &[
+    "sudo", "doas", "time", "nohup", "exec", "command", "builtin", "env", "nice",
+];
+
+/// The program a command line runs: the first word after any `VAR=value`
+/// assignments and wrappers like `sudo`
+pub fn program_of(command: &str) -> Option<String> {
+    command
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
+        .find(|word| {
+            let assignment = word
+                .split_once('=')
+                .is_some_and(|(name, _)| !name.is_empty() && !name.contains('/'));
+            !assignment && !word.starts_with('-') && !COMMAND_PREFIXES.contains(word)
+        })
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zsh_extended_and_plain() {
+        let content = ": 1700000000:0;git status\n\
+                       : 1700000100:2;FOO=1 sudo make install\n\
+                       : 1700000200:0;echo one \\\n\
+                       two\n\
+                       ls -la\n";
+        let entries = parse_zsh(content);
+        let programs: Vec<_> = entries
+            .iter()
+            .map(|e| (e.program.as_str(), e.timestamp))
+            .collect();
+        assert_eq!(
+            programs,
+            vec![
+                ("git", Some(1700000000)),
+                ("make", Some(1700000100)),
+                ("echo", Some(1700000200)),
+                ("ls", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_bash_timestamps() {
+        let content = "#1700000000\ncargo build\nvim notes.txt\n";
+        let entries = parse_bash(content);
+        assert_eq!(entries[0].program, "cargo");
+        assert_eq!(entries[0].timestamp, Some(1700000000));
+        assert_eq!(entries[1].program, "vim");
+        assert_eq!(entries[1].timestamp, None);
+    }
+
+    #[test]
+    fn test_program_of_skips_wrappers() {
+        assert_eq!(program_of("sudo -E apt update").as_deref(), Some("apt"));
+        assert_eq!(
+            program_of("RUST_LOG=debug ./target/app").as_deref(),
+            Some("./target/app")
+        );
+        assert_eq!(program_of("   "), None);
+    }
+}
@@ -0,0 +1,27 @@
+//! Engine behind the `dusty` CLI: binary discovery, the usage database,
+//! configuration, and dynamic library dependency analysis.
+//!
+//! ```no_run
+//! use dusty::{Config, Database};
+//!
+//! let config = Config::load()?;
+//! let db = Database::open()?;
+//! for b in db.get_all_binaries()? {
+//!     println!("{} ({}) used {}x", b.path, config.categorize_path(&b.path), b.count);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod config;
+pub mod defaults;
+pub mod deps;
+pub mod history;
+pub mod package;
+pub mod platform;
+pub mod storage;
+
+pub use config::Config;
+pub use deps::{DepsReport, analyze_deps};
+pub use package::scan_all_binaries;
+pub use platform::{DaemonManager, DylibAnalyzer, ProcessMonitor};
+pub use storage::{BinaryRecord, Database};
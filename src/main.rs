@@ -1,61 +1,171 @@
 mod cli;
 mod commands;
-mod config;
-mod defaults;
-mod deps;
-mod package;
-mod platform;
-mod storage;
 mod ui;
 mod utils;
 
+// The engine lives in the library crate; re-import it so `crate::storage` etc. resolve
+use dusty::{config, defaults, deps, history, package, platform, storage};
+
 use clap::Parser;
 use cli::{Cli, Commands};
 use console::style;
+use ui::OutputFormat;
 
 fn main() {
     let cli = Cli::parse();
+    utils::set_no_sync(cli.no_sync);
+    platform::set_offline(cli.offline);
+    if let Some(path) = &cli.output {
+        let redirected = match cli.command.interactive_use() {
+            Some(command) => Err(anyhow::anyhow!(
+                "--output can't be used with {}: it prompts on the terminal",
+                command
+            )),
+            None => ui::redirect_stdout(path),
+        };
+        if let Err(e) = redirected {
+            eprintln!("{} {:#}", style("error:").red().bold(), e);
+            std::process::exit(1);
+        }
+    }
+    if cli.timing {
+        ui::enable_timing();
+    }
+    if cli.internal_pager {
+        ui::force_internal_pager();
+    }
+    let display = configured_display();
+    if cli.utc {
+        utils::set_display_timezone(config::DisplayTimezone::UTC);
+    } else if let Some(tz) = display.as_ref().and_then(|d| d.timezone().ok()) {
+        utils::set_display_timezone(tz);
+    }
+    if let Some(display) = display {
+        ui::set_path_shorthands(display.path_shorthands);
+    }
 
+    let total = ui::Timer::start("total");
     let result = match cli.command {
         Commands::Start => commands::cmd_start(),
         Commands::Stop => commands::cmd_stop(),
         Commands::Status { json } => commands::cmd_status(json),
-        Commands::Stats { json } => commands::cmd_stats(json),
-        Commands::Report {
-            dust,
-            low,
-            stale,
-            source,
+        Commands::Stats {
+            since_boot,
+            compare,
+            projection,
+            format,
+            json,
+        } => commands::cmd_stats(
+            OutputFormat::from_flags(format, json),
+            compare,
+            since_boot,
+            projection,
+        ),
+        Commands::Report(args) => commands::cmd_report(args),
+        Commands::Clean(args) => commands::cmd_clean(args),
+        Commands::Config {
+            edit,
+            reset,
+            default,
+            diff,
+            migrate,
+        } => commands::cmd_config(edit, reset, default, diff, migrate),
+        Commands::Dupes {
+            name,
             all,
+            format,
             json,
-            export,
-        } => commands::cmd_report(dust, low, stale, source, all, json, export),
-        Commands::Clean {
-            dry_run,
-            stale,
-            source,
-            no_trash,
-        } => commands::cmd_clean(dry_run, stale, source, no_trash),
-        Commands::Config { edit } => commands::cmd_config(edit),
-        Commands::Dupes { name, all, json } => commands::cmd_dupes(name, all, json),
-        Commands::Trash { drop, empty, json } => commands::cmd_trash(drop, empty, json),
-        Commands::Restore { name } => commands::cmd_restore(name),
+        } => commands::cmd_dupes(name, all, OutputFormat::from_flags(format, json)),
+        Commands::Trash {
+            drop,
+            empty,
+            verify,
+            gc,
+            format,
+            json,
+        } => commands::cmd_trash(
+            drop,
+            empty,
+            verify,
+            gc,
+            OutputFormat::from_flags(format, json),
+        ),
+        Commands::Restore { name, to } => commands::cmd_restore(name, to),
         Commands::Inventory { source, all, json } => commands::cmd_inventory(source, all, json),
         Commands::Deps {
             orphans,
             binary,
             refresh,
+            manager,
+            min_size,
+            graph,
+            graph_format,
+            json,
+        } => commands::cmd_deps(
+            orphans,
+            binary,
+            refresh,
+            manager,
+            min_size,
+            graph.then_some(graph_format),
+            json,
+        ),
+        Commands::Audit {
+            refresh,
+            format,
+            json,
+        } => commands::cmd_audit(refresh, OutputFormat::from_flags(format, json)),
+        Commands::Export {
+            format,
+            all,
+            source,
+        } => commands::cmd_export(format, all, source),
+        Commands::Why { name, lib, json } => commands::cmd_why(name, lib, json),
+        Commands::Size {
+            dust,
+            source,
+            apparent,
+            include_deps,
+            sort,
+            reverse,
+            format,
             json,
-        } => commands::cmd_deps(orphans, binary, refresh, json),
-        Commands::Why { name, json } => commands::cmd_why(name, json),
-        Commands::Size { dust, source, json } => commands::cmd_size(dust, source, json),
+        } => commands::cmd_size(
+            dust,
+            source,
+            apparent,
+            include_deps,
+            sort,
+            reverse,
+            OutputFormat::from_flags(format, json),
+        ),
+        Commands::Alias { repair, json } => commands::cmd_alias(repair, json),
+        Commands::Import {
+            from_history,
+            dry_run,
+        } => commands::cmd_import(from_history.unwrap_or_else(|| "auto".into()), dry_run),
+        Commands::Vacuum => commands::cmd_vacuum(),
+        Commands::Recategorize => commands::cmd_recategorize(),
         Commands::Log { lines, follow } => commands::cmd_log(lines, follow),
+        Commands::Schema { command } => commands::cmd_schema(command),
         Commands::Completions { shell } => commands::cmd_completions(shell),
-        Commands::Daemon => commands::cmd_daemon(),
+        Commands::Complete { kind, prefix } => commands::cmd_complete(kind, &prefix),
+        Commands::Daemon { ingest, log_file } => commands::cmd_daemon(ingest, log_file),
     };
+    drop(total);
 
     if let Err(e) = result {
         eprintln!("{} {}", style("error:").red().bold(), e);
         std::process::exit(1);
     }
 }
+
+/// The `[display]` settings, read without creating a config file; a bad
+/// config is reported by the command's own config load
+fn configured_display() -> Option<config::DisplayConfig> {
+    config::Config::config_path()
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|_| config::Config::load().ok())
+        .map(|config| config.display)
+}
@@ -1,7 +1,7 @@
-use crate::config::Config;
+use crate::config::{Config, ScanConfig};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 
 /// Scan all directories in PATH and return all executable binaries
@@ -13,6 +13,8 @@ pub fn scan_all_binaries() -> Result<Vec<BinaryScanResult>> {
     let config = Config::load()?;
     let mut all_binaries = Vec::new();
     let mut seen_paths: std::collections::HashSet<String> = std::collections::HashSet::new();
+    // pip --user RECORD index, built the first time a ~/.local/bin shim shows up
+    let mut pip_records: Option<HashMap<String, String>> = None;
 
     // Get directories to scan from config
     let scan_dirs = config.get_scan_dirs();
@@ -38,7 +40,7 @@ pub fn scan_all_binaries() -> Result<Vec<BinaryScanResult>> {
                 }
 
                 // Check if executable
-                if !is_executable(&bin_path) {
+                if !is_executable(&bin_path, &config.scan) {
                     continue;
                 }
 
@@ -78,6 +80,13 @@ pub fn scan_all_binaries() -> Result<Vec<BinaryScanResult>> {
                     source.clone()
                 };
 
+                // pipx/pip shims: the owning distribution is what `uninstall` needs
+                let (pkg_name, refined_source) =
+                    match python_owner(&bin_path, &bin_name, &mut pip_records) {
+                        Some((owner_source, pkg)) => (pkg, owner_source.to_string()),
+                        None => (pkg_name, refined_source),
+                    };
+
                 // If it's a symlink, resolve to get the real path
                 // (eslogger reports resolved paths, so we need this mapping)
                 let resolved = fs::canonicalize(&bin_path)
@@ -107,17 +116,36 @@ fn expand_tilde(path: &str) -> PathBuf {
     PathBuf::from(path)
 }
 
-/// Check if a file is executable
-fn is_executable(path: &Path) -> bool {
+/// Check if a file is executable: the execute bit on Unix, a `PATHEXT`
+/// extension on Windows, or one of `[scan] executable_extensions`
+fn is_executable(path: &Path, scan: &ScanConfig) -> bool {
     if let Ok(metadata) = fs::metadata(path) {
-        let permissions = metadata.permissions();
-        // Check if any execute bit is set
-        permissions.mode() & 0o111 != 0
+        scan.has_executable_extension(path) || has_exec_permission(path, &metadata)
     } else {
         false
     }
 }
 
+#[cfg(unix)]
+fn has_exec_permission(_path: &Path, metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Check if any execute bit is set
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn has_exec_permission(path: &Path, _metadata: &fs::Metadata) -> bool {
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            pathext
+                .split(';')
+                .any(|p| p.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        })
+}
+
 /// Extract package name from a Cellar path (e.g. ".../Cellar/python@3.13/3.13.11_1/..." → "python@3.13")
 fn extract_cellar_package(path: &str) -> Option<String> {
     let after_cellar = path.split("Cellar/").nth(1)?;
@@ -128,24 +156,481 @@ fn extract_cellar_package(path: &str) -> Option<String> {
     Some(pkg.to_string())
 }
 
+/// Extract an npm package from a global bin's target (".../node_modules/@scope/cli/bin/x.js"
+/// → "@scope/cli"); scoped names keep their scope so `npm uninstall -g` can find them.
+fn extract_node_modules_package(path: &str) -> Option<String> {
+    let after = path.rsplit_once("node_modules/")?.1;
+    let mut parts = after.split('/');
+    let first = parts.next().filter(|p| !p.is_empty())?;
+    if first.starts_with('@') {
+        let name = parts.next().filter(|p| !p.is_empty())?;
+        Some(format!("{}/{}", first, name))
+    } else {
+        Some(first.to_string())
+    }
+}
+
+/// Extract a pipx venv name from a shim target or interpreter path
+/// (".../pipx/venvs/black/bin/black" → "black")
+fn extract_pipx_package(path: &str) -> Option<String> {
+    let after = path.split("pipx/venvs/").nth(1)?;
+    let pkg = after.split('/').next()?;
+    if pkg.is_empty() {
+        return None;
+    }
+    Some(pkg.to_string())
+}
+
+/// Derivation name of a Nix store path, without hash or version
+/// ("/nix/store/<hash>-ripgrep-14.1.0/bin/rg" → "ripgrep"). Profile
+/// environments ("<hash>-user-environment") aren't packages and yield None.
+fn extract_nix_package(path: &str) -> Option<String> {
+    let entry = path.strip_prefix("/nix/store/")?.split('/').next()?;
+    let (_hash, name) = entry.split_once('-')?;
+    // The version starts at the first "-<digit>"
+    let end = name
+        .match_indices('-')
+        .find(|(i, _)| name[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        .map_or(name.len(), |(i, _)| i);
+    let pkg = &name[..end];
+    if pkg.is_empty() || pkg == "user-environment" {
+        return None;
+    }
+    Some(pkg.to_string())
+}
+
+/// Data-directory markers of the asdf and mise version managers
+const VERSION_MANAGER_ROOTS: &[&str] = &["/.asdf/", "/mise/"];
+
+/// `tool@version` for a path under an asdf or mise data directory: installs
+/// ("~/.asdf/installs/nodejs/20.1.0/bin/node" → "nodejs@20.1.0") directly, shims
+/// via the installs that provide them. A shim backed by several versions has no
+/// single `tool@version` to uninstall, so it resolves to `None`.
+fn extract_version_manager_package(path: &str) -> Option<String> {
+    let (root, rest) = VERSION_MANAGER_ROOTS.iter().find_map(|marker| {
+        let idx = path.find(marker)?;
+        Some((&path[..idx + marker.len()], &path[idx + marker.len()..]))
+    })?;
+
+    if let Some(install) = rest.strip_prefix("installs/") {
+        let mut parts = install.split('/');
+        let tool = parts.next().filter(|p| !p.is_empty())?;
+        let version = parts.next().filter(|p| !p.is_empty())?;
+        return Some(format!("{}@{}", tool, version));
+    }
+
+    let shim = rest.strip_prefix("shims/")?;
+    // asdf shims list their providers; mise shims are symlinks to mise itself
+    let mut providers = shim_script_head(path)
+        .map(|content| asdf_shim_providers(&content))
+        .unwrap_or_default();
+    if providers.is_empty() {
+        providers = shim_install_providers(Path::new(root), shim);
+    }
+    match providers.as_slice() {
+        [] => None,
+        [(tool, version)] => Some(format!("{}@{}", tool, version)),
+        _ => None,
+    }
+}
+
+/// How much of a shim `shim_script_head` reads; asdf's header comments come first
+const SHIM_HEAD_BYTES: usize = 4096;
+
+/// The opening lines of an asdf shim script, where its `# asdf-plugin:`
+/// comments are. Symlinks (mise shims point at the multi-MB mise binary)
+/// aren't read, and a line cut off at the limit is dropped.
+fn shim_script_head(path: &str) -> Option<String> {
+    use std::io::Read;
+
+    if fs::symlink_metadata(path).ok()?.file_type().is_symlink() {
+        return None;
+    }
+    let mut head = Vec::with_capacity(SHIM_HEAD_BYTES);
+    fs::File::open(path)
+        .ok()?
+        .take(SHIM_HEAD_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    if head.len() == SHIM_HEAD_BYTES {
+        head.truncate(head.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1));
+    }
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// (plugin, version) pairs from an asdf shim's `# asdf-plugin: nodejs 20.1.0` lines
+fn asdf_shim_providers(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("# asdf-plugin: "))
+        .filter_map(|rest| {
+            let mut fields = rest.split_whitespace();
+            Some((fields.next()?.to_string(), fields.next()?.to_string()))
+        })
+        .collect()
+}
+
+/// (tool, version) of every install under `root` with a `bin/<name>`
+fn shim_install_providers(root: &Path, name: &str) -> Vec<(String, String)> {
+    let mut providers = Vec::new();
+    let Ok(tools) = fs::read_dir(root.join("installs")) else {
+        return providers;
+    };
+    for tool in tools.flatten() {
+        let Ok(versions) = fs::read_dir(tool.path()) else {
+            continue;
+        };
+        for version in versions.flatten() {
+            if version.path().join("bin").join(name).exists() {
+                providers.push((
+                    tool.file_name().to_string_lossy().to_string(),
+                    version.file_name().to_string_lossy().to_string(),
+                ));
+            }
+        }
+    }
+    providers.sort();
+    providers
+}
+
+/// Whether a path belongs to Nix: the immutable store or a profile linking
+/// into it. Nix removes these itself (`nix-env --uninstall`, garbage
+/// collection), so dusty must never delete or trash them directly.
+pub fn is_nix_managed(path: &str) -> bool {
+    path.starts_with("/nix/")
+        || path.contains("/.nix-profile/")
+        || path.ends_with("/.nix-profile")
+        || fs::canonicalize(path).is_ok_and(|p| p.starts_with("/nix/store"))
+}
+
+/// Directories `go install` writes to: `$GOBIN`, or `bin` under each
+/// `$GOPATH` entry (default `~/go`)
+fn go_bin_dirs() -> Vec<PathBuf> {
+    if let Some(gobin) = std::env::var_os("GOBIN").filter(|v| !v.is_empty()) {
+        return vec![PathBuf::from(gobin)];
+    }
+    match std::env::var_os("GOPATH").filter(|v| !v.is_empty()) {
+        Some(gopath) => std::env::split_paths(&gopath)
+            .map(|p| p.join("bin"))
+            .collect(),
+        None => dirs::home_dir()
+            .map(|h| vec![h.join("go").join("bin")])
+            .unwrap_or_default(),
+    }
+}
+
+/// Whether a binary sits directly in a `go install` target directory. These
+/// are single self-contained files, so removing one means removing the file.
+pub fn is_go_install(path: &str) -> bool {
+    Path::new(path)
+        .parent()
+        .is_some_and(|dir| go_bin_dirs().iter().any(|d| d == dir))
+}
+
+/// Provenance embedded in a binary by the Go toolchain (what `go version -m` prints)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoBuildInfo {
+    /// Import path of the main package, what `go install` was given
+    /// (e.g. "golang.org/x/tools/cmd/goimports")
+    pub path: String,
+    /// Module containing it (e.g. "golang.org/x/tools")
+    pub module: String,
+    /// Module version, "(devel)" for a local build
+    pub version: String,
+}
+
+impl GoBuildInfo {
+    /// Command that installs this exact build again
+    pub fn install_cmd(&self) -> String {
+        match self.version.as_str() {
+            "" | "(devel)" => format!("go install {}@latest", self.path),
+            version => format!("go install {}@{}", self.path, version),
+        }
+    }
+}
+
+const GO_BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+
+/// Read the Go build info from an ELF or Mach-O binary's buildinfo section.
+/// Only the inline format of Go 1.18+ is understood.
+pub fn go_build_info(path: &Path) -> Option<GoBuildInfo> {
+    let mut file = fs::File::open(path).ok()?;
+    let (offset, size) = go_buildinfo_section(&mut file)?;
+    let data = read_at(&mut file, offset, size.min(1 << 20) as usize)?;
+    parse_go_buildinfo(&data)
+}
+
+fn read_at(file: &mut fs::File, offset: u64, len: usize) -> Option<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut buf = vec![0u8; len];
+    file.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+fn le_u16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u64(b: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(b.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// File offset and size of `.go.buildinfo` (ELF) or `__go_buildinfo`
+/// (Mach-O), for little-endian 64-bit binaries
+fn go_buildinfo_section(file: &mut fs::File) -> Option<(u64, u64)> {
+    let header = read_at(file, 0, 64)?;
+    if header.starts_with(b"\x7fELF") {
+        // ELFCLASS64, little-endian
+        if header[4] != 2 || header[5] != 1 {
+            return None;
+        }
+        let shoff = le_u64(&header, 0x28)?;
+        let shentsize = le_u16(&header, 0x3a)? as usize;
+        let shnum = le_u16(&header, 0x3c)? as usize;
+        let shstrndx = le_u16(&header, 0x3e)? as usize;
+        if shentsize < 64 || shnum == 0 || shstrndx >= shnum || shentsize * shnum > 1 << 20 {
+            return None;
+        }
+        let sections = read_at(file, shoff, shentsize * shnum)?;
+        let section = |i: usize| {
+            let s = &sections[i * shentsize..];
+            Some((le_u32(s, 0)? as usize, le_u64(s, 24)?, le_u64(s, 32)?))
+        };
+        let (_, strtab_offset, strtab_size) = section(shstrndx)?;
+        let names = read_at(file, strtab_offset, strtab_size.min(1 << 20) as usize)?;
+        (0..shnum).find_map(|i| {
+            let (name, offset, size) = section(i)?;
+            let end = names.get(name..)?.iter().position(|&b| b == 0)?;
+            (&names[name..name + end] == b".go.buildinfo").then_some((offset, size))
+        })
+    } else if header.starts_with(&[0xcf, 0xfa, 0xed, 0xfe]) {
+        const LC_SEGMENT_64: u32 = 0x19;
+        let ncmds = le_u32(&header, 16)? as usize;
+        let sizeofcmds = le_u32(&header, 20)? as usize;
+        if sizeofcmds > 1 << 20 {
+            return None;
+        }
+        let cmds = read_at(file, 32, sizeofcmds)?;
+        let mut at = 0;
+        for _ in 0..ncmds {
+            let cmd = le_u32(&cmds, at)?;
+            let cmdsize = le_u32(&cmds, at + 4)? as usize;
+            if cmd == LC_SEGMENT_64 {
+                let nsects = le_u32(&cmds, at + 64)? as usize;
+                for i in 0..nsects {
+                    let s = at + 72 + i * 80;
+                    let name = cmds.get(s..s + 16)?;
+                    if name.starts_with(b"__go_buildinfo") {
+                        return Some((le_u32(&cmds, s + 48)? as u64, le_u64(&cmds, s + 40)?));
+                    }
+                }
+            }
+            if cmdsize == 0 {
+                return None;
+            }
+            at += cmdsize;
+        }
+        None
+    } else {
+        None
+    }
+}
+
+/// Decode a buildinfo blob: the 32-byte header, then the Go version and the
+/// module info as varint-length-prefixed strings
+fn parse_go_buildinfo(data: &[u8]) -> Option<GoBuildInfo> {
+    const FLAGS_VERSION_INLINE: u8 = 0x2;
+
+    fn uvarint(data: &[u8]) -> Option<(usize, &[u8])> {
+        let mut value = 0usize;
+        for (i, &b) in data.iter().enumerate().take(10) {
+            value |= ((b & 0x7f) as usize) << (7 * i);
+            if b & 0x80 == 0 {
+                return Some((value, &data[i + 1..]));
+            }
+        }
+        None
+    }
+    fn string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+        let (len, rest) = uvarint(data)?;
+        Some((rest.get(..len)?, rest.get(len..)?))
+    }
+
+    if !data.starts_with(GO_BUILDINFO_MAGIC) || data.get(15)? & FLAGS_VERSION_INLINE == 0 {
+        return None;
+    }
+    let (_go_version, rest) = string(data.get(32..)?)?;
+    let (modinfo, _) = string(rest)?;
+    // The module info is wrapped in two 16-byte sentinels
+    let modinfo = modinfo.get(16..modinfo.len().checked_sub(16)?)?;
+    parse_go_modinfo(&String::from_utf8_lossy(modinfo))
+}
+
+/// `path\t<pkg>` and `mod\t<module>\t<version>\t<sum>` from `go version -m` style text
+fn parse_go_modinfo(modinfo: &str) -> Option<GoBuildInfo> {
+    let mut path = None;
+    let mut module = None;
+    for line in modinfo.lines() {
+        let mut fields = line.split('\t');
+        match fields.next() {
+            Some("path") => path = fields.next().map(str::to_string),
+            Some("mod") => {
+                module = fields
+                    .next()
+                    .map(|m| (m.to_string(), fields.next().unwrap_or("").to_string()))
+            }
+            _ => {}
+        }
+    }
+    let path = path.filter(|p| !p.is_empty())?;
+    let (module, version) = module.unwrap_or_else(|| (path.clone(), String::new()));
+    Some(GoBuildInfo {
+        path,
+        module,
+        version,
+    })
+}
+
+/// Interpreter named on a script's `#!` line, if any
+fn shebang_target(path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let mut head = [0u8; 256];
+    let n = fs::File::open(path).ok()?.read(&mut head).ok()?;
+    let line = head[..n]
+        .strip_prefix(b"#!")?
+        .split(|&b| b == b'\n')
+        .next()?;
+    let line = String::from_utf8_lossy(line);
+    line.split_whitespace().next().map(str::to_string)
+}
+
+/// Distribution name from a dist-info directory ("black-24.1.0.dist-info" → "black")
+fn dist_info_name(dir_name: &str) -> Option<&str> {
+    let stem = dir_name.strip_suffix(".dist-info")?;
+    let name = stem.split('-').next()?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// Scripts a RECORD file installs into the user bin dir. site-packages sits at
+/// ~/.local/lib/pythonX.Y/site-packages, so those entries read "../../../bin/<name>".
+fn record_scripts(record: &str) -> impl Iterator<Item = &str> {
+    record.lines().filter_map(|line| {
+        let path = line.split(',').next()?;
+        let name = path.strip_prefix("../../../bin/")?;
+        (!name.is_empty() && !name.contains('/')).then_some(name)
+    })
+}
+
+/// Map each `pip install --user` script to its distribution by reading every
+/// ~/.local/lib/python*/site-packages/*.dist-info/RECORD
+fn pip_user_records() -> HashMap<String, String> {
+    let mut owners = HashMap::new();
+    let lib = expand_tilde("~/.local/lib");
+    let Ok(pythons) = fs::read_dir(&lib) else {
+        return owners;
+    };
+    for python in pythons.flatten() {
+        let Ok(dists) = fs::read_dir(python.path().join("site-packages")) else {
+            continue;
+        };
+        for dist in dists.flatten() {
+            let dir_name = dist.file_name().to_string_lossy().to_string();
+            let Some(name) = dist_info_name(&dir_name) else {
+                continue;
+            };
+            let Ok(record) = fs::read_to_string(dist.path().join("RECORD")) else {
+                continue;
+            };
+            for script in record_scripts(&record) {
+                owners.insert(script.to_string(), name.to_string());
+            }
+        }
+    }
+    owners
+}
+
+/// Resolve a Python entry-point shim to ("pipx" | "pip", package). pipx shims are
+/// symlinks into (or scripts run by) a venv named after the package; pip --user
+/// shims are looked up in the installed distributions' RECORD files.
+fn python_owner(
+    bin_path: &Path,
+    bin_name: &str,
+    pip_records: &mut Option<HashMap<String, String>>,
+) -> Option<(&'static str, String)> {
+    if let Ok(target) = fs::read_link(bin_path)
+        && let Some(pkg) = extract_pipx_package(&target.to_string_lossy())
+    {
+        return Some(("pipx", pkg));
+    }
+
+    // Only read scripts in the user bin dir; opening every binary on PATH is too slow
+    if bin_path.parent() != Some(expand_tilde("~/.local/bin").as_path()) {
+        return None;
+    }
+    if let Some(pkg) = shebang_target(bin_path).and_then(|t| extract_pipx_package(&t)) {
+        return Some(("pipx", pkg));
+    }
+    pip_records
+        .get_or_insert_with(pip_user_records)
+        .get(bin_name)
+        .map(|pkg| ("pip", pkg.clone()))
+}
+
 /// Try to determine package name from binary path.
-/// Checks Homebrew Cellar symlinks, then install root anchors, then falls back to binary name.
+/// Checks Homebrew Cellar and npm node_modules symlinks, then install root anchors,
+/// then falls back to binary name.
 pub fn get_package_name(bin_path: &Path, default_name: &str) -> String {
+    // Nix profiles link through a user-environment, so follow every hop into the store
+    let path_str = bin_path.to_string_lossy();
+    if path_str.starts_with("/nix/store/") || path_str.contains("/.nix-profile/") {
+        let resolved = fs::canonicalize(bin_path).unwrap_or_else(|_| bin_path.to_path_buf());
+        if let Some(pkg) = extract_nix_package(&resolved.to_string_lossy()) {
+            return pkg;
+        }
+    }
+
     // For Homebrew, resolve symlink to get package name
     if let Ok(resolved) = fs::read_link(bin_path) {
         let resolved_str = resolved.to_string_lossy();
 
-        if let Some(pkg) = extract_cellar_package(&resolved_str) {
+        if let Some(pkg) = extract_cellar_package(&resolved_str)
+            .or_else(|| extract_node_modules_package(&resolved_str))
+            .or_else(|| extract_pipx_package(&resolved_str))
+        {
             return pkg;
         }
     }
 
     // Also check the path itself — daemon-recorded paths are already resolved
-    let path_str = bin_path.to_string_lossy();
-    if let Some(pkg) = extract_cellar_package(&path_str) {
+    if let Some(pkg) = extract_cellar_package(&path_str)
+        .or_else(|| extract_node_modules_package(&path_str))
+        .or_else(|| extract_pipx_package(&path_str))
+        .or_else(|| extract_version_manager_package(&path_str))
+    {
         return pkg;
     }
 
+    // Unresolved asdf/mise shims keep their own name; the `~/` anchor below
+    // would otherwise file them all under ".asdf"
+    if VERSION_MANAGER_ROOTS.iter().any(|m| path_str.contains(m)) {
+        return default_name.to_string();
+    }
+
+    // `go install` binaries carry the import path they were built from; the
+    // `~/` anchor below would otherwise call every one of them "go"
+    if is_go_install(&path_str) {
+        return go_build_info(bin_path)
+            .map(|info| info.path)
+            .unwrap_or_else(|| default_name.to_string());
+    }
+
     // For downloaded software in well-known anchors (e.g. /opt/oss-cad-suite/bin/yosys),
     // use the install root directory name as the package name.
     let home = dirs::home_dir()
@@ -170,6 +655,61 @@ pub fn get_package_name(bin_path: &Path, default_name: &str) -> String {
     default_name.to_string()
 }
 
+/// Split a versioned package name into its family base name and numeric version.
+/// Recognizes "python@3.13", "node-18.2.0", "gcc_13" and dotted suffixes like "lua5.4";
+/// bare trailing digits ("python3", "x264") are treated as part of the name.
+pub fn split_version(name: &str) -> (&str, Option<Vec<u64>>) {
+    let version_start = name
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit() || *c == '.' || *c == '_')
+        .last()
+        .map(|(i, _)| i);
+    let Some(mut start) = version_start else {
+        return (name, None);
+    };
+    // Version must begin with a digit ("foo_.1" has no version)
+    while start < name.len() && !name.as_bytes()[start].is_ascii_digit() {
+        start += 1;
+    }
+    if start == 0 || start >= name.len() {
+        return (name, None);
+    }
+
+    let (prefix, version) = name.split_at(start);
+    let base = match prefix.strip_suffix(['@', '-', '_']) {
+        Some(base) => base,
+        None if version.contains('.') => prefix,
+        None => return (name, None),
+    };
+    if base.is_empty() {
+        return (name, None);
+    }
+
+    let parts: Option<Vec<u64>> = version
+        .split(['.', '_'])
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect();
+    match parts {
+        Some(parts) => (base, Some(parts)),
+        None => (name, None),
+    }
+}
+
+/// Base name a package folds into under `dusty report --merge-versions`.
+/// With a `pattern`, its match at the end of the name is stripped; otherwise
+/// the suffix `split_version` recognizes is. Names that would strip to nothing stay whole.
+pub fn version_base<'a>(name: &'a str, pattern: Option<&regex::Regex>) -> &'a str {
+    match pattern {
+        Some(re) => re
+            .find_iter(name)
+            .find(|m| m.end() == name.len() && m.start() > 0)
+            .map_or(name, |m| &name[..m.start()]),
+        None => split_version(name).0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,6 +737,195 @@ mod tests {
         assert_eq!(get_package_name(path, "pip"), "python@3.13");
     }
 
+    #[test]
+    fn test_extract_node_modules_package() {
+        assert_eq!(
+            extract_node_modules_package("../lib/node_modules/@angular/cli/bin/ng.js"),
+            Some("@angular/cli".to_string())
+        );
+        assert_eq!(
+            extract_node_modules_package("../lib/node_modules/typescript/bin/tsc"),
+            Some("typescript".to_string())
+        );
+        // Nested deps resolve to the innermost package
+        assert_eq!(
+            extract_node_modules_package("/x/node_modules/a/node_modules/@s/b/cli.js"),
+            Some("@s/b".to_string())
+        );
+        assert_eq!(extract_node_modules_package("/x/node_modules/@scope"), None);
+        assert_eq!(extract_node_modules_package("/usr/bin/node"), None);
+    }
+
+    #[test]
+    fn test_get_package_name_npm_scoped_global() {
+        let prefix = std::env::temp_dir().join(format!("dusty-npm-{}", std::process::id()));
+        let target = prefix.join("lib/node_modules/@vendor/cli/bin");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("cli.js"), "").unwrap();
+        fs::create_dir_all(prefix.join("bin")).unwrap();
+        let link = prefix.join("bin/vendor-cli");
+        std::os::unix::fs::symlink("../lib/node_modules/@vendor/cli/bin/cli.js", &link).unwrap();
+
+        let name = get_package_name(&link, "vendor-cli");
+        fs::remove_dir_all(&prefix).unwrap();
+        assert_eq!(name, "@vendor/cli");
+    }
+
+    #[test]
+    fn test_extract_pipx_package() {
+        assert_eq!(
+            extract_pipx_package("/home/me/.local/share/pipx/venvs/black/bin/black"),
+            Some("black".to_string())
+        );
+        assert_eq!(
+            extract_pipx_package("/home/me/.local/pipx/venvs/poetry/bin/python"),
+            Some("poetry".to_string())
+        );
+        assert_eq!(extract_pipx_package("/home/me/.local/bin/black"), None);
+    }
+
+    #[test]
+    fn test_extract_version_manager_package() {
+        assert_eq!(
+            extract_version_manager_package("/home/u/.asdf/installs/nodejs/20.1.0/bin/node"),
+            Some("nodejs@20.1.0".to_string())
+        );
+        assert_eq!(
+            extract_version_manager_package(
+                "/home/u/.local/share/mise/installs/python/3.12.1/bin/python3"
+            ),
+            Some("python@3.12.1".to_string())
+        );
+        assert_eq!(
+            extract_version_manager_package("/home/u/.asdf/shims/does-not-exist"),
+            None
+        );
+        assert_eq!(extract_version_manager_package("/usr/bin/node"), None);
+    }
+
+    #[test]
+    fn test_multi_version_shim_has_no_package() {
+        let root = std::env::temp_dir().join(format!("dusty-asdf-{}", std::process::id()));
+        let shims = root.join(".asdf/shims");
+        fs::create_dir_all(&shims).unwrap();
+        let single = shims.join("npx");
+        let multi = shims.join("node");
+        fs::write(
+            &single,
+            "#!/usr/bin/env bash\n# asdf-plugin: nodejs 20.1.0\n",
+        )
+        .unwrap();
+        fs::write(
+            &multi,
+            "#!/usr/bin/env bash\n# asdf-plugin: nodejs 18.12.0\n# asdf-plugin: nodejs 20.1.0\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract_version_manager_package(&single.to_string_lossy()),
+            Some("nodejs@20.1.0".to_string())
+        );
+        assert_eq!(
+            extract_version_manager_package(&multi.to_string_lossy()),
+            None
+        );
+        assert_eq!(get_package_name(&multi, "node"), "node");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_shim_script_head() {
+        let dir = std::env::temp_dir().join(format!("dusty-shim-head-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Only the head of a large file is read, minus the line cut at the limit
+        let big = dir.join("node");
+        let mut content = b"#!/usr/bin/env bash\n# asdf-plugin: nodejs 20.1.0\n".to_vec();
+        content.resize(SHIM_HEAD_BYTES - 4, b'#');
+        content.extend_from_slice(b"\n# asdf-plugin: nodejs 18.12.0\n");
+        content.extend(std::iter::repeat_n(0xff, 1 << 20));
+        fs::write(&big, &content).unwrap();
+        let head = shim_script_head(&big.to_string_lossy()).unwrap();
+        assert!(head.len() < SHIM_HEAD_BYTES);
+        assert_eq!(
+            asdf_shim_providers(&head),
+            vec![("nodejs".to_string(), "20.1.0".to_string())]
+        );
+
+        // A symlinked shim (mise) isn't read at all
+        let link = dir.join("npx");
+        std::os::unix::fs::symlink(&big, &link).unwrap();
+        assert_eq!(shim_script_head(&link.to_string_lossy()), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_asdf_shim_providers() {
+        let shim = "#!/usr/bin/env bash\n\
+                    # asdf-plugin: nodejs 18.12.0\n\
+                    # asdf-plugin: nodejs 20.1.0\n\
+                    exec /home/u/.asdf/bin/asdf exec \"node\" \"$@\"\n";
+        assert_eq!(
+            asdf_shim_providers(shim),
+            vec![
+                ("nodejs".to_string(), "18.12.0".to_string()),
+                ("nodejs".to_string(), "20.1.0".to_string()),
+            ]
+        );
+        assert!(asdf_shim_providers("#!/bin/sh\nexec mise x -- node\n").is_empty());
+    }
+
+    #[test]
+    fn test_extract_nix_package() {
+        assert_eq!(
+            extract_nix_package(
+                "/nix/store/0c8ql9mhqa1yd5ibqs3jjwbzrq9fmijx-ripgrep-14.1.0/bin/rg"
+            ),
+            Some("ripgrep".to_string())
+        );
+        assert_eq!(
+            extract_nix_package(
+                "/nix/store/0c8ql9mhqa1yd5ibqs3jjwbzrq9fmijx-python3-3.11.6/bin/python3"
+            ),
+            Some("python3".to_string())
+        );
+        assert_eq!(
+            extract_nix_package(
+                "/nix/store/0c8ql9mhqa1yd5ibqs3jjwbzrq9fmijx-git-crypt-0.7.0/bin/git-crypt"
+            ),
+            Some("git-crypt".to_string())
+        );
+        assert_eq!(
+            extract_nix_package(
+                "/nix/store/0c8ql9mhqa1yd5ibqs3jjwbzrq9fmijx-user-environment/bin/rg"
+            ),
+            None
+        );
+        assert_eq!(extract_nix_package("/usr/local/bin/rg"), None);
+        assert!(is_nix_managed("/home/me/.nix-profile/bin/rg"));
+        assert!(!is_nix_managed("/usr/local/bin/rg"));
+    }
+
+    #[test]
+    fn test_pip_record_scripts() {
+        let record = "black/__init__.py,sha256=abc,123\n\
+                      ../../../bin/black,sha256=def,220\n\
+                      ../../../bin/blackd,sha256=ghi,221\n\
+                      black-24.1.0.dist-info/RECORD,,\n";
+        assert_eq!(
+            record_scripts(record).collect::<Vec<_>>(),
+            vec!["black", "blackd"]
+        );
+        assert_eq!(dist_info_name("black-24.1.0.dist-info"), Some("black"));
+        assert_eq!(
+            dist_info_name("ruamel.yaml-0.18.5.dist-info"),
+            Some("ruamel.yaml")
+        );
+        assert_eq!(dist_info_name("black-24.1.0.egg-info"), None);
+    }
+
     #[test]
     fn test_get_package_name_install_root() {
         let path = Path::new("/opt/oss-cad-suite/bin/yosys");
@@ -209,6 +938,124 @@ mod tests {
         assert_eq!(get_package_name(path, "mytool"), "mytool");
     }
 
+    #[test]
+    fn test_parse_go_buildinfo_inline() {
+        let modinfo = "path\tgolang.org/x/tools/cmd/goimports\n\
+                       mod\tgolang.org/x/tools\tv0.21.0\th1:abc=\n\
+                       dep\tgolang.org/x/mod\tv0.17.0\th1:def=\n";
+        let sentinel = [0u8; 16];
+        let wrapped = [&sentinel[..], modinfo.as_bytes(), &sentinel[..]].concat();
+
+        let mut data = GO_BUILDINFO_MAGIC.to_vec();
+        data.extend([8, 0x2]);
+        data.resize(32, 0);
+        data.push(8);
+        data.extend(b"go1.22.3");
+        // Lengths past 127 take a second varint byte
+        assert!(wrapped.len() > 127);
+        data.extend([
+            (wrapped.len() as u8 & 0x7f) | 0x80,
+            (wrapped.len() >> 7) as u8,
+        ]);
+        data.extend(&wrapped);
+
+        let info = parse_go_buildinfo(&data).unwrap();
+        assert_eq!(info.path, "golang.org/x/tools/cmd/goimports");
+        assert_eq!(info.module, "golang.org/x/tools");
+        assert_eq!(
+            info.install_cmd(),
+            "go install golang.org/x/tools/cmd/goimports@v0.21.0"
+        );
+
+        // Pre-1.18 pointer-based headers aren't understood
+        data[15] = 0;
+        assert_eq!(parse_go_buildinfo(&data), None);
+    }
+
+    /// A little-endian ELF64 file with a null section, `.shstrtab`,
+    /// `.go.buildinfo` (at 0x1000, 0x20 bytes long) and empty sections up to `shnum`
+    fn minimal_elf64(shentsize: u16, shnum: u16) -> Vec<u8> {
+        let names = b"\0.shstrtab\0.go.buildinfo\0";
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[0x28..0x30].copy_from_slice(&128u64.to_le_bytes());
+        elf[0x3a..0x3c].copy_from_slice(&shentsize.to_le_bytes());
+        elf[0x3c..0x3e].copy_from_slice(&shnum.to_le_bytes());
+        elf[0x3e..0x40].copy_from_slice(&1u16.to_le_bytes());
+        elf.extend(names);
+        elf.resize(128, 0);
+        for (name, offset, size) in [
+            (0u32, 0u64, 0u64),
+            (1, 64, names.len() as u64),
+            (11, 0x1000, 0x20),
+        ] {
+            let mut section = vec![0u8; shentsize as usize];
+            section[..4].copy_from_slice(&name.to_le_bytes());
+            section[24..32].copy_from_slice(&offset.to_le_bytes());
+            section[32..40].copy_from_slice(&size.to_le_bytes());
+            elf.extend(section);
+        }
+        elf.resize(128 + shentsize as usize * shnum as usize, 0);
+        elf
+    }
+
+    #[test]
+    fn test_go_buildinfo_section_elf() {
+        let dir = std::env::temp_dir().join(format!("dusty-go-elf-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tool");
+
+        fs::write(&path, minimal_elf64(64, 3)).unwrap();
+        let mut file = fs::File::open(&path).unwrap();
+        assert_eq!(go_buildinfo_section(&mut file), Some((0x1000, 0x20)));
+
+        // A section table over 1 MiB is refused rather than read into memory
+        fs::write(&path, minimal_elf64(512, 4096)).unwrap();
+        let mut file = fs::File::open(&path).unwrap();
+        assert_eq!(go_buildinfo_section(&mut file), None);
+
+        // Neither ELF nor Mach-O
+        fs::write(&path, [b'#'; 64]).unwrap();
+        let mut file = fs::File::open(&path).unwrap();
+        assert_eq!(go_buildinfo_section(&mut file), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_go_modinfo_devel_build() {
+        let info =
+            parse_go_modinfo("path\texample.com/tool\nmod\texample.com/tool\t(devel)\t\n").unwrap();
+        assert_eq!(info.install_cmd(), "go install example.com/tool@latest");
+        assert_eq!(parse_go_modinfo("dep\texample.com/lib\tv1.0.0\n"), None);
+    }
+
+    #[test]
+    fn test_split_version() {
+        assert_eq!(split_version("python@3.13"), ("python", Some(vec![3, 13])));
+        assert_eq!(split_version("node-18.2.0"), ("node", Some(vec![18, 2, 0])));
+        assert_eq!(split_version("openssl@3"), ("openssl", Some(vec![3])));
+        assert_eq!(split_version("lua5.4"), ("lua", Some(vec![5, 4])));
+        assert_eq!(split_version("gcc_13"), ("gcc", Some(vec![13])));
+        assert_eq!(split_version("3.11.4_1"), ("3.11.4_1", None));
+        assert_eq!(split_version("python3"), ("python3", None));
+        assert_eq!(split_version("x264"), ("x264", None));
+        assert_eq!(split_version("git"), ("git", None));
+    }
+
+    #[test]
+    fn test_version_base() {
+        assert_eq!(version_base("python@3.12", None), "python");
+        assert_eq!(version_base("node-20", None), "node");
+        assert_eq!(version_base("python3", None), "python3");
+        assert_eq!(version_base("git", None), "git");
+
+        let re = regex::Regex::new(r"-v\d+$").unwrap();
+        assert_eq!(version_base("tool-v2", Some(&re)), "tool");
+        assert_eq!(version_base("python@3.12", Some(&re)), "python@3.12");
+        assert_eq!(version_base("-v2", Some(&re)), "-v2");
+    }
+
     #[test]
     fn test_expand_tilde() {
         assert_eq!(expand_tilde("/usr/bin"), PathBuf::from("/usr/bin"));
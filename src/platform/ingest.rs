@@ -0,0 +1,225 @@
+//! Exec events from another producer (`[daemon] backend = "stdin"` or
+//! `dusty daemon --ingest <fifo>`) instead of a system tracer
+
+use super::{ExecEvent, ProcessMonitor, daemon_log};
+use crate::config::LogLevel;
+use anyhow::{Context, Result};
+use std::io::BufRead;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Reads newline-delimited exec events: an absolute executable path,
+/// optionally followed by a tab and the source to file it under.
+///
+/// Without a path the events come from stdin and the monitor disconnects at
+/// EOF. A named pipe is created if missing and reopened whenever its writer
+/// goes away, so producers can come and go.
+pub struct IngestMonitor {
+    fifo: Option<PathBuf>,
+    stop_flag: Arc<AtomicBool>,
+    parse_errors: Arc<AtomicU64>,
+}
+
+impl IngestMonitor {
+    pub fn with_fifo(fifo: Option<PathBuf>) -> Self {
+        Self {
+            fifo,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            parse_errors: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl ProcessMonitor for IngestMonitor {
+    fn new() -> Self {
+        Self::with_fifo(None)
+    }
+
+    fn start(&mut self) -> Result<Receiver<ExecEvent>> {
+        let (tx, rx) = mpsc::channel();
+        let stop_flag = self.stop_flag.clone();
+        let parse_errors = self.parse_errors.clone();
+
+        let Some(fifo) = self.fifo.clone() else {
+            thread::spawn(move || {
+                let stdin = std::io::stdin().lock();
+                forward_lines(stdin, &tx, &stop_flag, &parse_errors);
+            });
+            return Ok(rx);
+        };
+
+        match std::fs::symlink_metadata(&fifo) {
+            Ok(meta) if meta.file_type().is_fifo() => {}
+            // Re-reading a regular file after every EOF would count its execs again and again
+            Ok(_) => anyhow::bail!(
+                "{} exists but is not a FIFO; pass a new path or an existing named pipe",
+                fifo.display()
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => create_fifo(&fifo)?,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to inspect {}", fifo.display()));
+            }
+        }
+
+        thread::spawn(move || {
+            while !stop_flag.load(Ordering::Relaxed) {
+                // Opening blocks until a writer connects; EOF means it left
+                let file = match std::fs::File::open(&fifo) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        daemon_log(
+                            LogLevel::Error,
+                            format_args!("failed to open {}: {}", fifo.display(), e),
+                        );
+                        return;
+                    }
+                };
+                let reader = std::io::BufReader::new(file);
+                if !forward_lines(reader, &tx, &stop_flag, &parse_errors) {
+                    return;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn backend_name(&self) -> &'static str {
+        if self.fifo.is_some() { "fifo" } else { "stdin" }
+    }
+
+    fn pid(&self) -> Option<u32> {
+        None
+    }
+
+    fn take_parse_errors(&self) -> u64 {
+        self.parse_errors.swap(0, Ordering::Relaxed)
+    }
+}
+
+fn create_fifo(fifo: &Path) -> Result<()> {
+    let c_path = std::ffi::CString::new(fifo.as_os_str().as_encoded_bytes())
+        .context("FIFO path contains a NUL byte")?;
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to create FIFO {}", fifo.display()));
+    }
+    Ok(())
+}
+
+/// Send every parseable line as an event. Returns false once the daemon
+/// stopped listening.
+fn forward_lines(
+    reader: impl BufRead,
+    tx: &Sender<ExecEvent>,
+    stop_flag: &AtomicBool,
+    parse_errors: &AtomicU64,
+) -> bool {
+    for line in reader.lines().map_while(Result::ok) {
+        if stop_flag.load(Ordering::Relaxed) {
+            return false;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_ingest_line(&line) {
+            Some(event) => {
+                if tx.send(event).is_err() {
+                    return false;
+                }
+            }
+            None => {
+                parse_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    true
+}
+
+/// `/abs/path` or `/abs/path\t<source>`
+fn parse_ingest_line(line: &str) -> Option<ExecEvent> {
+    let (path, source) = match line.split_once('\t') {
+        Some((path, source)) => (path, Some(source.trim())),
+        None => (line, None),
+    };
+    let path = path.trim();
+    if !path.starts_with('/') {
+        return None;
+    }
+    Some(ExecEvent {
+        path: path.to_string(),
+        invoked_as: None,
+        source: source.filter(|s| !s.is_empty()).map(String::from),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(line: &str) -> Option<(String, Option<String>)> {
+        parse_ingest_line(line).map(|e| (e.path, e.source))
+    }
+
+    #[test]
+    fn test_parse_ingest_line() {
+        assert_eq!(
+            parsed("/usr/bin/rg"),
+            Some(("/usr/bin/rg".to_string(), None))
+        );
+        assert_eq!(
+            parsed("  /opt/tool/bin/run \t ci \n"),
+            Some(("/opt/tool/bin/run".to_string(), Some("ci".to_string())))
+        );
+        // An empty source falls back to categorizing the path
+        assert_eq!(
+            parsed("/usr/bin/rg\t"),
+            Some(("/usr/bin/rg".to_string(), None))
+        );
+        assert_eq!(parsed("rg"), None);
+        assert_eq!(parsed("./bin/rg\tci"), None);
+        assert_eq!(parsed(""), None);
+        assert!(
+            parse_ingest_line("/usr/bin/rg")
+                .unwrap()
+                .invoked_as
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ingest_rejects_regular_file() {
+        let dir = std::env::temp_dir().join(format!("dusty-ingest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("execs.log");
+        std::fs::write(&file, "/usr/bin/rg\n").unwrap();
+
+        let err = IngestMonitor::with_fifo(Some(file.clone()))
+            .start()
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a FIFO"), "{err}");
+
+        // A missing path becomes a named pipe
+        let fifo = dir.join("execs.fifo");
+        let mut monitor = IngestMonitor::with_fifo(Some(fifo.clone()));
+        monitor.start().unwrap();
+        monitor.stop().unwrap();
+        assert!(
+            std::fs::symlink_metadata(&fifo)
+                .unwrap()
+                .file_type()
+                .is_fifo()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
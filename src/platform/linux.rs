@@ -5,7 +5,8 @@ mod linux_distro;
 pub use linux_distro::{InitSystem, LinuxInfo, PackageManager};
 
 use super::{
-    DaemonManager, DylibAnalysis, DylibAnalyzer, DylibDep, LibPackageInfo, ProcessMonitor,
+    DaemonManager, DylibAnalysis, DylibAnalyzer, DylibDep, ExecEvent, LibPackageInfo, Linkage,
+    ProcessMonitor,
 };
 use anyhow::{Context, Result};
 use std::fs;
@@ -28,7 +29,7 @@ impl ProcessMonitor for Monitor {
         Self { child: None }
     }
 
-    fn start(&mut self) -> Result<Receiver<String>> {
+    fn start(&mut self) -> Result<Receiver<ExecEvent>> {
         // Use fatrace to monitor exec events
         // fatrace is a simple CLI wrapper around fanotify
         // When running as root (e.g. system service), call fatrace directly.
@@ -64,8 +65,13 @@ impl ProcessMonitor for Monitor {
             for line in reader.lines().map_while(Result::ok) {
                 // fatrace output format: "timestamp process(pid): O filename"
                 // We want to extract the filename from exec events
-                if let Some(path) = parse_fatrace_line(&line) {
-                    let _ = tx.send(path);
+                if let Some((path, pid)) = parse_fatrace_line(&line) {
+                    let invoked_as = pid.and_then(|pid| invoked_name(pid, &path));
+                    let _ = tx.send(ExecEvent {
+                        path,
+                        invoked_as,
+                        source: None,
+                    });
                 }
             }
         });
@@ -80,6 +86,14 @@ impl ProcessMonitor for Monitor {
         }
         Ok(())
     }
+
+    fn backend_name(&self) -> &'static str {
+        "fatrace"
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(|c| c.id())
+    }
 }
 
 impl Drop for Monitor {
@@ -88,8 +102,8 @@ impl Drop for Monitor {
     }
 }
 
-/// Parse fatrace output line to extract executable path
-fn parse_fatrace_line(line: &str) -> Option<String> {
+/// Parse fatrace output line to extract executable path and the pid running it
+fn parse_fatrace_line(line: &str) -> Option<(String, Option<u32>)> {
     // Format: "timestamp process(pid): O /path/to/file"
     // We want files that are being executed (typically in bin directories)
     let parts: Vec<&str> = line.splitn(4, ' ').collect();
@@ -97,12 +111,30 @@ fn parse_fatrace_line(line: &str) -> Option<String> {
         let path = parts[3].trim();
         // Filter to only track binaries in common locations
         if is_binary_path(path) {
-            return Some(path.to_string());
+            let pid = parts[1]
+                .trim_end_matches(':')
+                .trim_end_matches(')')
+                .rsplit_once('(')
+                .and_then(|(_, pid)| pid.parse().ok());
+            return Some((path.to_string(), pid));
         }
     }
     None
 }
 
+/// argv[0] of `pid`, once it is running `path`. fatrace reports the exec as
+/// it happens, so the process may still be the parent image or already gone;
+/// scripts never match since their exe is the interpreter.
+fn invoked_name(pid: u32, path: &str) -> Option<String> {
+    let exe = fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+    if exe.to_str() != Some(path) {
+        return None;
+    }
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let argv0 = cmdline.split(|&b| b == 0).next()?;
+    Some(String::from_utf8_lossy(argv0).into_owned()).filter(|a| !a.is_empty())
+}
+
 /// Check if a path looks like an executable binary
 fn is_binary_path(path: &str) -> bool {
     path.contains("/bin/")
@@ -111,6 +143,43 @@ fn is_binary_path(path: &str) -> bool {
         || path.contains("/go/bin/")
 }
 
+/// System boot time as a unix timestamp, from the `btime` line of /proc/stat
+pub fn boot_time() -> Option<i64> {
+    let stat = fs::read_to_string("/proc/stat").ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Whether the system is running on battery, from /sys/class/power_supply.
+/// None when no AC adapter or battery is exposed (desktops, containers).
+pub fn on_battery() -> Option<bool> {
+    let read = |dir: &std::path::Path, file: &str| {
+        fs::read_to_string(dir.join(file))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut known = false;
+    let mut discharging = false;
+    for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_str() {
+            "Mains" | "USB" => {
+                if read(&dir, "online") == "1" {
+                    return Some(false);
+                }
+                known = true;
+            }
+            "Battery" => {
+                known = true;
+                discharging |= read(&dir, "status") == "Discharging";
+            }
+            _ => {}
+        }
+    }
+    known.then_some(discharging)
+}
+
 /// Linux daemon manager - supports systemd, OpenRC, and runit
 pub struct Daemon;
 
@@ -133,6 +202,12 @@ impl Daemon {
             .or_else(|_| std::env::var("USER"))
             .unwrap_or_else(|_| "root".to_string());
         let home = std::env::var("HOME").unwrap_or_else(|_| format!("/home/{}", user));
+        // The daemon must write to the same database the CLI reads
+        let data_dir = std::env::var("DUSTY_DATA_DIR")
+            .ok()
+            .filter(|d| !d.is_empty())
+            .map(|d| format!("Environment=\"DUSTY_DATA_DIR={}\"\n", d))
+            .unwrap_or_default();
 
         format!(
             r#"[Unit]
@@ -146,7 +221,7 @@ Restart=always
 RestartSec=5
 Environment=HOME={home}
 Environment=USER={user}
-
+{data_dir}
 [Install]
 WantedBy=multi-user.target
 "#,
@@ -511,21 +586,36 @@ const SKIP_LIB_PREFIXES: &[&str] = &[
 
 impl DylibAnalyzer for Analyzer {
     fn analyze_binary(binary_path: &str) -> Result<DylibAnalysis> {
-        let output = Command::new("ldd").arg(binary_path).output();
+        // ldd says "not a dynamic executable" for scripts too, so check for ELF first
+        let mut magic = [0u8; 4];
+        std::fs::File::open(binary_path)
+            .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut magic))
+            .with_context(|| format!("Failed to read {}", binary_path))?;
+        if &magic != b"\x7fELF" {
+            return Ok(DylibAnalysis {
+                linkage: Linkage::Script,
+                libs: vec![],
+            });
+        }
 
-        let output = match output {
-            Ok(o) => o,
-            Err(_) => {
-                return Ok(DylibAnalysis { libs: vec![] });
-            }
-        };
+        let output = Command::new("ldd")
+            .arg(binary_path)
+            .output()
+            .context("Failed to run ldd")?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stdout.contains("statically linked") || stderr.contains("not a dynamic executable") {
+            return Ok(DylibAnalysis {
+                linkage: Linkage::Static,
+                libs: vec![],
+            });
+        }
 
         if !output.status.success() {
-            // "not a dynamic executable" or similar
-            return Ok(DylibAnalysis { libs: vec![] });
+            anyhow::bail!("ldd failed on {}: {}", binary_path, stderr.trim());
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
         let libs = stdout
             .lines()
             .filter_map(|line| {
@@ -551,10 +641,16 @@ impl DylibAnalyzer for Analyzer {
             })
             .collect();
 
-        Ok(DylibAnalysis { libs })
+        Ok(DylibAnalysis {
+            linkage: Linkage::Dynamic,
+            libs,
+        })
     }
 
     fn resolve_lib_packages(lib_paths: &[String]) -> Result<Vec<LibPackageInfo>> {
+        if super::is_offline() {
+            return Ok(vec![]);
+        }
         let info = LinuxInfo::detect();
         match info.package_manager {
             PackageManager::Apt => resolve_via_dpkg(lib_paths),
@@ -567,6 +663,9 @@ impl DylibAnalyzer for Analyzer {
     }
 
     fn get_package_size(_manager: &str, package_name: &str) -> Result<Option<u64>> {
+        if super::is_offline() {
+            return Ok(None);
+        }
         let info = LinuxInfo::detect();
         match info.package_manager {
             PackageManager::Apt => {
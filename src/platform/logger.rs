@@ -0,0 +1,208 @@
+//! The daemon's log: timestamped lines on stdout/stderr, or in a file that is
+//! rolled over by size so an always-on daemon can't grow it without bound
+
+use crate::config::{DaemonConfig, LogLevel};
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+static LOGGER: OnceLock<DaemonLogger> = OnceLock::new();
+
+/// Writes daemon diagnostics at or above `[daemon] log_level`.
+///
+/// Without a log file, info and debug lines go to stdout and warnings and
+/// errors to stderr, for systemd or launchd to capture.
+pub struct DaemonLogger {
+    level: LogLevel,
+    file: Option<Mutex<LogFile>>,
+}
+
+struct LogFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+    file: File,
+    size: u64,
+}
+
+impl DaemonLogger {
+    /// Log to `path` (appending) if given, otherwise to stdout/stderr
+    pub fn new(config: &DaemonConfig, path: Option<PathBuf>) -> Result<Self> {
+        let file = match path {
+            Some(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                let file = open_append(&path)
+                    .with_context(|| format!("Failed to open log file {}", path.display()))?;
+                let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+                Some(Mutex::new(LogFile {
+                    path,
+                    max_bytes: config.log_max_bytes,
+                    keep: config.log_keep,
+                    file,
+                    size,
+                }))
+            }
+            None => None,
+        };
+        Ok(Self {
+            level: config.log_level,
+            file,
+        })
+    }
+
+    /// Route [`daemon_log`] through this logger for the rest of the process
+    pub fn install(self) {
+        LOGGER.set(self).ok();
+    }
+
+    fn write(&self, level: LogLevel, message: &str) {
+        if level > self.level {
+            return;
+        }
+        let now = Local::now().format("%Y-%m-%d %H:%M:%S");
+        let line = match level {
+            LogLevel::Info => format!("[{}] {}\n", now, message),
+            _ => format!("[{}] {}: {}\n", now, level.as_str(), message),
+        };
+        match &self.file {
+            Some(file) => {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = file.write_line(&line) {
+                    eprint!("{}", line);
+                    eprintln!("[dusty] failed to write {}: {}", file.path.display(), e);
+                }
+            }
+            None if level <= LogLevel::Warn => eprint!("{}", line),
+            None => print!("{}", line),
+        }
+    }
+}
+
+impl LogFile {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.roll_over()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// `dusty.log` becomes `dusty.log.1`, `.1` becomes `.2` and so on; the
+    /// oldest beyond `keep` is overwritten. With `keep = 0` the log is truncated.
+    fn roll_over(&mut self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            self.file.set_len(0)?;
+        } else {
+            for n in (1..self.keep).rev() {
+                std::fs::rename(rolled(&self.path, n), rolled(&self.path, n + 1)).ok();
+            }
+            std::fs::rename(&self.path, rolled(&self.path, 1))?;
+            self.file = open_append(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// `path` with `.n` appended
+fn rolled(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Log one daemon message. Before a [`DaemonLogger`] is installed, messages
+/// go to stdout/stderr at the default level.
+pub fn daemon_log(level: LogLevel, message: impl std::fmt::Display) {
+    let message = message.to_string();
+    match LOGGER.get() {
+        Some(logger) => logger.write(level, &message),
+        None => DaemonLogger {
+            level: LogLevel::default(),
+            file: None,
+        }
+        .write(level, &message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_file(dir: &Path, max_bytes: u64, keep: u32) -> LogFile {
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join("dusty.log");
+        LogFile {
+            file: open_append(&path).unwrap(),
+            path,
+            max_bytes,
+            keep,
+            size: 0,
+        }
+    }
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_roll_over_shifts_and_caps() {
+        let dir = std::env::temp_dir().join(format!("dusty-log-{}", std::process::id()));
+        let mut log = log_file(&dir, 10, 2);
+        let path = log.path.clone();
+
+        // Fits under the threshold together, so no roll yet
+        log.write_line("aaaa\n").unwrap();
+        log.write_line("bbbb\n").unwrap();
+        assert_eq!(read(&path), "aaaa\nbbbb\n");
+        assert!(!rolled(&path, 1).exists());
+
+        // Crossing it moves the log to .1, then .1 to .2
+        log.write_line("cccc\n").unwrap();
+        assert_eq!(read(&rolled(&path, 1)), "aaaa\nbbbb\n");
+        assert_eq!(read(&path), "cccc\n");
+        log.write_line("dddddddd\n").unwrap();
+        assert_eq!(read(&rolled(&path, 2)), "aaaa\nbbbb\n");
+        assert_eq!(read(&rolled(&path, 1)), "cccc\n");
+        assert_eq!(read(&path), "dddddddd\n");
+
+        // Only `keep` old logs survive: the oldest is overwritten
+        log.write_line("eeee\n").unwrap();
+        assert_eq!(read(&rolled(&path, 2)), "cccc\n");
+        assert_eq!(read(&rolled(&path, 1)), "dddddddd\n");
+        assert!(!rolled(&path, 3).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_roll_over_keep_zero_truncates() {
+        let dir = std::env::temp_dir().join(format!("dusty-log0-{}", std::process::id()));
+        let mut log = log_file(&dir, 8, 0);
+        let path = log.path.clone();
+
+        log.write_line("aaaa\n").unwrap();
+        log.write_line("bbbb\n").unwrap();
+        assert_eq!(read(&path), "bbbb\n");
+        assert!(!rolled(&path, 1).exists());
+
+        // A line longer than the limit still goes into an empty log
+        log.write_line("cccccccccccc\n").unwrap();
+        log.write_line("dd\n").unwrap();
+        assert_eq!(read(&path), "dd\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,11 @@
 //! macOS-specific implementation using eslogger and launchd
 
 use super::{
-    DaemonManager, DylibAnalysis, DylibAnalyzer, DylibDep, LibPackageInfo, ProcessMonitor,
+    DaemonManager, DylibAnalysis, DylibAnalyzer, DylibDep, ExecEvent, LibPackageInfo, Linkage,
+    ProcessMonitor, daemon_log,
 };
+use crate::config::LogLevel;
 use anyhow::{Context, Result};
-use chrono::Local;
 use serde::Deserialize;
 use std::fs;
 use std::io::BufRead;
@@ -16,7 +17,7 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-/// Event from eslogger exec - we extract the target executable path
+/// Event from eslogger exec - we extract the target executable path and argv
 #[derive(Debug, Deserialize)]
 struct EsloggerEvent {
     event: Event,
@@ -30,6 +31,8 @@ struct Event {
 #[derive(Debug, Deserialize)]
 struct ExecInfo {
     target: TargetProcess,
+    #[serde(default)]
+    args: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,11 +46,12 @@ struct Executable {
 }
 
 impl EsloggerEvent {
-    fn executable_path(&self) -> Option<&str> {
-        self.event
-            .exec
-            .as_ref()
-            .map(|e| e.target.executable.path.as_str())
+    fn exec_event(&self) -> Option<ExecEvent> {
+        self.event.exec.as_ref().map(|e| ExecEvent {
+            path: e.target.executable.path.clone(),
+            invoked_as: e.args.first().filter(|a| !a.is_empty()).cloned(),
+            source: None,
+        })
     }
 }
 
@@ -61,8 +65,42 @@ fn spawn_eslogger() -> Result<Child> {
         .context("Failed to spawn eslogger. Make sure you have Full Disk Access enabled.")
 }
 
-fn timestamp() -> String {
-    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+/// System boot time as a unix timestamp, via sysctl `kern.boottime`
+pub fn boot_time() -> Option<i64> {
+    let mut tv = libc::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    let mut size = std::mem::size_of::<libc::timeval>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c"kern.boottime".as_ptr(),
+            &mut tv as *mut libc::timeval as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret == 0 && tv.tv_sec > 0 {
+        Some(tv.tv_sec as i64)
+    } else {
+        None
+    }
+}
+
+/// Whether the system is running on battery, from `pmset -g batt`.
+/// None when pmset fails or reports neither power source.
+pub fn on_battery() -> Option<bool> {
+    let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first = stdout.lines().next()?;
+    if first.contains("'Battery Power'") {
+        Some(true)
+    } else if first.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
 }
 
 /// macOS process monitor using eslogger with automatic restart on crash
@@ -72,13 +110,6 @@ pub struct Monitor {
     parse_errors: Arc<AtomicU64>,
 }
 
-impl Monitor {
-    /// Get and reset the parse error count since last call
-    pub fn take_parse_errors(&self) -> u64 {
-        self.parse_errors.swap(0, Ordering::Relaxed)
-    }
-}
-
 impl ProcessMonitor for Monitor {
     fn new() -> Self {
         Self {
@@ -88,7 +119,7 @@ impl ProcessMonitor for Monitor {
         }
     }
 
-    fn start(&mut self) -> Result<Receiver<String>> {
+    fn start(&mut self) -> Result<Receiver<ExecEvent>> {
         let (tx, rx) = mpsc::channel();
         let stop_flag = self.stop_flag.clone();
         let child_holder = self.child.clone();
@@ -101,7 +132,10 @@ impl ProcessMonitor for Monitor {
                 match spawn_eslogger() {
                     Ok(mut child) => {
                         let pid = child.id();
-                        println!("[{}] eslogger started (pid: {})", timestamp(), pid);
+                        daemon_log(
+                            LogLevel::Info,
+                            format_args!("eslogger started (pid: {})", pid),
+                        );
 
                         let stdout = child.stdout.take().unwrap();
 
@@ -125,8 +159,8 @@ impl ProcessMonitor for Monitor {
                             }
                             match serde_json::from_str::<EsloggerEvent>(&line) {
                                 Ok(event) => {
-                                    if let Some(path) = event.executable_path() {
-                                        if tx.send(path.to_string()).is_err() {
+                                    if let Some(exec) = event.exec_event() {
+                                        if tx.send(exec).is_err() {
                                             return; // receiver dropped
                                         }
                                     }
@@ -148,20 +182,24 @@ impl ProcessMonitor for Monitor {
                             let status_str = status
                                 .map(|s| format!("{}", s))
                                 .unwrap_or_else(|| "unknown".into());
-                            println!(
-                                "[{}] eslogger exited (status: {}), restarting in {}s",
-                                timestamp(),
-                                status_str,
-                                backoff.as_secs()
+                            daemon_log(
+                                LogLevel::Warn,
+                                format_args!(
+                                    "eslogger exited (status: {}), restarting in {}s",
+                                    status_str,
+                                    backoff.as_secs()
+                                ),
                             );
                         }
                     }
                     Err(e) => {
-                        println!(
-                            "[{}] eslogger spawn failed: {}, retrying in {}s",
-                            timestamp(),
-                            e,
-                            backoff.as_secs()
+                        daemon_log(
+                            LogLevel::Error,
+                            format_args!(
+                                "eslogger spawn failed: {}, retrying in {}s",
+                                e,
+                                backoff.as_secs()
+                            ),
                         );
                     }
                 }
@@ -196,6 +234,18 @@ impl ProcessMonitor for Monitor {
         }
         Ok(())
     }
+
+    fn backend_name(&self) -> &'static str {
+        "eslogger"
+    }
+
+    fn take_parse_errors(&self) -> u64 {
+        self.parse_errors.swap(0, Ordering::Relaxed)
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.lock().unwrap().as_ref().map(|c| c.id())
+    }
 }
 
 impl Drop for Monitor {
@@ -221,7 +271,9 @@ impl Daemon {
 
     fn generate_plist(exe_path: &str) -> String {
         let log_dir = Self::log_dir();
+        // The daemon writes and rotates dusty.log itself; stdout only catches strays
         let log_path = log_dir.join("dusty.log");
+        let out_path = log_dir.join("dusty.out");
         let err_path = log_dir.join("dusty.err");
         // Set HOME so dirs::data_local_dir() / dirs::config_dir() resolve
         // to the real user's paths, not /var/root/ (daemon runs as root)
@@ -229,6 +281,21 @@ impl Daemon {
             .expect("Could not determine home directory")
             .to_string_lossy()
             .to_string();
+        // The daemon must write to the same database the CLI reads
+        let data_dir = std::env::var("DUSTY_DATA_DIR")
+            .ok()
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                let d = d
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                format!(
+                    "\n        <key>DUSTY_DATA_DIR</key>\n        <string>{}</string>",
+                    d
+                )
+            })
+            .unwrap_or_default();
         format!(
             r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
@@ -240,18 +307,20 @@ impl Daemon {
     <array>
         <string>{exe}</string>
         <string>daemon</string>
+        <string>--log-file</string>
+        <string>{log}</string>
     </array>
     <key>EnvironmentVariables</key>
     <dict>
         <key>HOME</key>
-        <string>{home}</string>
+        <string>{home}</string>{data_dir}
     </dict>
     <key>RunAtLoad</key>
     <true/>
     <key>KeepAlive</key>
     <true/>
     <key>StandardOutPath</key>
-    <string>{log}</string>
+    <string>{out}</string>
     <key>StandardErrorPath</key>
     <string>{err}</string>
 </dict>
@@ -260,7 +329,9 @@ impl Daemon {
             label = Self::LABEL,
             exe = exe_path,
             home = user_home,
+            data_dir = data_dir,
             log = log_path.to_string_lossy(),
+            out = out_path.to_string_lossy(),
             err = err_path.to_string_lossy(),
         )
     }
@@ -380,7 +451,8 @@ impl DaemonManager for Daemon {
         let mut cmd = Command::new("tail");
         cmd.arg("-n").arg(lines.to_string());
         if follow {
-            cmd.arg("-f");
+            // The daemon rotates dusty.log; -F reopens it after each roll
+            cmd.arg("-F");
         }
         cmd.arg(&log_file);
         cmd.status().context("Failed to run tail")?;
@@ -393,21 +465,28 @@ pub struct Analyzer;
 
 impl DylibAnalyzer for Analyzer {
     fn analyze_binary(binary_path: &str) -> Result<DylibAnalysis> {
-        let output = Command::new("otool").args(["-L", binary_path]).output();
+        let output = Command::new("otool")
+            .args(["-L", binary_path])
+            .output()
+            .context("Failed to run otool")?;
 
-        let output = match output {
-            Ok(o) => o,
-            Err(_) => {
-                return Ok(DylibAnalysis { libs: vec![] });
-            }
-        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.contains("is not an object file") {
+            return Ok(DylibAnalysis {
+                linkage: Linkage::Script,
+                libs: vec![],
+            });
+        }
 
         if !output.status.success() {
-            return Ok(DylibAnalysis { libs: vec![] });
+            anyhow::bail!(
+                "otool failed on {}: {}",
+                binary_path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let libs = stdout
+        let libs: Vec<DylibDep> = stdout
             .lines()
             .skip(1)
             .filter_map(|line| {
@@ -433,7 +512,13 @@ impl DylibAnalyzer for Analyzer {
             })
             .collect();
 
-        Ok(DylibAnalysis { libs })
+        // Go and Rust binaries typically link only libSystem: nothing dusty can act on
+        let linkage = if libs.is_empty() {
+            Linkage::Static
+        } else {
+            Linkage::Dynamic
+        };
+        Ok(DylibAnalysis { linkage, libs })
     }
 
     fn resolve_lib_packages(lib_paths: &[String]) -> Result<Vec<LibPackageInfo>> {
@@ -451,6 +536,9 @@ impl DylibAnalyzer for Analyzer {
     }
 
     fn get_package_size(_manager: &str, package_name: &str) -> Result<Option<u64>> {
+        if super::is_offline() {
+            return Ok(None);
+        }
         // Try common Homebrew Cellar locations
         for prefix in &["/opt/homebrew/Cellar", "/usr/local/Cellar"] {
             let cellar_path = format!("{}/{}", prefix, package_name);
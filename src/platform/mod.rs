@@ -10,14 +10,59 @@ mod linux;
 #[cfg(target_os = "linux")]
 pub use linux::*;
 
+mod ingest;
+pub use ingest::IngestMonitor;
+
+mod logger;
+pub use logger::{DaemonLogger, daemon_log};
+
 use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Receiver;
 
-/// Trait for platform-specific process monitoring
+/// Set from the global `--offline` flag
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// `--offline` or a non-empty, non-"0" `DUSTY_OFFLINE`: never run package managers
+/// or binary inspection tools; only data already in the database is used
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+        || std::env::var("DUSTY_OFFLINE").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// An exec reported by the process monitor
+#[derive(Debug, Clone)]
+pub struct ExecEvent {
+    /// Resolved path of the executable
+    pub path: String,
+    /// The name it was run under (argv[0]), when the backend can tell
+    pub invoked_as: Option<String>,
+    /// Source named by the producer; otherwise categorized from the path
+    pub source: Option<String>,
+}
+
+/// Trait for process monitoring backends
 pub trait ProcessMonitor {
-    fn new() -> Self;
-    fn start(&mut self) -> Result<Receiver<String>>;
+    fn new() -> Self
+    where
+        Self: Sized;
+    fn start(&mut self) -> Result<Receiver<ExecEvent>>;
     fn stop(&mut self) -> Result<()>;
+
+    /// Name of the tool feeding exec events (e.g. "fatrace", "eslogger")
+    fn backend_name(&self) -> &'static str;
+
+    /// PID of the running monitor process, if one is currently alive
+    fn pid(&self) -> Option<u32>;
+
+    /// Get and reset the count of unparseable events since the last call
+    fn take_parse_errors(&self) -> u64 {
+        0
+    }
 }
 
 /// Trait for platform-specific daemon management
@@ -53,9 +98,32 @@ pub struct DylibDep {
     pub path: String,
 }
 
+/// How a binary links, as far as dependency analysis can tell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Loads shared libraries at runtime (system ones are not listed in `libs`)
+    Dynamic,
+    /// Statically linked, or links nothing beyond the OS's own libraries
+    Static,
+    /// Not a native executable (shell/Python script, etc.)
+    Script,
+}
+
+impl Linkage {
+    /// Stable name stored in the deps cache and shown in JSON
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Linkage::Dynamic => "dynamic",
+            Linkage::Static => "static",
+            Linkage::Script => "script",
+        }
+    }
+}
+
 /// Result of analyzing a binary's dynamic library dependencies
 #[derive(Debug)]
 pub struct DylibAnalysis {
+    pub linkage: Linkage,
     pub libs: Vec<DylibDep>,
 }
 
@@ -69,12 +137,14 @@ pub struct LibPackageInfo {
 
 /// Trait for platform-specific dynamic library analysis
 pub trait DylibAnalyzer {
-    /// Analyze a binary's dynamic library dependencies
+    /// Analyze a binary's dynamic library dependencies.
+    /// Errors mean the analysis itself failed, as opposed to a static binary.
     fn analyze_binary(binary_path: &str) -> Result<DylibAnalysis>;
 
-    /// Resolve library paths to their owning packages (batch)
+    /// Resolve library paths to their owning packages (batch).
+    /// Resolves nothing when `is_offline()`.
     fn resolve_lib_packages(lib_paths: &[String]) -> Result<Vec<LibPackageInfo>>;
 
-    /// Get installed size of a package in bytes
+    /// Get installed size of a package in bytes (unknown when `is_offline()`)
     fn get_package_size(manager: &str, package_name: &str) -> Result<Option<u64>>;
 }
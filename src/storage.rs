@@ -1,13 +1,14 @@
 use anyhow::Result;
-use rusqlite::{Connection, params};
-use std::path::PathBuf;
+use rusqlite::{Connection, Transaction, params};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct Database {
     conn: Connection,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BinaryRecord {
     pub path: String,
     pub count: i64,
@@ -15,6 +16,43 @@ pub struct BinaryRecord {
     pub last_seen: Option<i64>,
     pub source: Option<String>,
     pub package_name: Option<String>,
+    /// The daemon has seen this binary execute (as opposed to only finding it in PATH)
+    pub observed: bool,
+}
+
+impl BinaryRecord {
+    /// (source, package) the binary is grouped under: "other" and its file
+    /// name stand in for a missing source or package name
+    pub fn package_key(&self) -> (String, String) {
+        let source = self.source.clone().unwrap_or_else(|| "other".to_string());
+        let pkg = self.package_name.clone().unwrap_or_else(|| {
+            std::path::Path::new(&self.path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+        (source, pkg)
+    }
+
+    /// Never run, and first seen within the last `grace_days`: a fresh install
+    /// that shouldn't count as dust yet
+    pub fn is_new(&self, now: i64, grace_days: u32) -> bool {
+        self.count == 0
+            && self
+                .first_seen
+                .is_some_and(|ts| ts >= now - grace_days as i64 * 24 * 60 * 60)
+    }
+}
+
+/// Execs of a binary under a name other than its file name (a symlink or a
+/// multicall binary like busybox), as the daemon saw them in argv[0]
+#[derive(Debug, Clone)]
+pub struct InvocationRecord {
+    pub path: String,
+    pub name: String,
+    pub count: i64,
+    pub last_seen: i64,
 }
 
 #[derive(Debug)]
@@ -29,22 +67,144 @@ pub struct TrashRecord {
     pub restore_cmd: Option<String>,
 }
 
+/// The command name in an argv[0]: login shells' leading `-` and any
+/// directory are dropped (`-zsh` -> `zsh`, `/usr/bin/vi` -> `vi`)
+pub fn invocation_name(argv0: &str) -> &str {
+    let name = argv0.strip_prefix('-').unwrap_or(argv0);
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// Whether a binary that doesn't resolve has really been removed, rather
+/// than sitting on a filesystem that's unreadable or unmounted right now.
+///
+/// A readable parent directory settles it. Otherwise the path is kept only
+/// when the nearest ancestor that still exists is unreadable, a dangling
+/// symlink lies on the way (NixOS's /run/current-system mid-switch), or one
+/// of `mounts` sits between that ancestor and the path. An emptied directory
+/// on its own (`~/.pyenv/versions/` after removing the last version) means
+/// the file is gone.
+fn is_gone(path: &std::path::Path, mounts: &[PathBuf]) -> bool {
+    if path.exists() {
+        return false;
+    }
+    let Some(parent) = path.parent() else {
+        return true;
+    };
+    for dir in parent.ancestors() {
+        if dir.exists() {
+            if std::fs::read_dir(dir).is_err() {
+                return false;
+            }
+            return dir == parent
+                || !mounts
+                    .iter()
+                    .any(|m| m.parent().is_some() && m.starts_with(dir) && path.starts_with(m));
+        }
+        if dir.symlink_metadata().is_ok() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Mount points that may hold binaries: everything mounted now (autofs
+/// triggers included), plus what fstab and the automounter master map would
+/// mount
+fn mount_points() -> Vec<PathBuf> {
+    let table = |file: &str, field: usize| {
+        std::fs::read_to_string(file)
+            .map(|content| parse_mount_table(&content, field))
+            .unwrap_or_default()
+    };
+    let mut mounts = table("/proc/self/mounts", 1);
+    mounts.extend(table("/etc/fstab", 1));
+    mounts.extend(table("/etc/auto.master", 0));
+    mounts.extend(table("/etc/auto_master", 0));
+    mounts
+}
+
+/// The absolute paths in one whitespace-separated column of a mount table,
+/// with the octal escapes of /proc/self/mounts (`\040` for a space) decoded
+fn parse_mount_table(content: &str, field: usize) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_whitespace().nth(field))
+        .filter(|p| p.starts_with('/') && *p != "/-")
+        .map(|p| PathBuf::from(unescape_octal(p)))
+        .collect()
+}
+
+fn unescape_octal(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(idx) = rest.find('\\') {
+        out.push_str(&rest[..idx]);
+        let code = rest.get(idx + 1..idx + 4);
+        match code.and_then(|c| u8::from_str_radix(c, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[idx + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// dusty's data directory (database and trash).
+///
+/// `$DUSTY_DATA_DIR` wins when set. Otherwise this is `dusty/` under the
+/// platform data directory, falling back to `$XDG_DATA_HOME` and then
+/// `$HOME/.local/share` when the platform lookup fails (e.g. no passwd entry
+/// in a container).
+pub fn data_dir() -> Result<PathBuf> {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    if let Some(dir) = env_dir("DUSTY_DATA_DIR") {
+        return Ok(dir);
+    }
+    dirs::data_local_dir()
+        .or_else(|| env_dir("XDG_DATA_HOME"))
+        .or_else(|| env_dir("HOME").map(|h| h.join(".local").join("share")))
+        .map(|d| d.join("dusty"))
+        .ok_or_else(|| anyhow::anyhow!("Could not find local data directory; set DUSTY_DATA_DIR"))
+}
+
 impl Database {
     pub fn open() -> Result<Self> {
-        let path = Self::db_path()?;
+        Self::open_at(&Self::db_path()?)
+    }
+
+    /// Open (creating if needed) the database file at `path`
+    pub fn open_at(path: &Path) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let conn = Connection::open(&path)?;
+        let conn = Connection::open(path)?;
         let db = Self { conn };
         db.init_schema()?;
         Ok(db)
     }
 
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        let db = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        db.init_schema()?;
+        Ok(db)
+    }
+
     pub fn db_path() -> Result<PathBuf> {
-        let data_dir = dirs::data_local_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not find local data directory"))?;
-        Ok(data_dir.join("dusty").join("dusty.db"))
+        Ok(data_dir()?.join("dusty.db"))
     }
 
     fn init_schema(&self) -> Result<()> {
@@ -56,7 +216,8 @@ impl Database {
                 first_seen INTEGER,
                 last_seen INTEGER,
                 source TEXT,
-                package_name TEXT
+                package_name TEXT,
+                observed INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS meta (
@@ -79,7 +240,8 @@ impl Database {
             CREATE TABLE IF NOT EXISTS deps_meta (
                 binary_path TEXT PRIMARY KEY,
                 analyzed_at INTEGER NOT NULL,
-                binary_mtime INTEGER
+                binary_mtime INTEGER,
+                linkage TEXT
             );
 
             CREATE INDEX IF NOT EXISTS idx_dylib_deps_lib ON dylib_deps(lib_path);
@@ -99,56 +261,253 @@ impl Database {
                 method TEXT NOT NULL,
                 restore_cmd TEXT
             );
+
+            CREATE TABLE IF NOT EXISTS exec_daily (
+                path TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (path, day)
+            );
+
+            CREATE TABLE IF NOT EXISTS exec_invocations (
+                path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                count INTEGER NOT NULL DEFAULT 0,
+                last_seen INTEGER NOT NULL,
+                PRIMARY KEY (path, name)
+            );
+
+            CREATE TABLE IF NOT EXISTS package_sizes (
+                manager TEXT NOT NULL,
+                package_name TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                measured_at INTEGER NOT NULL,
+                PRIMARY KEY (manager, package_name)
+            );
+
+            CREATE TABLE IF NOT EXISTS reports (
+                kind TEXT PRIMARY KEY,
+                ts INTEGER NOT NULL,
+                json TEXT NOT NULL
+            );
             ",
         )?;
 
+        // Databases created before linkage was tracked lack the column
+        if self
+            .conn
+            .prepare("SELECT linkage FROM deps_meta LIMIT 0")
+            .is_err()
+        {
+            self.conn
+                .execute_batch("ALTER TABLE deps_meta ADD COLUMN linkage TEXT")?;
+        }
+
+        // Databases created before provenance was tracked: anything with uses
+        // or exec history must have been seen by the daemon
+        if self
+            .conn
+            .prepare("SELECT observed FROM binaries LIMIT 0")
+            .is_err()
+        {
+            self.conn.execute_batch(
+                "ALTER TABLE binaries ADD COLUMN observed INTEGER NOT NULL DEFAULT 0;
+                 UPDATE binaries SET observed = 1
+                 WHERE count > 0 OR path IN (SELECT path FROM exec_daily);",
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Record one exec of `path`. `invoked_as` is the name it was run under
+    /// (argv[0]) when the monitor could tell.
+    pub fn record_exec(
+        &self,
+        path: &str,
+        source: Option<&str>,
+        invoked_as: Option<&str>,
+    ) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.record_exec_at(path, source, invoked_as, now, true)
+    }
+
+    /// Record a batch of (path, source, timestamp, invoked name) execs in one transaction
+    pub fn record_execs(&self, execs: &[(String, String, i64, Option<String>)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (path, source, ts, invoked_as) in execs {
+            self.record_exec_at(path, Some(source), invoked_as.as_deref(), *ts, true)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Like `record_execs`, for execs read back from somewhere else (shell
+    /// history): they count as uses but don't mean the daemon saw the binary run
+    pub fn import_execs(&self, execs: &[(String, String, i64, Option<String>)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (path, source, ts, invoked_as) in execs {
+            self.record_exec_at(path, Some(source), invoked_as.as_deref(), *ts, false)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Add undated execs to tracked binaries' counts, leaving last_seen and the
+    /// per-day history alone (e.g. shell history without timestamps)
+    pub fn add_exec_counts(&self, counts: &[(String, i64)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (path, count) in counts {
+            let canonical = self.resolve_alias(path)?;
+            self.conn.execute(
+                "UPDATE binaries SET count = count + ?2 WHERE path = ?1",
+                params![canonical.as_deref().unwrap_or(path), count],
+            )?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn record_exec(&self, path: &str, source: Option<&str>) -> Result<()> {
+    fn record_exec_at(
+        &self,
+        path: &str,
+        source: Option<&str>,
+        invoked_as: Option<&str>,
+        now: i64,
+        observed: bool,
+    ) -> Result<()> {
         // Check if this path is an alias (resolved symlink) for a canonical path
         let canonical = self.resolve_alias(path)?;
         let effective_path = canonical.as_deref().unwrap_or(path);
 
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-
         self.conn.execute(
             "
-            INSERT INTO binaries (path, count, first_seen, last_seen, source)
-            VALUES (?1, 1, ?2, ?2, ?3)
+            INSERT INTO binaries (path, count, first_seen, last_seen, source, observed)
+            VALUES (?1, 1, ?2, ?2, ?3, ?4)
             ON CONFLICT(path) DO UPDATE SET
                 count = count + 1,
-                last_seen = ?2
+                last_seen = MAX(COALESCE(binaries.last_seen, ?2), ?2),
+                observed = MAX(binaries.observed, ?4),
+                source = CASE
+                    WHEN binaries.source IS NULL OR binaries.source = 'other'
+                    THEN COALESCE(excluded.source, binaries.source)
+                    ELSE binaries.source
+                END
             ",
-            params![effective_path, now, source],
+            params![effective_path, now, source, observed],
         )?;
+        self.conn.execute(
+            "
+            INSERT INTO exec_daily (path, day, count) VALUES (?1, ?2, 1)
+            ON CONFLICT(path, day) DO UPDATE SET count = count + 1
+            ",
+            params![effective_path, now / 86400],
+        )?;
+
+        // Only names that differ from the file's own are worth remembering;
+        // everything else is implied by the binary's count
+        let file_name = std::path::Path::new(effective_path)
+            .file_name()
+            .and_then(|n| n.to_str());
+        if let Some(name) = invoked_as.map(invocation_name)
+            && !name.is_empty()
+            && Some(name) != file_name
+        {
+            self.conn.execute(
+                "
+                INSERT INTO exec_invocations (path, name, count, last_seen) VALUES (?1, ?2, 1, ?3)
+                ON CONFLICT(path, name) DO UPDATE SET
+                    count = count + 1,
+                    last_seen = MAX(exec_invocations.last_seen, ?3)
+                ",
+                params![effective_path, name, now],
+            )?;
+        }
         Ok(())
     }
 
+    /// Every recorded invocation name that differs from its binary's file name
+    pub fn get_invocations(&self) -> Result<Vec<InvocationRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, name, count, last_seen FROM exec_invocations ORDER BY path, name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(InvocationRecord {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                count: row.get(2)?,
+                last_seen: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Per-binary exec counts by day (days since the unix epoch, UTC)
+    pub fn get_exec_history(&self) -> Result<HashMap<String, Vec<(i64, i64)>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, day, count FROM exec_daily ORDER BY path, day")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+        let mut history: HashMap<String, Vec<(i64, i64)>> = HashMap::new();
+        for row in rows {
+            let (path, day, count) = row?;
+            history.entry(path).or_default().push((day, count));
+        }
+        Ok(history)
+    }
+
     pub fn get_tracking_since(&self) -> Result<Option<i64>> {
+        Ok(self
+            .get_meta("tracking_since")?
+            .and_then(|v| v.parse().ok()))
+    }
+
+    pub fn set_tracking_since(&self, timestamp: i64) -> Result<()> {
+        self.set_meta("tracking_since", &timestamp.to_string())
+    }
+
+    /// When the daemon's event loop last reported in
+    pub fn get_heartbeat(&self) -> Result<Option<i64>> {
+        Ok(self
+            .get_meta("daemon_heartbeat_ts")?
+            .and_then(|v| v.parse().ok()))
+    }
+
+    pub fn set_heartbeat(&self, timestamp: i64) -> Result<()> {
+        self.set_meta("daemon_heartbeat_ts", &timestamp.to_string())
+    }
+
+    /// Read a value from the meta key/value table
+    pub fn get_meta(&self, key: &str) -> Result<Option<String>> {
         let result: Option<String> = self
             .conn
             .query_row(
-                "SELECT value FROM meta WHERE key = 'tracking_since'",
-                [],
+                "SELECT value FROM meta WHERE key = ?1",
+                params![key],
                 |row| row.get(0),
             )
             .ok();
 
-        Ok(result.and_then(|v| v.parse().ok()))
+        Ok(result)
     }
 
-    pub fn set_tracking_since(&self, timestamp: i64) -> Result<()> {
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<()> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO meta (key, value) VALUES ('tracking_since', ?1)",
-            params![timestamp.to_string()],
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
         )?;
         Ok(())
     }
 
     pub fn get_all_binaries(&self) -> Result<Vec<BinaryRecord>> {
         let mut stmt = self.conn.prepare(
-            "SELECT path, count, first_seen, last_seen, source, package_name
+            "SELECT path, count, first_seen, last_seen, source, package_name, observed
              FROM binaries
              ORDER BY count DESC",
         )?;
@@ -161,6 +520,7 @@ impl Database {
                 last_seen: row.get(3)?,
                 source: row.get(4)?,
                 package_name: row.get(5)?,
+                observed: row.get(6)?,
             })
         })?;
 
@@ -175,15 +535,37 @@ impl Database {
     }
 
     /// Register a binary from a package manager scan (with count = 0 if new).
-    /// Uses COALESCE to fill in missing fields without clobbering existing data.
+    /// Uses COALESCE to fill in missing fields without clobbering existing data;
+    /// a source of "other" (e.g. from a daemon exec seen before the scan) is upgraded.
     pub fn register_binary(&self, path: &str, package_name: &str, source: &str) -> Result<bool> {
         let rows = self.conn.execute(
             "INSERT INTO binaries (path, count, first_seen, last_seen, source, package_name)
-             VALUES (?1, 0, NULL, NULL, ?2, ?3)
+             VALUES (
+                 ?1, 0,
+                 -- binaries found by the very first scan predate tracking
+                 CASE WHEN EXISTS (SELECT 1 FROM meta WHERE key = 'tracking_since')
+                      THEN ?4 END,
+                 NULL, ?2, ?3
+             )
              ON CONFLICT(path) DO UPDATE SET
-                 source = COALESCE(binaries.source, excluded.source),
-                 package_name = COALESCE(binaries.package_name, excluded.package_name)",
-            params![path, source, package_name],
+                 source = CASE
+                     WHEN binaries.source IS NULL OR binaries.source = 'other'
+                     THEN excluded.source
+                     ELSE binaries.source
+                 END,
+                 package_name = CASE
+                     -- scoped npm names replace the bare binary name older scans stored
+                     WHEN excluded.package_name LIKE '@%/%' THEN excluded.package_name
+                     -- as do Go import paths read from a binary's build info
+                     WHEN excluded.package_name LIKE '%.%/%' THEN excluded.package_name
+                     ELSE COALESCE(binaries.package_name, excluded.package_name)
+                 END",
+            params![
+                path,
+                source,
+                package_name,
+                SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64
+            ],
         )?;
         Ok(rows > 0)
     }
@@ -214,7 +596,42 @@ impl Database {
         Ok(count)
     }
 
+    /// Re-derive source and package_name for every binary, e.g. after the
+    /// `[[sources]]` rules changed. Returns how many binaries changed.
+    pub fn recategorize_all<F>(&self, categorize: F) -> Result<u64>
+    where
+        F: Fn(&str) -> (String, String),
+    {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, source, package_name FROM binaries")?;
+        let rows: Vec<(String, Option<String>, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut count = 0u64;
+        for (path, old_source, old_pkg) in &rows {
+            let (source, pkg_name) = categorize(path);
+            if old_source.as_deref() == Some(source.as_str())
+                && old_pkg.as_deref() == Some(pkg_name.as_str())
+            {
+                continue;
+            }
+            tx.execute(
+                "UPDATE binaries SET source = ?2, package_name = ?3 WHERE path = ?1",
+                params![path, source, pkg_name],
+            )?;
+            count += 1;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
     /// Remove binaries from the database whose files no longer exist on disk.
+    /// Ones on a filesystem that's unreadable or unmounted right now are kept,
+    /// history and all, until it's back.
     pub fn prune_missing(&self) -> Result<u64> {
         let mut stmt = self.conn.prepare("SELECT path FROM binaries")?;
         let paths: Vec<String> = stmt
@@ -222,22 +639,62 @@ impl Database {
             .filter_map(|r| r.ok())
             .collect();
 
+        let mounts = mount_points();
         let mut count = 0u64;
         for path in &paths {
-            if !std::path::Path::new(path).exists() {
+            if is_gone(std::path::Path::new(path), &mounts) {
                 self.conn
                     .execute("DELETE FROM binaries WHERE path = ?1", params![path])?;
-                // Also clean up aliases pointing to this binary
+                // Also clean up aliases and history pointing to this binary
                 self.conn.execute(
                     "DELETE FROM path_aliases WHERE canonical_path = ?1",
                     params![path],
                 )?;
+                self.conn
+                    .execute("DELETE FROM exec_daily WHERE path = ?1", params![path])?;
+                self.conn.execute(
+                    "DELETE FROM exec_invocations WHERE path = ?1",
+                    params![path],
+                )?;
                 count += 1;
             }
         }
         Ok(count)
     }
 
+    /// Roll per-day history older than `retain_days` into a single row per binary
+    /// (dated at its newest compacted day), keeping the headline count intact.
+    /// Returns how many rows were removed. `retain_days = 0` keeps everything.
+    pub fn compact_history(&self, retain_days: u32) -> Result<u64> {
+        if retain_days == 0 {
+            return Ok(0);
+        }
+        let today = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64 / 86400;
+        let cutoff = today - retain_days as i64;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let rolled: Vec<(String, i64, i64)> = {
+            let mut stmt = tx.prepare(
+                "SELECT path, MAX(day), SUM(count) FROM exec_daily
+                 WHERE day < ?1 GROUP BY path",
+            )?;
+            stmt.query_map(params![cutoff], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?
+        };
+        let deleted = tx.execute("DELETE FROM exec_daily WHERE day < ?1", params![cutoff])?;
+        {
+            let mut insert =
+                tx.prepare_cached("INSERT INTO exec_daily (path, day, count) VALUES (?1, ?2, ?3)")?;
+            for (path, day, count) in &rolled {
+                insert.execute(params![path, day, count])?;
+            }
+        }
+        tx.commit()?;
+        Ok((deleted - rolled.len()) as u64)
+    }
+
     /// Get count of dusty (never used) binaries
     pub fn get_dusty_count(&self) -> Result<i64> {
         let count: i64 =
@@ -253,15 +710,16 @@ impl Database {
     /// Store dynamic library dependencies for a binary (replaces any existing)
     pub fn store_dylib_deps(&self, binary_path: &str, lib_paths: &[String]) -> Result<()> {
         let tx = self.conn.unchecked_transaction()?;
-        tx.execute(
-            "DELETE FROM dylib_deps WHERE binary_path = ?1",
-            params![binary_path],
-        )?;
-        for lib_path in lib_paths {
-            tx.execute(
-                "INSERT OR IGNORE INTO dylib_deps (binary_path, lib_path) VALUES (?1, ?2)",
-                params![binary_path, lib_path],
-            )?;
+        write_dylib_deps(&tx, binary_path, lib_paths)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store dependencies for many binaries in a single transaction
+    pub fn store_dylib_deps_many(&self, entries: &[(String, Vec<String>)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (binary_path, lib_paths) in entries {
+            write_dylib_deps(&tx, binary_path, lib_paths)?;
         }
         tx.commit()?;
         Ok(())
@@ -282,12 +740,30 @@ impl Database {
     }
 
     /// Mark a binary as analyzed with its current mtime
-    pub fn mark_deps_analyzed(&self, binary_path: &str, mtime: Option<i64>) -> Result<()> {
+    pub fn mark_deps_analyzed(
+        &self,
+        binary_path: &str,
+        mtime: Option<i64>,
+        linkage: &str,
+    ) -> Result<()> {
+        self.mark_deps_analyzed_many(&[(binary_path.to_string(), mtime, linkage)])
+    }
+
+    /// Record analysis time, binary mtime, and linkage ("dynamic", "static",
+    /// "script", or "failed") for many binaries in a single transaction
+    pub fn mark_deps_analyzed_many(&self, entries: &[(String, Option<i64>, &str)]) -> Result<()> {
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-        self.conn.execute(
-            "INSERT OR REPLACE INTO deps_meta (binary_path, analyzed_at, binary_mtime) VALUES (?1, ?2, ?3)",
-            params![binary_path, now, mtime],
-        )?;
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR REPLACE INTO deps_meta (binary_path, analyzed_at, binary_mtime, linkage)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (binary_path, mtime, linkage) in entries {
+                stmt.execute(params![binary_path, now, mtime, linkage])?;
+            }
+        }
+        tx.commit()?;
         Ok(())
     }
 
@@ -296,7 +772,9 @@ impl Database {
         let result = self
             .conn
             .query_row(
-                "SELECT analyzed_at, binary_mtime FROM deps_meta WHERE binary_path = ?1",
+                // Rows cached before linkage was tracked count as never analyzed
+                "SELECT analyzed_at, binary_mtime FROM deps_meta
+                 WHERE binary_path = ?1 AND linkage IS NOT NULL",
                 params![binary_path],
                 |row| Ok((row.get(0)?, row.get(1)?)),
             )
@@ -304,6 +782,16 @@ impl Database {
         Ok(result)
     }
 
+    /// Recorded linkage of every analyzed binary, keyed by path
+    pub fn get_all_linkage(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT binary_path, linkage FROM deps_meta WHERE linkage IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(Into::into)
+    }
+
     /// Get all library paths that haven't been resolved to a package yet
     pub fn get_unresolved_libs(&self) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
@@ -324,6 +812,20 @@ impl Database {
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Every cached binary → library mapping, keyed by binary path
+    pub fn get_all_dylib_deps(&self) -> Result<HashMap<String, Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT binary_path, lib_path FROM dylib_deps ORDER BY binary_path, lib_path",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?;
+        let mut deps: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (binary_path, lib_path) = row?;
+            deps.entry(binary_path).or_default().push(lib_path);
+        }
+        Ok(deps)
+    }
+
     /// Get all resolved library packages: (lib_path, manager, package_name)
     pub fn get_all_lib_packages(&self) -> Result<Vec<(String, String, String)>> {
         let mut stmt = self
@@ -336,7 +838,45 @@ impl Database {
     /// Clear all dependency analysis cache (for --refresh)
     pub fn clear_all_deps(&self) -> Result<()> {
         self.conn.execute_batch(
-            "DELETE FROM dylib_deps; DELETE FROM lib_packages; DELETE FROM deps_meta;",
+            "DELETE FROM dylib_deps; DELETE FROM lib_packages; DELETE FROM deps_meta;
+             DELETE FROM package_sizes;",
+        )?;
+        Ok(())
+    }
+
+    // --- Package size cache ---
+
+    /// Cached on-disk size of a package, if measured within `max_age_secs`
+    pub fn get_package_size(
+        &self,
+        manager: &str,
+        package_name: &str,
+        max_age_secs: i64,
+    ) -> Result<Option<u64>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let result = self
+            .conn
+            .query_row(
+                "SELECT size_bytes FROM package_sizes
+                 WHERE manager = ?1 AND package_name = ?2 AND measured_at >= ?3",
+                params![manager, package_name, now - max_age_secs],
+                |row| row.get::<_, i64>(0),
+            )
+            .ok();
+        Ok(result.map(|b| b as u64))
+    }
+
+    pub fn store_package_size(
+        &self,
+        manager: &str,
+        package_name: &str,
+        size_bytes: u64,
+    ) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO package_sizes (manager, package_name, size_bytes, measured_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![manager, package_name, size_bytes as i64, now],
         )?;
         Ok(())
     }
@@ -376,6 +916,21 @@ impl Database {
             .map_err(Into::into)
     }
 
+    /// All (alias_path, canonical_path) pairs, sorted by canonical path
+    pub fn get_all_aliases(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT alias_path, canonical_path FROM path_aliases ORDER BY canonical_path, alias_path",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Drop every alias mapping (used before re-deriving them from a fresh scan)
+    pub fn clear_aliases(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM path_aliases", [])?;
+        Ok(())
+    }
+
     // --- Trash methods ---
 
     pub fn record_trash(
@@ -436,6 +991,29 @@ impl Database {
         rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
 
+    /// Replace the saved report of this kind (e.g. the `--since-last` baseline)
+    pub fn save_report(&self, kind: &str, json: &str) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO reports (kind, ts, json) VALUES (?1, ?2, ?3)",
+            params![kind, now, json],
+        )?;
+        Ok(())
+    }
+
+    /// The saved report of this kind and when it was taken
+    pub fn get_report(&self, kind: &str) -> Result<Option<(i64, String)>> {
+        let result = self
+            .conn
+            .query_row(
+                "SELECT ts, json FROM reports WHERE kind = ?1",
+                params![kind],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+        Ok(result)
+    }
+
     pub fn delete_trash(&self, id: i64) -> Result<()> {
         self.conn
             .execute("DELETE FROM trash WHERE id = ?1", params![id])?;
@@ -446,4 +1024,320 @@ impl Database {
         self.conn.execute("DELETE FROM trash", [])?;
         Ok(())
     }
+
+    /// Fold the write-ahead log back into the main file and truncate it
+    /// (a no-op when the database isn't in WAL mode)
+    pub fn checkpoint(&self) -> Result<()> {
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Checkpoint, then rebuild the file to release pages freed by deletions
+    pub fn vacuum(&self) -> Result<()> {
+        self.checkpoint()?;
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+}
+
+/// Replace one binary's dylib rows, reusing the prepared statements across calls
+fn write_dylib_deps(tx: &Transaction, binary_path: &str, lib_paths: &[String]) -> Result<()> {
+    tx.prepare_cached("DELETE FROM dylib_deps WHERE binary_path = ?1")?
+        .execute(params![binary_path])?;
+    let mut insert = tx.prepare_cached(
+        "INSERT OR IGNORE INTO dylib_deps (binary_path, lib_path) VALUES (?1, ?2)",
+    )?;
+    for lib_path in lib_paths {
+        insert.execute(params![binary_path, lib_path])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_of(db: &Database, path: &str) -> Option<String> {
+        db.get_all_binaries()
+            .unwrap()
+            .into_iter()
+            .find(|b| b.path == path)
+            .and_then(|b| b.source)
+    }
+
+    #[test]
+    fn test_is_gone_keeps_unmounted_paths() {
+        let root = std::env::temp_dir().join(format!("dusty-prune-{}", std::process::id()));
+        let bin = root.join("tools/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::create_dir_all(root.join("mnt")).unwrap();
+        std::fs::create_dir_all(root.join(".pyenv/versions")).unwrap();
+        std::fs::write(bin.join("present"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("nowhere"), root.join("current")).unwrap();
+        let mounts = vec![PathBuf::from("/"), root.join("mnt")];
+
+        let gone = |p: &str| is_gone(&root.join(p), &mounts);
+        assert!(!gone("tools/bin/present"));
+        // Removed from a directory that's still there
+        assert!(gone("tools/bin/removed"));
+        // Whole install dir removed next to others
+        assert!(gone("tools/other/bin/x"));
+        // Last version removed, leaving an empty versions directory
+        assert!(gone(".pyenv/versions/3.11.4/bin/python"));
+        // Empty mount point, dangling profile symlink
+        assert!(!gone("mnt/share/bin/x"));
+        assert!(!gone("current/sw/bin/x"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_mount_table() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      server:/export /mnt/my\\040share nfs rw 0 0\n\
+                      auto.home /home autofs rw 0 0\n";
+        assert_eq!(
+            parse_mount_table(mounts, 1),
+            vec![
+                PathBuf::from("/"),
+                PathBuf::from("/mnt/my share"),
+                PathBuf::from("/home")
+            ]
+        );
+        let fstab = "# <fs> <mount>\nUUID=abc none swap sw 0 0\nUUID=def /data ext4 defaults 0 2\n";
+        assert_eq!(parse_mount_table(fstab, 1), vec![PathBuf::from("/data")]);
+        let master = "+auto.master\n/net -hosts\n/- auto.direct\n";
+        assert_eq!(parse_mount_table(master, 0), vec![PathBuf::from("/net")]);
+    }
+
+    #[test]
+    fn test_observed_set_by_exec_not_scan() {
+        let db = Database::open_in_memory().unwrap();
+        db.register_binary("/usr/bin/scanned", "scanned", "apt")
+            .unwrap();
+        db.register_binary("/usr/bin/run", "run", "apt").unwrap();
+        db.record_exec("/usr/bin/run", Some("apt"), None).unwrap();
+        db.record_exec("/opt/x/bin/daemon-only", None, None)
+            .unwrap();
+
+        let observed = |path: &str| {
+            db.get_all_binaries()
+                .unwrap()
+                .into_iter()
+                .find(|b| b.path == path)
+                .unwrap()
+                .observed
+        };
+        assert!(!observed("/usr/bin/scanned"));
+        assert!(observed("/usr/bin/run"));
+        assert!(observed("/opt/x/bin/daemon-only"));
+
+        // Shell history counts as use, not as the daemon seeing it run
+        db.register_binary("/usr/bin/hist", "hist", "apt").unwrap();
+        db.import_execs(&[("/usr/bin/hist".into(), "apt".into(), 100, None)])
+            .unwrap();
+        db.add_exec_counts(&[("/usr/bin/hist".into(), 2)]).unwrap();
+        let hist = db
+            .get_all_binaries()
+            .unwrap()
+            .into_iter()
+            .find(|b| b.path == "/usr/bin/hist")
+            .unwrap();
+        assert_eq!(hist.count, 3);
+        assert!(!hist.observed);
+    }
+
+    #[test]
+    fn test_first_seen_set_only_after_tracking_starts() {
+        let db = Database::open_in_memory().unwrap();
+        let first_of = |path: &str| {
+            db.get_all_binaries()
+                .unwrap()
+                .into_iter()
+                .find(|b| b.path == path)
+                .unwrap()
+        };
+
+        // The very first scan finds things installed before dusty
+        db.register_binary("/usr/bin/old", "old", "apt").unwrap();
+        db.set_tracking_since(1).unwrap();
+        db.register_binary("/usr/bin/fresh", "fresh", "apt")
+            .unwrap();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let old = first_of("/usr/bin/old");
+        let fresh = first_of("/usr/bin/fresh");
+        assert_eq!(old.first_seen, None);
+        assert!(!old.is_new(now, 7));
+        assert!(fresh.is_new(now, 7));
+        assert!(!fresh.is_new(now + 8 * 24 * 60 * 60, 7));
+    }
+
+    #[test]
+    fn test_daemon_binary_recategorized_after_scan() {
+        let db = Database::open_in_memory().unwrap();
+        let path = "/opt/homebrew/bin/jq";
+
+        // Daemon sees the exec before any scan knows about the source
+        db.record_exec(path, Some("other"), None).unwrap();
+        assert_eq!(source_of(&db, path).as_deref(), Some("other"));
+
+        // A later scan categorizes it properly
+        db.register_binary(path, "jq", "homebrew").unwrap();
+        let record = db.get_all_binaries().unwrap().remove(0);
+        assert_eq!(record.source.as_deref(), Some("homebrew"));
+        assert_eq!(record.package_name.as_deref(), Some("jq"));
+        assert_eq!(record.count, 1);
+
+        // Further execs categorized as "other" must not downgrade it
+        db.record_exec(path, Some("other"), None).unwrap();
+        assert_eq!(source_of(&db, path).as_deref(), Some("homebrew"));
+    }
+
+    #[test]
+    fn test_register_binary_upgrades_go_import_path() {
+        let db = Database::open_in_memory().unwrap();
+        let path = "/home/me/go/bin/goimports";
+        db.register_binary(path, "go", "go").unwrap();
+        db.register_binary(path, "golang.org/x/tools/cmd/goimports", "go")
+            .unwrap();
+        let record = db.get_all_binaries().unwrap().remove(0);
+        assert_eq!(
+            record.package_name.as_deref(),
+            Some("golang.org/x/tools/cmd/goimports")
+        );
+
+        // Plain names never overwrite what a scan already stored
+        db.register_binary(path, "goimports", "go").unwrap();
+        let record = db.get_all_binaries().unwrap().remove(0);
+        assert_eq!(
+            record.package_name.as_deref(),
+            Some("golang.org/x/tools/cmd/goimports")
+        );
+    }
+
+    #[test]
+    fn test_recategorize_all_overwrites_stored_source() {
+        let db = Database::open_in_memory().unwrap();
+        db.register_binary("/opt/tools/bin/a", "tools", "opt")
+            .unwrap();
+        db.register_binary("/usr/bin/b", "b", "apt").unwrap();
+
+        let changed = db
+            .recategorize_all(|path| {
+                if path.starts_with("/opt/tools/") {
+                    ("company".to_string(), "tools".to_string())
+                } else {
+                    ("apt".to_string(), "b".to_string())
+                }
+            })
+            .unwrap();
+        assert_eq!(changed, 1);
+        assert_eq!(
+            source_of(&db, "/opt/tools/bin/a").as_deref(),
+            Some("company")
+        );
+        assert_eq!(source_of(&db, "/usr/bin/b").as_deref(), Some("apt"));
+    }
+
+    #[test]
+    fn test_record_exec_keeps_differing_invocation_names() {
+        let db = Database::open_in_memory().unwrap();
+        let path = "/usr/bin/nvim";
+        db.record_exec(path, Some("apt"), Some("vi")).unwrap();
+        db.record_exec(path, Some("apt"), Some("/usr/bin/vi"))
+            .unwrap();
+        db.record_exec(path, Some("apt"), Some("nvim")).unwrap();
+        db.record_exec(path, Some("apt"), None).unwrap();
+
+        let invocations = db.get_invocations().unwrap();
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].name, "vi");
+        assert_eq!(invocations[0].count, 2);
+        assert_eq!(db.get_all_binaries().unwrap()[0].count, 4);
+    }
+
+    #[test]
+    fn test_record_exec_upgrades_other_source() {
+        let db = Database::open_in_memory().unwrap();
+        let path = "/home/u/.cargo/bin/rg";
+
+        db.record_exec(path, Some("other"), None).unwrap();
+        db.record_exec(path, Some("cargo"), None).unwrap();
+        assert_eq!(source_of(&db, path).as_deref(), Some("cargo"));
+
+        db.record_exec(path, None, None).unwrap();
+        assert_eq!(source_of(&db, path).as_deref(), Some("cargo"));
+    }
+
+    #[test]
+    fn test_package_size_cache() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(db.get_package_size("homebrew", "jq", 60).unwrap(), None);
+
+        db.store_package_size("homebrew", "jq", 4096).unwrap();
+        assert_eq!(
+            db.get_package_size("homebrew", "jq", 60).unwrap(),
+            Some(4096)
+        );
+        assert_eq!(db.get_package_size("apt", "jq", 60).unwrap(), None);
+
+        db.conn
+            .execute(
+                "UPDATE package_sizes SET measured_at = measured_at - 120",
+                [],
+            )
+            .unwrap();
+        assert_eq!(db.get_package_size("homebrew", "jq", 60).unwrap(), None);
+
+        db.store_package_size("homebrew", "jq", 8192).unwrap();
+        db.clear_all_deps().unwrap();
+        assert_eq!(db.get_package_size("homebrew", "jq", 60).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compact_history_rolls_up_old_rows() {
+        let db = Database::open_in_memory().unwrap();
+        let path = "/home/u/.cargo/bin/rg";
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+            / 86400;
+
+        db.record_exec(path, Some("cargo"), None).unwrap();
+        for age in [400, 300, 200, 10, 5] {
+            db.conn
+                .execute(
+                    "INSERT INTO exec_daily (path, day, count) VALUES (?1, ?2, 2)",
+                    params![path, today - age],
+                )
+                .unwrap();
+        }
+        let rows = |db: &Database| -> i64 {
+            db.conn
+                .query_row("SELECT COUNT(*) FROM exec_daily", [], |r| r.get(0))
+                .unwrap()
+        };
+        assert_eq!(rows(&db), 6);
+
+        // 400, 300 and 200 days ago collapse into one row dated 200 days ago
+        assert_eq!(db.compact_history(100).unwrap(), 2);
+        assert_eq!(rows(&db), 4);
+        let history = db.get_exec_history().unwrap();
+        assert_eq!(history[path][0], (today - 200, 6));
+        assert_eq!(history[path].iter().map(|&(_, n)| n).sum::<i64>(), 11);
+
+        // Headline stats live in `binaries` and are untouched
+        assert_eq!(db.get_all_binaries().unwrap()[0].count, 1);
+
+        // Compacting again is a no-op, and 0 disables retention entirely
+        assert_eq!(db.compact_history(100).unwrap(), 0);
+        assert_eq!(db.compact_history(0).unwrap(), 0);
+        assert_eq!(rows(&db), 4);
+    }
 }
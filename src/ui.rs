@@ -1,22 +1,354 @@
+use anyhow::Result;
 use console::style;
+use serde::Serialize;
+use serde_json::Value;
 use std::process::Command;
+use std::sync::OnceLock;
 
-use crate::defaults;
+use crate::{config, defaults};
+
+/// Output format for commands that print tabular data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table (default)
+    #[default]
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Markdown table
+    Md,
+    /// One compact JSON object per line
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// Resolve `--format` together with the deprecated `--json` flag.
+    /// clap rejects passing both, so `--json` simply means `--format json`.
+    pub fn from_flags(format: OutputFormat, json: bool) -> Self {
+        if json { OutputFormat::Json } else { format }
+    }
+
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+}
+
+/// Version of the JSON output contract, stamped on every `--json` document.
+/// Bump it when a field is renamed, removed or changes type; adding fields is
+/// not a breaking change. See docs/json-output.md.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Add `schema_version` as the first key of an object, or of every object in
+/// an array so list outputs keep their shape
+pub fn with_schema_version(value: Value) -> Value {
+    fn stamp(map: serde_json::Map<String, Value>) -> Value {
+        let mut out = serde_json::Map::new();
+        out.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+        out.extend(map);
+        Value::Object(out)
+    }
+    match value {
+        Value::Object(map) => stamp(map),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| match item {
+                    Value::Object(map) => stamp(map),
+                    other => other,
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Add the `schema_version` property [`with_schema_version`] stamps to a JSON
+/// Schema: on the root object, or on the items of a root array, following
+/// `$ref`s into `$defs` and through `anyOf`/`oneOf` alternatives
+pub fn stamp_schema_version(schema: &mut Value) {
+    fn stamp(node: &mut Value, defs: &mut serde_json::Map<String, Value>) {
+        let Some(obj) = node.as_object_mut() else {
+            return;
+        };
+        if let Some(name) = obj
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix("#/$defs/"))
+            .map(String::from)
+        {
+            if let Some(mut def) = defs.remove(&name) {
+                stamp(&mut def, defs);
+                defs.insert(name, def);
+            }
+            return;
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(alternatives)) = obj.get_mut(key) {
+                for alternative in alternatives {
+                    stamp(alternative, defs);
+                }
+                return;
+            }
+        }
+        if obj.get("type").and_then(Value::as_str) == Some("array") {
+            if let Some(items) = obj.get_mut("items") {
+                stamp(items, defs);
+            }
+            return;
+        }
+        if let Some(Value::Object(properties)) = obj.get("properties") {
+            if properties.contains_key("schema_version") {
+                return;
+            }
+            let mut stamped = serde_json::Map::new();
+            stamped.insert(
+                "schema_version".to_string(),
+                serde_json::json!({ "type": "integer", "const": SCHEMA_VERSION }),
+            );
+            stamped.extend(properties.clone());
+            obj.insert("properties".to_string(), Value::Object(stamped));
+            let required = obj
+                .entry("required")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(required) = required {
+                required.insert(0, "schema_version".into());
+            }
+        }
+    }
+
+    let mut defs = match schema.as_object_mut().and_then(|o| o.remove("$defs")) {
+        Some(Value::Object(defs)) => defs,
+        _ => serde_json::Map::new(),
+    };
+    stamp(schema, &mut defs);
+    if !defs.is_empty()
+        && let Some(obj) = schema.as_object_mut()
+    {
+        obj.insert("$defs".to_string(), Value::Object(defs));
+    }
+}
+
+/// Print pretty JSON stamped with `schema_version`
+pub fn print_json<T: Serialize>(data: &T) -> Result<()> {
+    let value = with_schema_version(serde_json::to_value(data)?);
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Print serializable data in a machine-readable format.
+/// Arrays become one row per element; a single object is a one-row table.
+pub fn emit<T: Serialize>(data: &T, format: OutputFormat) -> Result<()> {
+    print!("{}", render_structured(data, format)?);
+    Ok(())
+}
+
+pub fn render_structured<T: Serialize>(data: &T, format: OutputFormat) -> Result<String> {
+    let value = serde_json::to_value(data)?;
+    let rows: Vec<&Value> = match &value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+
+    let out = match format {
+        OutputFormat::Table => anyhow::bail!("table output is rendered by each command"),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&with_schema_version(value.clone()))?
+        ),
+        OutputFormat::Ndjson => {
+            let mut out = String::new();
+            for row in rows {
+                out.push_str(&serde_json::to_string(row)?);
+                out.push('\n');
+            }
+            out
+        }
+        OutputFormat::Csv => {
+            let columns = collect_columns(&rows);
+            let mut out = String::new();
+            if !columns.is_empty() {
+                let header: Vec<String> = columns.iter().map(|c| csv_escape(c)).collect();
+                out.push_str(&header.join(","));
+                out.push('\n');
+            }
+            for row in &rows {
+                let cells: Vec<String> = columns
+                    .iter()
+                    .map(|c| csv_escape(&cell_text(row.get(c))))
+                    .collect();
+                out.push_str(&cells.join(","));
+                out.push('\n');
+            }
+            out
+        }
+        OutputFormat::Md => {
+            let columns = collect_columns(&rows);
+            let mut out = String::new();
+            if !columns.is_empty() {
+                out.push_str(&format!("| {} |\n", columns.join(" | ")));
+                out.push_str(&format!("|{}\n", "---|".repeat(columns.len())));
+            }
+            for row in &rows {
+                let cells: Vec<String> = columns
+                    .iter()
+                    .map(|c| cell_text(row.get(c)).replace('|', "\\|"))
+                    .collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            out
+        }
+    };
+    Ok(out)
+}
+
+/// Union of object keys across rows, in first-seen order
+fn collect_columns(rows: &[&Value]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows {
+        if let Value::Object(map) = row {
+            for key in map.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    columns
+}
+
+/// Flatten a JSON value into a single table cell (nested values stay as JSON)
+fn cell_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Set from the global `--timing` flag
+static TIMING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn enable_timing() {
+    TIMING.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Scope guard that reports how long a phase took on stderr (only with `--timing`),
+/// so JSON on stdout stays clean.
+pub struct Timer {
+    label: &'static str,
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub fn start(label: &'static str) -> Self {
+        Self {
+            label,
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if TIMING.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!(
+                "  {} {:<14} {:>9.1}ms",
+                style("timing").dim(),
+                self.label,
+                self.start.elapsed().as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+/// `TERM=dumb` (Emacs shells, some CI) can't handle cursor movement, forced
+/// colors, pagers, or interactive prompts.
+pub fn is_dumb_terminal() -> bool {
+    std::env::var("TERM").is_ok_and(|t| t == "dumb")
+}
+
+/// Whether stdout is a terminal that can take forced ANSI styling
+pub fn styled_stdout() -> bool {
+    console::Term::stdout().is_term() && !is_dumb_terminal()
+}
+
+/// Send everything written to stdout into `path` (the global `--output`).
+/// Done at the file-descriptor level so tables, JSON/CSV and the pager fallback
+/// all land in the file; styling is forced off so the file never holds ANSI codes.
+/// Progress and errors stay on stderr.
+pub fn redirect_stdout(path: &std::path::Path) -> Result<()> {
+    use anyhow::Context;
+    use std::os::fd::AsRawFd;
+
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    if unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to redirect output to {}", path.display()));
+    }
+    console::set_colors_enabled(false);
+    STDOUT_REDIRECTED.store(true, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+static STDOUT_REDIRECTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Whether `--output` sent stdout to a file, so nothing should prompt
+pub fn stdout_redirected() -> bool {
+    STDOUT_REDIRECTED.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Point stdout at stderr and hand back the original stdout, so a command can
+/// keep its human output off stdout and write only a final machine line there
+pub fn stdout_to_stderr() -> Result<std::fs::File> {
+    use anyhow::Context;
+    use std::io::Write;
+    use std::os::fd::FromRawFd;
+
+    std::io::stdout().flush().ok();
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if saved < 0 || unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to redirect stdout");
+    }
+    Ok(unsafe { std::fs::File::from_raw_fd(saved) })
+}
 
 /// Animated status line on stderr (hides cursor, overwrites with \r).
+/// Silent on dumb terminals.
 pub struct Spinner {
     term: console::Term,
     tick: usize,
+    quiet: bool,
 }
 
 impl Spinner {
     pub fn new() -> Self {
         let term = console::Term::stderr();
-        let _ = term.hide_cursor();
-        Self { term, tick: 0 }
+        let quiet = is_dumb_terminal();
+        if !quiet {
+            let _ = term.hide_cursor();
+        }
+        Self {
+            term,
+            tick: 0,
+            quiet,
+        }
     }
 
     pub fn update(&mut self, label: &str, current: usize, total: usize) {
+        if self.quiet {
+            return;
+        }
         let dots = [".", "..", "..."];
         let dot = dots[self.tick % dots.len()];
         let msg = format!(
@@ -31,6 +363,9 @@ impl Spinner {
     }
 
     pub fn message(&self, label: &str) {
+        if self.quiet {
+            return;
+        }
         let _ = self.term.write_str(&format!(
             "\r{:<70}",
             format!("  {} ...", style(label).cyan())
@@ -38,6 +373,9 @@ impl Spinner {
     }
 
     pub fn finish(self) {
+        if self.quiet {
+            return;
+        }
         let _ = self.term.show_cursor();
         let _ = self.term.write_str(&format!("\r{:<70}\r", ""));
     }
@@ -55,13 +393,81 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Parse a human size like "500K", "20MB" or "1.5 GiB" into bytes. Units
+/// are powers of 1024, as in `format_bytes`; a bare number is bytes.
+pub fn parse_bytes(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a size (e.g. 500K, 20MB, 1.5G)", text))?;
+    let power = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        other => {
+            return Err(format!(
+                "unknown size unit '{}' (use B, K, M, G or T)",
+                other
+            ));
+        }
+    };
+    Ok((number * 1024f64.powi(power)) as u64)
+}
+
+/// Render a unix timestamp as a compact relative age ("2h ago", "3d ago", "5mo ago").
+pub fn humanize_ago(ts: i64) -> String {
+    humanize_secs(chrono::Utc::now().timestamp() - ts)
+}
+
+/// Render an age in seconds the same way ("just now", "2h ago", ...).
+pub fn humanize_secs(secs: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    if secs < MINUTE {
+        "just now".to_string()
+    } else if secs < HOUR {
+        format!("{}m ago", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h ago", secs / HOUR)
+    } else if secs < 30 * DAY {
+        format!("{}d ago", secs / DAY)
+    } else if secs < 365 * DAY {
+        format!("{}mo ago", secs / (30 * DAY))
+    } else {
+        format!("{}y ago", secs / (365 * DAY))
+    }
+}
+
+/// Set once at startup from `[display] path_shorthands`
+static PATH_SHORTHANDS: OnceLock<Vec<config::PathShorthand>> = OnceLock::new();
+
+pub fn set_path_shorthands(shorthands: Vec<config::PathShorthand>) {
+    let _ = PATH_SHORTHANDS.set(shorthands);
+}
+
 pub fn shorten_path(path: &str) -> String {
     let home = dirs::home_dir()
         .map(|h| h.display().to_string())
         .unwrap_or_default();
+    let custom = PATH_SHORTHANDS.get().map_or(&[][..], Vec::as_slice);
+    shorten_path_with(path, &home, custom)
+}
 
-    for &(prefix, replacement) in defaults::PATH_SHORTHANDS {
-        let expanded = prefix.replace('~', &home);
+/// Apply configured shorthands first, then the built-in ones
+fn shorten_path_with(path: &str, home: &str, custom: &[config::PathShorthand]) -> String {
+    let custom = custom
+        .iter()
+        .map(|s| (s.prefix.as_str(), s.replacement.as_str()));
+    for (prefix, replacement) in custom.chain(defaults::PATH_SHORTHANDS.iter().copied()) {
+        let expanded = prefix.replace('~', home);
         if path.starts_with(&expanded) {
             return format!("{}{}", replacement, &path[expanded.len()..]);
         }
@@ -77,15 +483,40 @@ pub fn truncate_str(s: &str, max_len: usize) -> String {
     }
 }
 
+/// Set from the global `--internal-pager` flag
+static INTERNAL_PAGER: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn force_internal_pager() {
+    INTERNAL_PAGER.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Page `content` through $PAGER, or the built-in viewer when $PAGER is unset
+/// or `--internal-pager` is passed. Printed directly when stdout isn't a terminal
+/// or the content fits on screen.
 pub fn print_with_pager(content: &str) {
     use std::io::Write;
 
-    if !console::Term::stdout().is_term() {
+    if !styled_stdout() {
         print!("{}", content);
         return;
     }
 
-    let pager_cmd = std::env::var("PAGER").unwrap_or_else(|_| defaults::DEFAULT_PAGER.to_string());
+    let pager_cmd = match std::env::var("PAGER").ok().filter(|p| !p.is_empty()) {
+        Some(cmd) if !INTERNAL_PAGER.load(std::sync::atomic::Ordering::Relaxed) => cmd,
+        _ => {
+            // The viewer uses the alternate screen, so output that fits is
+            // printed normally to stay visible after the command exits
+            let (rows, _) = console::Term::stdout().size();
+            let fits = content.lines().count() < rows as usize;
+            if fits
+                || !std::io::IsTerminal::is_terminal(&std::io::stdin())
+                || internal_pager(content).is_err()
+            {
+                print!("{}", content);
+            }
+            return;
+        }
+    };
     let (program, base_args): (&str, Vec<&str>) = if pager_cmd.contains("less") {
         (pager_cmd.as_str(), vec![defaults::PAGER_COLOR_FLAG])
     } else {
@@ -109,6 +540,137 @@ pub fn print_with_pager(content: &str) {
     }
 }
 
+/// Scrollable full-screen viewer: arrows/j/k scroll, PgUp/PgDn/space/b page,
+/// g/G jump to the ends, `/` searches (case-insensitive), n/N repeat, q quits
+fn internal_pager(content: &str) -> std::io::Result<()> {
+    use console::Key;
+    use std::io::Write;
+
+    let term = console::Term::stdout();
+    let lines: Vec<&str> = content.lines().collect();
+    let plain: Vec<String> = lines
+        .iter()
+        .map(|l| console::strip_ansi_codes(l).to_lowercase())
+        .collect();
+    let (rows, cols) = term.size();
+    let page = (rows as usize).saturating_sub(1).max(1);
+    let max_top = lines.len().saturating_sub(page);
+
+    let mut top = 0;
+    let mut query = String::new();
+    let mut hit: Option<usize> = None;
+    let mut message = String::new();
+
+    // Alternate screen, so the terminal is left as it was on quit
+    write!(&term, "\x1b[?1049h")?;
+    term.hide_cursor()?;
+    let result = (|| -> std::io::Result<()> {
+        loop {
+            let mut frame = String::from("\x1b[H\x1b[2J");
+            for (i, line) in lines.iter().enumerate().skip(top).take(page) {
+                let line = if hit == Some(i) {
+                    style(console::strip_ansi_codes(line).to_string())
+                        .reverse()
+                        .force_styling(true)
+                        .to_string()
+                } else {
+                    line.to_string()
+                };
+                frame.push_str(&console::truncate_str(&line, cols as usize, ""));
+                frame.push_str("\r\n");
+            }
+            let status = if message.is_empty() {
+                format!(
+                    "lines {}-{} of {}  (/ search, n/N next/prev, q quit)",
+                    (top + 1).min(lines.len()),
+                    (top + page).min(lines.len()),
+                    lines.len()
+                )
+            } else {
+                std::mem::take(&mut message)
+            };
+            frame.push_str(&style(status).reverse().force_styling(true).to_string());
+            write!(&term, "{}", frame)?;
+
+            let search_from = |from: usize, forward: bool, query: &str| {
+                find_line(&plain, &query.to_lowercase(), from, forward)
+            };
+            let found = match term.read_key()? {
+                Key::Char('q') | Key::Escape | Key::CtrlC => return Ok(()),
+                Key::ArrowDown | Key::Char('j') | Key::Enter => {
+                    top = (top + 1).min(max_top);
+                    continue;
+                }
+                Key::ArrowUp | Key::Char('k') => {
+                    top = top.saturating_sub(1);
+                    continue;
+                }
+                Key::PageDown | Key::Char(' ') | Key::Char('f') => {
+                    top = (top + page).min(max_top);
+                    continue;
+                }
+                Key::PageUp | Key::Char('b') => {
+                    top = top.saturating_sub(page);
+                    continue;
+                }
+                Key::Home | Key::Char('g') => {
+                    top = 0;
+                    continue;
+                }
+                Key::End | Key::Char('G') => {
+                    top = max_top;
+                    continue;
+                }
+                Key::Char('/') => {
+                    write!(&term, "\x1b[{};1H\x1b[2K/", page + 1)?;
+                    term.show_cursor()?;
+                    let input = term.read_line()?;
+                    term.hide_cursor()?;
+                    if !input.is_empty() {
+                        query = input;
+                    }
+                    search_from(top, true, &query)
+                }
+                // Repeat from the last match, or from the top of the screen
+                Key::Char('n') => search_from(hit.map_or(top, |h| h + 1), true, &query),
+                Key::Char('N') => hit
+                    .unwrap_or(top + 1)
+                    .checked_sub(1)
+                    .and_then(|from| search_from(from, false, &query)),
+                _ => continue,
+            };
+            match found {
+                Some(line) => {
+                    hit = Some(line);
+                    if line < top || line >= top + page {
+                        top = line.min(max_top);
+                    }
+                }
+                None if query.is_empty() => {}
+                None => message = format!("pattern not found: {}", query),
+            }
+        }
+    })();
+    term.show_cursor()?;
+    write!(&term, "\x1b[?1049l")?;
+    result
+}
+
+/// First line at or after `from` (or at or before it, going backwards)
+/// containing `query`. `lines` are expected lowercased and without styling.
+fn find_line(lines: &[String], query: &str, from: usize, forward: bool) -> Option<usize> {
+    if query.is_empty() {
+        return None;
+    }
+    if forward {
+        (from..lines.len()).find(|&i| lines[i].contains(query))
+    } else {
+        (0..=from.min(lines.len().checked_sub(1)?))
+            .rev()
+            .find(|&i| lines[i].contains(query))
+    }
+}
+
 /// Returns how many content rows fit in the terminal, reserving `overhead` lines
 /// for headers, summaries, and padding. Returns 0 if detection fails (show all).
 pub fn terminal_fit(overhead: usize) -> usize {
@@ -122,6 +684,49 @@ pub fn terminal_fit(overhead: usize) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_line_both_directions() {
+        let lines: Vec<String> = ["git", "ripgrep", "gitui", "jq"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(find_line(&lines, "git", 0, true), Some(0));
+        assert_eq!(find_line(&lines, "git", 1, true), Some(2));
+        assert_eq!(find_line(&lines, "git", 1, false), Some(0));
+        assert_eq!(find_line(&lines, "git", 3, true), None);
+        assert_eq!(find_line(&lines, "", 0, true), None);
+    }
+
+    #[test]
+    fn test_shorten_path_with_configured_prefix() {
+        let custom = vec![
+            config::PathShorthand {
+                prefix: "/opt/acme/bin/".to_string(),
+                replacement: "acme:".to_string(),
+            },
+            config::PathShorthand {
+                prefix: "~/tools/".to_string(),
+                replacement: "tools:".to_string(),
+            },
+        ];
+        let home = "/home/me";
+        assert_eq!(
+            shorten_path_with("/opt/acme/bin/deploy", home, &custom),
+            "acme:deploy"
+        );
+        assert_eq!(
+            shorten_path_with("/home/me/tools/fmt", home, &custom),
+            "tools:fmt"
+        );
+        // Built-ins still apply after the configured ones
+        assert_eq!(
+            shorten_path_with("/home/me/.cargo/bin/rg", home, &custom),
+            "cargo:rg"
+        );
+        assert_eq!(shorten_path_with("/usr/bin/ls", home, &custom), "/usr/ls");
+        assert_eq!(shorten_path_with("/srv/x", home, &custom), "/srv/x");
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(0), "0 B");
@@ -133,6 +738,17 @@ mod tests {
         assert_eq!(format_bytes(11_811_160_064), "11.0 GB");
     }
 
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("512"), Ok(512));
+        assert_eq!(parse_bytes("500K"), Ok(512_000));
+        assert_eq!(parse_bytes("20MB"), Ok(20 * 1_048_576));
+        assert_eq!(parse_bytes("1.5 GiB"), Ok(1_610_612_736));
+        assert_eq!(parse_bytes("2g"), Ok(2 * 1_073_741_824));
+        assert!(parse_bytes("MB").is_err());
+        assert!(parse_bytes("10 parsecs").is_err());
+    }
+
     #[test]
     fn test_truncate_str() {
         assert_eq!(truncate_str("short", 10), "short");
@@ -140,6 +756,53 @@ mod tests {
         assert_eq!(truncate_str("this is way too long", 10), "...oo long");
     }
 
+    #[test]
+    fn test_humanize_secs() {
+        assert_eq!(humanize_secs(-5), "just now");
+        assert_eq!(humanize_secs(59), "just now");
+        assert_eq!(humanize_secs(90), "1m ago");
+        assert_eq!(humanize_secs(2 * 3600 + 10), "2h ago");
+        assert_eq!(humanize_secs(3 * 86400), "3d ago");
+        assert_eq!(humanize_secs(150 * 86400), "5mo ago");
+        assert_eq!(humanize_secs(800 * 86400), "2y ago");
+    }
+
+    #[test]
+    fn test_render_structured_formats() {
+        #[derive(Serialize)]
+        struct Row {
+            name: &'static str,
+            uses: i64,
+            last: Option<&'static str>,
+        }
+        let rows = vec![
+            Row {
+                name: "git",
+                uses: 12,
+                last: Some("2025-01-01"),
+            },
+            Row {
+                name: "a,b",
+                uses: 0,
+                last: None,
+            },
+        ];
+
+        assert_eq!(
+            render_structured(&rows, OutputFormat::Csv).unwrap(),
+            "name,uses,last\ngit,12,2025-01-01\n\"a,b\",0,\n"
+        );
+        assert_eq!(
+            render_structured(&rows, OutputFormat::Md).unwrap(),
+            "| name | uses | last |\n|---|---|---|\n| git | 12 | 2025-01-01 |\n| a,b | 0 |  |\n"
+        );
+        assert_eq!(
+            render_structured(&rows, OutputFormat::Ndjson).unwrap(),
+            "{\"name\":\"git\",\"uses\":12,\"last\":\"2025-01-01\"}\n{\"name\":\"a,b\",\"uses\":0,\"last\":null}\n"
+        );
+        assert!(render_structured(&rows, OutputFormat::Table).is_err());
+    }
+
     #[test]
     fn test_shorten_path() {
         assert_eq!(shorten_path("/opt/homebrew/bin/git"), "brew:git");
@@ -149,4 +812,29 @@ mod tests {
         );
         assert_eq!(shorten_path("/some/random/path"), "/some/random/path");
     }
+
+    #[test]
+    fn test_stamp_schema_version_follows_refs_into_items() {
+        let mut schema = serde_json::json!({
+            "type": "array",
+            "items": { "$ref": "#/$defs/Row" },
+            "$defs": {
+                "Row": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } },
+                    "required": ["name"]
+                }
+            }
+        });
+        stamp_schema_version(&mut schema);
+        let row = &schema["$defs"]["Row"];
+        let keys: Vec<&String> = row["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["schema_version", "name"]);
+        assert_eq!(row["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+        assert_eq!(
+            row["required"],
+            serde_json::json!(["schema_version", "name"])
+        );
+        assert!(schema.get("properties").is_none());
+    }
 }
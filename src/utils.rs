@@ -1,20 +1,82 @@
 use anyhow::Result;
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{DateTime, FixedOffset, Local, TimeZone};
 use console::style;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
+use std::io::BufRead;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 use crate::config;
 use crate::defaults;
-use crate::package::scan_all_binaries;
+use crate::package::{BinaryScanResult, scan_all_binaries};
 use crate::platform::{Daemon, DaemonManager};
-use crate::storage::Database;
+use crate::storage::{BinaryRecord, Database};
+use crate::ui::Timer;
 
-/// Convert a Unix timestamp to a local DateTime, handling invalid values gracefully.
-pub fn local_datetime(ts: i64) -> DateTime<Local> {
-    Local
-        .timestamp_opt(ts, 0)
-        .single()
-        .unwrap_or_else(|| Local.timestamp_opt(0, 0).single().unwrap())
+/// Set once at startup from `--utc` or `[display] timezone`
+static DISPLAY_TIMEZONE: OnceLock<config::DisplayTimezone> = OnceLock::new();
+
+pub fn set_display_timezone(tz: config::DisplayTimezone) {
+    let _ = DISPLAY_TIMEZONE.set(tz);
+}
+
+fn display_timezone() -> config::DisplayTimezone {
+    DISPLAY_TIMEZONE
+        .get()
+        .copied()
+        .unwrap_or(config::DisplayTimezone::Local)
+}
+
+/// Convert a Unix timestamp to a DateTime in the display timezone, handling
+/// invalid values gracefully.
+pub fn display_datetime(ts: i64) -> DateTime<FixedOffset> {
+    datetime_in(display_timezone(), ts)
+}
+
+fn datetime_in(timezone: config::DisplayTimezone, ts: i64) -> DateTime<FixedOffset> {
+    fn at<Tz: TimeZone>(tz: Tz, ts: i64) -> DateTime<FixedOffset> {
+        tz.timestamp_opt(ts, 0)
+            .single()
+            .unwrap_or_else(|| tz.timestamp_opt(0, 0).single().unwrap())
+            .fixed_offset()
+    }
+    match timezone {
+        config::DisplayTimezone::Local => at(Local, ts),
+        config::DisplayTimezone::Named(tz) => at(tz, ts),
+    }
+}
+
+/// A timestamp as shown in tables: `2025-01-31 14:05`
+pub fn format_timestamp(ts: i64) -> String {
+    display_datetime(ts).format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// A timestamp's day: `2025-01-31`
+pub fn format_date(ts: i64) -> String {
+    display_datetime(ts).format("%Y-%m-%d").to_string()
+}
+
+/// A timestamp in structured output. Once a timezone is chosen this is
+/// ISO-8601 with its offset (`2025-01-31T14:05:00Z` under `--utc`); in local
+/// time it stays in the table format.
+pub fn json_timestamp(ts: i64) -> String {
+    json_timestamp_in(display_timezone(), ts)
+}
+
+fn json_timestamp_in(timezone: config::DisplayTimezone, ts: i64) -> String {
+    let datetime = datetime_in(timezone, ts);
+    if timezone == config::DisplayTimezone::Local {
+        return datetime.format("%Y-%m-%d %H:%M").to_string();
+    }
+    datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Now, in the display timezone (e.g. for "generated at" stamps)
+pub fn display_now() -> DateTime<FixedOffset> {
+    display_datetime(chrono::Utc::now().timestamp())
 }
 
 /// Start the daemon (returns true if started, false if already running).
@@ -70,17 +132,30 @@ pub fn start_daemon(silent: bool) -> Result<bool> {
     Ok(true)
 }
 
-/// Sync binaries from PATH to database (runs silently)
+/// Set from the global `--no-sync` flag
+static NO_SYNC: AtomicBool = AtomicBool::new(false);
+
+pub fn set_no_sync(no_sync: bool) {
+    NO_SYNC.store(no_sync, Ordering::Relaxed);
+}
+
+/// `--no-sync` or a non-empty, non-"0" `DUSTY_NO_SYNC` skips the implicit scan
+fn sync_disabled() -> bool {
+    NO_SYNC.load(Ordering::Relaxed)
+        || std::env::var("DUSTY_NO_SYNC").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Sync binaries from PATH to database (runs silently).
+/// A no-op under `--no-sync`, so commands read exactly what is stored.
 pub fn sync_binaries(db: &Database) -> Result<()> {
+    if sync_disabled() {
+        return Ok(());
+    }
+    let _timer = Timer::start("sync");
+
     let config = config::Config::load()?;
     let binaries = scan_all_binaries()?;
 
-    // Set tracking start if not already set
-    if db.get_tracking_since()?.is_none() {
-        let now = chrono::Utc::now().timestamp();
-        db.set_tracking_since(now)?;
-    }
-
     for (bin_path, pkg_name, source, resolved) in &binaries {
         db.register_binary(bin_path, pkg_name, source)?;
 
@@ -92,24 +167,154 @@ pub fn sync_binaries(db: &Database) -> Result<()> {
         }
     }
 
+    // Set tracking start if not already set. This comes after registration so
+    // the first scan's binaries keep a NULL first_seen (installed before tracking).
+    if db.get_tracking_since()?.is_none() {
+        let now = chrono::Utc::now().timestamp();
+        db.set_tracking_since(now)?;
+    }
+
     // Remove binaries that no longer exist on disk
     db.prune_missing()?;
 
+    // Keep per-day history bounded
+    db.compact_history(config.history.retain_days)?;
+
     // Backfill source + package_name for binaries discovered by the daemon
-    db.backfill_uncategorized(|path| {
-        let source = config.categorize_path(path);
-        let bin_path = std::path::Path::new(path);
-        let default_name = bin_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-        let pkg_name = crate::package::get_package_name(bin_path, default_name);
-        (source, pkg_name)
-    })?;
+    db.backfill_uncategorized(|path| categorize_by_path(&config, path))?;
+
+    // An edited config may have changed the rules for everything already stored
+    let stamp = config_stamp(&config);
+    if stamp.is_some() && db.get_meta(CONFIG_STAMP_KEY)? != stamp {
+        recategorize_binaries(db, &config, &binaries)?;
+    }
 
     Ok(())
 }
 
+/// `meta` key holding the config stamp as of the last full re-categorization
+const CONFIG_STAMP_KEY: &str = "config_hash";
+
+/// Hash of the settings that decide categorization, `[[sources]]` and `[scan]`,
+/// includes merged in, so editing an included file counts as a change too.
+/// Other sections don't touch it. A toolchain with a different hasher only
+/// costs one extra re-categorization.
+fn config_stamp(config: &config::Config) -> Option<String> {
+    use std::hash::{Hash, Hasher};
+    let effective = serde_json::to_string(&(&config.sources, &config.scan)).ok()?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    effective.hash(&mut hasher);
+    Some(format!("{:016x}", hasher.finish()))
+}
+
+/// Source and package name for a path by the config's path rules alone
+fn categorize_by_path(config: &config::Config, path: &str) -> (String, String) {
+    let source = config.categorize_path(path);
+    let bin_path = std::path::Path::new(path);
+    let default_name = bin_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    let pkg_name = crate::package::get_package_name(bin_path, default_name);
+    (source, pkg_name)
+}
+
+/// Re-derive source and package_name for every stored binary under the
+/// current config: what `scanned` (a PATH scan) found for binaries on PATH,
+/// the path rules for the rest. Records the config's stamp so the next sync
+/// doesn't repeat it. Returns how many binaries changed.
+pub fn recategorize_binaries(
+    db: &Database,
+    config: &config::Config,
+    scanned: &[BinaryScanResult],
+) -> Result<u64> {
+    let by_path: HashMap<&str, (&str, &str)> = scanned
+        .iter()
+        .map(|(path, pkg_name, source, _)| (path.as_str(), (source.as_str(), pkg_name.as_str())))
+        .collect();
+    let changed = db.recategorize_all(|path| match by_path.get(path) {
+        Some(&(source, pkg_name)) => (source.to_string(), pkg_name.to_string()),
+        None => categorize_by_path(config, path),
+    })?;
+    if let Some(stamp) = config_stamp(config) {
+        db.set_meta(CONFIG_STAMP_KEY, &stamp)?;
+    }
+    Ok(changed)
+}
+
+/// Usage status of a binary or package in `report` and `why`: `new` when never
+/// run and first seen within `grace_days`, then `dusty`, `low` (under 5 uses)
+/// or `active`
+pub fn usage_status(uses: i64, first_seen: Option<i64>, now: i64, grace_days: u32) -> &'static str {
+    let grace_start = now - grace_days as i64 * 24 * 60 * 60;
+    if uses == 0 && first_seen.is_some_and(|ts| ts >= grace_start) {
+        "new"
+    } else if uses == 0 {
+        "dusty"
+    } else if uses < 5 {
+        "low"
+    } else {
+        "active"
+    }
+}
+
+/// Turn a `--binary` style argument into a tracked path. Anything containing
+/// a `/` is taken as a path; a bare name is looked up by file name among the
+/// tracked binaries. Several matches prompt on a terminal and error otherwise.
+pub fn resolve_binary_arg(db: &Database, arg: &str) -> Result<String> {
+    if arg.contains('/') {
+        return Ok(arg.to_string());
+    }
+    resolve_binary_name(&db.get_all_binaries()?, arg)
+}
+
+/// The path of the one binary in `binaries` whose file name is `name`,
+/// prompting on a terminal when there are several (unless `--output` is set)
+pub fn resolve_binary_name(binaries: &[BinaryRecord], name: &str) -> Result<String> {
+    let interactive = console::Term::stderr().is_term()
+        && std::io::IsTerminal::is_terminal(&std::io::stdin())
+        && !crate::ui::stdout_redirected();
+    choose_binary(binaries, name, interactive)
+}
+
+/// [`resolve_binary_name`], prompting among several matches only when
+/// `interactive`
+fn choose_binary(binaries: &[BinaryRecord], name: &str, interactive: bool) -> Result<String> {
+    let mut matches: Vec<String> = binaries
+        .iter()
+        .filter(|b| {
+            std::path::Path::new(&b.path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                == Some(name)
+        })
+        .map(|b| b.path.clone())
+        .collect();
+    matches.sort();
+
+    match matches.len() {
+        0 => anyhow::bail!(
+            "no tracked binary named '{}' (pass a full path to analyze an untracked one)",
+            name
+        ),
+        1 => Ok(matches.remove(0)),
+        _ if interactive => {
+            let choice = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt(format!("Several binaries are named '{}'", name))
+                .items(&matches)
+                .default(0)
+                .interact()?;
+            Ok(matches.remove(choice))
+        }
+        _ => anyhow::bail!(
+            "'{}' matches {} tracked binaries; pass the full path:\n  {}",
+            name,
+            matches.len(),
+            matches.join("\n  ")
+        ),
+    }
+}
+
 /// Detect install root directories from a set of binary paths.
 /// e.g. ["/opt/anaconda3/bin/python", "/opt/anaconda3/bin/conda"] -> ["/opt/anaconda3"]
 /// Walks up from each binary path to find a reasonable root (one level below
@@ -127,6 +332,11 @@ pub fn detect_install_roots(paths: &[&str]) -> Vec<String> {
 
     let mut roots = BTreeSet::new();
     for path in paths {
+        // A `go install` target like ~/go/bin also holds the module cache;
+        // its binaries are single files with no root of their own
+        if crate::package::is_go_install(path) {
+            continue;
+        }
         // Try to match an anchor
         for anchor in &anchors {
             if path.starts_with(anchor.as_str()) {
@@ -144,3 +354,237 @@ pub fn detect_install_roots(paths: &[&str]) -> Vec<String> {
 
     roots.into_iter().collect()
 }
+
+/// Run a single `du -sk` over `paths` and return bytes per path.
+/// `du` is killed once `du_timeout_secs` elapses (e.g. a stale network mount);
+/// paths it hadn't reported by then are simply absent from the result.
+pub fn du_sizes(paths: &[&str], size_config: &config::SizeConfig) -> HashMap<String, u64> {
+    let mut result = HashMap::new();
+    if paths.is_empty() {
+        return result;
+    }
+
+    let _timer = Timer::start("du");
+    let mut cmd = Command::new("du");
+    cmd.arg("-sk");
+    if size_config.apparent_size {
+        cmd.arg(defaults::DU_APPARENT_FLAG);
+    }
+    let Ok(mut child) = cmd
+        .args(paths)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return result;
+    };
+
+    // Read lines on a separate thread so a hung `du` can't block us:
+    // du prints each path as soon as it's done, so partial output is still useful.
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        std::thread::spawn(move || {
+            let reader = std::io::BufReader::new(stdout);
+            for line in reader.lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let deadline = (size_config.du_timeout_secs > 0)
+        .then(|| Instant::now() + Duration::from_secs(size_config.du_timeout_secs));
+
+    loop {
+        let line = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                rx.recv_timeout(remaining)
+            }
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match line {
+            Ok(line) => {
+                if let Some((size_str, path)) = line.split_once(char::is_whitespace)
+                    && let Ok(kb) = size_str.parse::<u64>()
+                {
+                    result.insert(path.trim_start().to_string(), kb * 1024);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                // Don't wait() here: a process stuck on a dead mount may not die
+                child.kill().ok();
+                break;
+            }
+        }
+    }
+    child.try_wait().ok();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_stamp_follows_categorization_inputs() {
+        let config = config::Config::default();
+        let stamp = config_stamp(&config);
+        assert!(stamp.is_some());
+        assert_eq!(stamp, config_stamp(&config.clone()));
+
+        // What an include merges in changes the stamp just like a local edit
+        let mut included = config.clone();
+        included.scan.extra_dirs.push("/opt/team/bin".to_string());
+        assert_ne!(stamp, config_stamp(&included));
+
+        let mut renamed = config.clone();
+        renamed.sources[0].name = "renamed".to_string();
+        assert_ne!(stamp, config_stamp(&renamed));
+
+        // Display and history settings don't decide categorization
+        let mut unrelated = config.clone();
+        unrelated.display.timezone = Some("UTC".to_string());
+        unrelated.history.half_life_days += 1.0;
+        assert_eq!(stamp, config_stamp(&unrelated));
+    }
+
+    #[test]
+    fn test_recategorize_binaries() {
+        let dir = std::env::temp_dir().join(format!("dusty-recategorize-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = Database::open_at(&dir.join("dusty.db")).unwrap();
+        let mut config = config::Config::default();
+        // One binary already where the default rules put it, one a new source claims
+        let settled = "/opt/elsewhere/bin/settled";
+        let (default_source, default_pkg) = categorize_by_path(&config, settled);
+        db.register_binary(settled, &default_pkg, &default_source)
+            .unwrap();
+        db.register_binary("/opt/tools/bin/mytool", "mytool", "other")
+            .unwrap();
+        config.sources.insert(
+            0,
+            config::SourceDef {
+                name: "tools".to_string(),
+                path: "/opt/tools/".to_string(),
+                uninstall_cmd: None,
+                list_cmd: None,
+                read_only: false,
+                scope: None,
+                ignore_binaries: Vec::new(),
+            },
+        );
+
+        assert_eq!(recategorize_binaries(&db, &config, &[]).unwrap(), 1);
+        let sources: HashMap<String, Option<String>> = db
+            .get_all_binaries()
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.path, b.source))
+            .collect();
+        assert_eq!(sources["/opt/tools/bin/mytool"].as_deref(), Some("tools"));
+        assert_eq!(sources[settled].as_ref(), Some(&default_source));
+
+        // The stamp is recorded, and a second pass has nothing left to change
+        assert_eq!(
+            db.get_meta(CONFIG_STAMP_KEY).unwrap(),
+            config_stamp(&config)
+        );
+        assert_eq!(recategorize_binaries(&db, &config, &[]).unwrap(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn binary(path: &str) -> BinaryRecord {
+        BinaryRecord {
+            path: path.to_string(),
+            count: 0,
+            first_seen: None,
+            last_seen: None,
+            source: None,
+            package_name: None,
+            observed: false,
+        }
+    }
+
+    #[test]
+    fn test_choose_binary() {
+        let binaries = vec![
+            binary("/usr/local/bin/python"),
+            binary("/home/u/.pyenv/shims/python"),
+            binary("/usr/local/bin/rg"),
+            binary("/usr/local/bin/xrg"),
+        ];
+        assert_eq!(
+            choose_binary(&binaries, "rg", false).unwrap(),
+            "/usr/local/bin/rg"
+        );
+        assert!(choose_binary(&binaries, "fd", false).is_err());
+
+        // Several matches without a terminal to prompt on: list them, sorted
+        let err = choose_binary(&binaries, "python", false)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "'python' matches 2 tracked binaries; pass the full path:\n  /home/u/.pyenv/shims/python\n  /usr/local/bin/python"
+        );
+    }
+
+    #[test]
+    fn test_timestamps_in_display_timezone() {
+        use config::DisplayTimezone;
+
+        // 2025-01-31 14:05:00 UTC, and a day in northern summer
+        let winter = 1_738_332_300;
+        let summer = 1_751_378_700;
+        let new_york = DisplayTimezone::parse("America/New_York").unwrap();
+        let tokyo = DisplayTimezone::parse("Asia/Tokyo").unwrap();
+
+        assert_eq!(
+            json_timestamp_in(DisplayTimezone::UTC, winter),
+            "2025-01-31T14:05:00Z"
+        );
+        assert_eq!(
+            json_timestamp_in(tokyo, winter),
+            "2025-01-31T23:05:00+09:00"
+        );
+        assert_eq!(
+            json_timestamp_in(new_york, winter),
+            "2025-01-31T09:05:00-05:00"
+        );
+        assert_eq!(
+            json_timestamp_in(new_york, summer),
+            "2025-07-01T10:05:00-04:00"
+        );
+        assert_eq!(
+            datetime_in(tokyo, winter)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2025-01-31 23:05"
+        );
+
+        // Local time keeps the table format in structured output
+        assert_eq!(
+            json_timestamp_in(DisplayTimezone::Local, winter),
+            datetime_in(DisplayTimezone::Local, winter)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_usage_status() {
+        const DAY: i64 = 24 * 60 * 60;
+        let now = 100 * DAY;
+        assert_eq!(usage_status(0, Some(now - 2 * DAY), now, 7), "new");
+        assert_eq!(usage_status(0, Some(now - 8 * DAY), now, 7), "dusty");
+        assert_eq!(usage_status(0, None, now, 7), "dusty");
+        assert_eq!(usage_status(0, Some(now), now, 0), "new");
+        assert_eq!(usage_status(4, Some(now), now, 7), "low");
+        assert_eq!(usage_status(5, Some(now - 8 * DAY), now, 7), "active");
+    }
+}